@@ -0,0 +1,328 @@
+//! Export archives of the history and settings.
+//!
+//! An archive is a JSON Lines file. The first line is a header naming the format and
+//! its version, and every following line is a record tagged by `type`:
+//! - `config`: the `AppConfig`
+//! - `image`: PNG data, base64-encoded and keyed by its content hash
+//! - `item`: a history item, referring to its image by key
+//! - `folder`: a snippet folder
+//! - `snippet`: a snippet, referring to its folder by name
+//!
+//! Images are written before the items that use them, and folders before the snippets. Record types this version does
+//! not know are skipped, so later versions can add records without breaking it.
+
+use base64::{engine::general_purpose, Engine};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use crate::libs::constants::{ARCHIVE_FORMAT, ARCHIVE_VERSION};
+use crate::libs::database::DatabaseManager;
+use crate::libs::error::ClipRayError;
+use crate::libs::hash::content_hash;
+use crate::libs::types::{AppConfig, ContentType, Selection, SourceApp};
+
+/// History item with everything needed to recreate it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchivedItem {
+    pub content_type: ContentType,
+    pub content: String,
+    /// Formats stored next to the item's own content
+    pub formats: BTreeMap<ContentType, String>,
+    /// PNG data, for image items and items copied with an image
+    pub image: Option<Vec<u8>>,
+    pub timestamp: String,
+    pub last_used: String,
+    pub copy_count: u32,
+    pub pinned: bool,
+    /// Tag names
+    pub tags: Vec<String>,
+    pub selection: Selection,
+    pub source_app: Option<SourceApp>,
+}
+
+/// Snippet with everything needed to recreate it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedSnippet {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abbreviation: Option<String>,
+    pub content: String,
+    /// Folder name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Contents of an archive
+#[derive(Debug)]
+pub struct Archive {
+    pub config: Option<AppConfig>,
+    pub items: Vec<ArchivedItem>,
+    /// Snippet folder names, including empty folders
+    pub folders: Vec<String>,
+    pub snippets: Vec<ArchivedSnippet>,
+}
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Io(io::Error),
+    Database(ClipRayError),
+    /// The file does not start with an archive header
+    NotAnArchive,
+    /// The archive was written by a newer version of the application
+    VersionTooNew {
+        found: u32,
+        supported: u32,
+    },
+    InvalidRecord {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::Io(e) => write!(f, "Archive file error: {}", e),
+            ArchiveError::Database(e) => write!(f, "{}", e),
+            ArchiveError::NotAnArchive => write!(f, "The file is not a Clipray export"),
+            ArchiveError::VersionTooNew { found, supported } => write!(
+                f,
+                "Export version {} is newer than the supported version {}. Please update Clipray.",
+                found, supported
+            ),
+            ArchiveError::InvalidRecord { line, message } => {
+                write!(f, "Invalid export at line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<io::Error> for ArchiveError {
+    fn from(e: io::Error) -> Self {
+        ArchiveError::Io(e)
+    }
+}
+
+impl From<ClipRayError> for ArchiveError {
+    fn from(e: ClipRayError) -> Self {
+        ArchiveError::Database(e)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Record {
+    Header {
+        format: String,
+        version: u32,
+        app_version: String,
+        exported_at: String,
+    },
+    Config {
+        config: AppConfig,
+    },
+    Image {
+        key: String,
+        data: String,
+    },
+    Item(ItemRecord),
+    Folder {
+        name: String,
+    },
+    Snippet(ArchivedSnippet),
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ItemRecord {
+    content_type: ContentType,
+    #[serde(default)]
+    content: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    formats: BTreeMap<ContentType, String>,
+    /// Key of an image record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    timestamp: String,
+    last_used: String,
+    #[serde(default = "default_copy_count")]
+    copy_count: u32,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default)]
+    selection: Selection,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_app: Option<SourceApp>,
+}
+
+fn default_copy_count() -> u32 {
+    1
+}
+
+/// Write the settings, history and snippets to `writer`. Returns the number of items written.
+pub fn write_archive<W: Write>(db: &DatabaseManager, writer: W) -> Result<usize, ArchiveError> {
+    let config = db.get_config()?;
+    let items = db.export_items()?;
+    let folders = db.list_snippet_folders()?;
+    let snippets = db.export_snippets()?;
+    let mut writer = io::BufWriter::new(writer);
+
+    write_record(
+        &mut writer,
+        &Record::Header {
+            format: ARCHIVE_FORMAT.to_string(),
+            version: ARCHIVE_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: Utc::now().to_rfc3339(),
+        },
+    )?;
+    write_record(&mut writer, &Record::Config { config })?;
+
+    // Each image is written once, even when several items use it
+    let mut written_images = HashSet::new();
+    for item in &items {
+        let image = match &item.image {
+            Some(png) => {
+                let key = content_hash(png);
+                if written_images.insert(key.clone()) {
+                    write_record(
+                        &mut writer,
+                        &Record::Image {
+                            key: key.clone(),
+                            data: general_purpose::STANDARD.encode(png),
+                        },
+                    )?;
+                }
+                Some(key)
+            }
+            None => None,
+        };
+        write_record(
+            &mut writer,
+            &Record::Item(ItemRecord {
+                content_type: item.content_type,
+                content: item.content.clone(),
+                formats: item.formats.clone(),
+                image,
+                timestamp: item.timestamp.clone(),
+                last_used: item.last_used.clone(),
+                copy_count: item.copy_count,
+                pinned: item.pinned,
+                tags: item.tags.clone(),
+                selection: item.selection,
+                source_app: item.source_app.clone(),
+            }),
+        )?;
+    }
+    for folder in folders {
+        write_record(&mut writer, &Record::Folder { name: folder.name })?;
+    }
+    for snippet in snippets {
+        write_record(&mut writer, &Record::Snippet(snippet))?;
+    }
+
+    writer.flush()?;
+    Ok(items.len())
+}
+
+/// Read an archive written by `write_archive`
+pub fn read_archive<R: BufRead>(reader: R) -> Result<Archive, ArchiveError> {
+    let mut archive = Archive {
+        config: None,
+        items: Vec::new(),
+        folders: Vec::new(),
+        snippets: Vec::new(),
+    };
+    let mut images: HashMap<String, Vec<u8>> = HashMap::new();
+    let mut has_header = false;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |message: String| ArchiveError::InvalidRecord {
+            line: number,
+            message,
+        };
+
+        let record: Result<Record, _> = serde_json::from_str(&line);
+        if !has_header {
+            match record {
+                Ok(Record::Header {
+                    format, version, ..
+                }) if format == ARCHIVE_FORMAT => {
+                    if version > ARCHIVE_VERSION {
+                        return Err(ArchiveError::VersionTooNew {
+                            found: version,
+                            supported: ARCHIVE_VERSION,
+                        });
+                    }
+                    has_header = true;
+                    continue;
+                }
+                _ => return Err(ArchiveError::NotAnArchive),
+            }
+        }
+
+        match record.map_err(|e| invalid(e.to_string()))? {
+            Record::Header { .. } => return Err(invalid("Unexpected header".to_string())),
+            Record::Config { config } => archive.config = Some(config),
+            Record::Image { key, data } => {
+                let png = general_purpose::STANDARD
+                    .decode(data)
+                    .map_err(|e| invalid(format!("Invalid image data: {}", e)))?;
+                images.insert(key, png);
+            }
+            Record::Item(item) => {
+                let image = match &item.image {
+                    Some(key) => Some(
+                        images
+                            .get(key)
+                            .cloned()
+                            .ok_or_else(|| invalid(format!("Unknown image '{}'", key)))?,
+                    ),
+                    None => None,
+                };
+                archive.items.push(ArchivedItem {
+                    content_type: item.content_type,
+                    content: item.content,
+                    formats: item.formats,
+                    image,
+                    timestamp: item.timestamp,
+                    last_used: item.last_used,
+                    copy_count: item.copy_count,
+                    pinned: item.pinned,
+                    tags: item.tags,
+                    selection: item.selection,
+                    source_app: item.source_app,
+                });
+            }
+            Record::Folder { name } => archive.folders.push(name),
+            Record::Snippet(snippet) => archive.snippets.push(snippet),
+            Record::Unknown => {}
+        }
+    }
+
+    if !has_header {
+        return Err(ArchiveError::NotAnArchive);
+    }
+    Ok(archive)
+}
+
+fn write_record<W: Write>(writer: &mut W, record: &Record) -> Result<(), ArchiveError> {
+    serde_json::to_writer(&mut *writer, record).map_err(io::Error::from)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
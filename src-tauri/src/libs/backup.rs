@@ -0,0 +1,236 @@
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use rusqlite::ErrorCode;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::libs::constants::{
+    BACKUP_CHECK_INTERVAL_SECS, BACKUP_DIR_NAME, BACKUP_FILE_PREFIX, BACKUP_INTERVAL_HOURS,
+    BACKUP_KEEP_COUNT, BACKUP_TIME_FORMAT,
+};
+use crate::libs::database::DatabaseManager;
+use crate::libs::encryption::open_database_file;
+use crate::libs::migrations::MigrationError;
+use crate::libs::types::RecoveryNotice;
+
+/// Recovery done at startup, kept until the window has shown it
+#[derive(Default)]
+pub struct RecoveryState(Mutex<Option<RecoveryNotice>>);
+
+impl RecoveryState {
+    pub fn set(&self, notice: RecoveryNotice) {
+        *self.0.lock().unwrap() = Some(notice);
+    }
+
+    pub fn take(&self) -> Option<RecoveryNotice> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+/// Take a backup whenever the latest one is older than BACKUP_INTERVAL_HOURS
+pub async fn run_backups(app_handle: AppHandle<Wry>) {
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(BACKUP_CHECK_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        // The database is not available until an encrypted one is unlocked
+        if let Some(db) = app_handle.try_state::<DatabaseManager>() {
+            let Some(db_path) = db.path() else { continue };
+            if is_backup_due(&db_path, Utc::now()) {
+                if let Err(e) = create_backup(&db, Utc::now()) {
+                    eprintln!("Failed to back up the database: {}", e);
+                }
+            }
+        }
+    }
+}
+
+pub fn backup_dir(db_path: &Path) -> PathBuf {
+    db_path.with_file_name(BACKUP_DIR_NAME)
+}
+
+/// Backups of the database at `db_path`, newest first
+pub fn list_backups(db_path: &Path) -> io::Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(backup_dir(db_path)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut backups = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if backup_time(&path).is_some() {
+            backups.push(path);
+        }
+    }
+    // Names start with the time the backup was taken
+    backups.sort_by(|a, b| b.cmp(a));
+    Ok(backups)
+}
+
+/// When a backup was taken, from its file name
+pub fn backup_time(path: &Path) -> Option<DateTime<Utc>> {
+    let stamp = path
+        .file_name()?
+        .to_str()?
+        .strip_prefix(BACKUP_FILE_PREFIX)?
+        .strip_suffix(".db")?;
+    NaiveDateTime::parse_from_str(stamp, BACKUP_TIME_FORMAT)
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+pub fn is_backup_due(db_path: &Path, now: DateTime<Utc>) -> bool {
+    let latest = list_backups(db_path)
+        .ok()
+        .and_then(|backups| backups.first().and_then(|path| backup_time(path)));
+    match latest {
+        Some(time) => now - time >= Duration::hours(BACKUP_INTERVAL_HOURS),
+        None => true,
+    }
+}
+
+/// Back up the database into the backup directory, then delete the oldest backups
+/// beyond BACKUP_KEEP_COUNT. Returns the path of the new backup.
+pub fn create_backup(db: &DatabaseManager, now: DateTime<Utc>) -> Result<PathBuf, MigrationError> {
+    let db_path = db.path().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "In-memory databases cannot be backed up",
+        )
+    })?;
+    let dir = backup_dir(&db_path);
+    fs::create_dir_all(&dir)?;
+
+    let path = dir.join(format!(
+        "{}{}.db",
+        BACKUP_FILE_PREFIX,
+        now.format(BACKUP_TIME_FORMAT)
+    ));
+    // Written under another name first, so a partial backup is never restored
+    let partial = path.with_extension("db.partial");
+    let _ = fs::remove_file(&partial);
+    if let Err(e) = db.backup_to(&partial) {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, &path)?;
+
+    // The new backup is in place; an old one that cannot be deleted is tried again next time
+    match list_backups(&db_path) {
+        Ok(backups) => {
+            for old in backups.into_iter().skip(BACKUP_KEEP_COUNT) {
+                if let Err(e) = fs::remove_file(&old) {
+                    eprintln!("Failed to delete old backup {}: {}", old.display(), e);
+                }
+            }
+        }
+        Err(e) => eprintln!("Failed to list old backups: {}", e),
+    }
+    Ok(path)
+}
+
+/// Delete every backup of the database at `db_path`. Used when the encryption changes,
+/// since older backups keep the previous key, or no encryption at all.
+pub fn delete_backups(db_path: &Path) -> io::Result<()> {
+    for old in list_backups(db_path)? {
+        fs::remove_file(old)?;
+    }
+    Ok(())
+}
+
+/// Open the history database like `open_database`, and check its integrity.
+/// A damaged database is moved aside and replaced by the newest backup that opens
+/// and passes the check, or by a new database when no backup does; the returned
+/// notice tells the user what happened.
+pub fn open_or_recover(
+    db_path: &Path,
+    passphrase: Option<&str>,
+) -> Result<(DatabaseManager, Option<RecoveryNotice>), MigrationError> {
+    // A locked database is never replaced: it is most likely encrypted, and a backup
+    // that opens without the key would be an older plaintext copy
+    let reason = match open_checked(db_path, db_path, passphrase) {
+        Ok(db) => return Ok((db, None)),
+        Err(e) if is_damaged(&e) => e.to_string(),
+        Err(e) => return Err(e),
+    };
+
+    let staging = db_path.with_extension("db.restore");
+    let mut restored = None;
+    for backup in list_backups(db_path)? {
+        fs::copy(&backup, &staging)?;
+        match open_checked(&staging, db_path, passphrase) {
+            Ok(db) => {
+                drop(db);
+                restored = Some(backup);
+                break;
+            }
+            Err(e) => eprintln!("Skipping backup {}: {}", backup.display(), e),
+        }
+    }
+    if restored.is_none() {
+        let _ = fs::remove_file(&staging);
+    }
+
+    let damaged_copy = move_aside(db_path, "damaged", Utc::now())?;
+    if restored.is_some() {
+        fs::rename(&staging, db_path)?;
+    }
+    let db = open_checked(db_path, db_path, passphrase)?;
+
+    eprintln!(
+        "The database was damaged ({}) and was moved to {}",
+        reason,
+        damaged_copy.display()
+    );
+    let notice = RecoveryNotice {
+        reason,
+        backup_time: restored
+            .as_deref()
+            .and_then(backup_time)
+            .map(|time| time.to_rfc3339()),
+        damaged_copy: damaged_copy.display().to_string(),
+    };
+    Ok((db, Some(notice)))
+}
+
+fn open_checked(
+    file: &Path,
+    db_path: &Path,
+    passphrase: Option<&str>,
+) -> Result<DatabaseManager, MigrationError> {
+    let db = open_database_file(file, db_path, passphrase)?;
+    db.check_integrity()?;
+    Ok(db)
+}
+
+fn is_damaged(error: &MigrationError) -> bool {
+    match error {
+        MigrationError::Corrupt(_) => true,
+        MigrationError::Sqlite(rusqlite::Error::SqliteFailure(e, _)) => {
+            matches!(e.code, ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase)
+        }
+        _ => false,
+    }
+}
+
+/// Rename the database, and its rollback journal which must not be applied to
+/// another file, to `<name>.db.<label>-<time>`. Returns the new path of the database.
+pub fn move_aside(db_path: &Path, label: &str, now: DateTime<Utc>) -> io::Result<PathBuf> {
+    let moved = db_path.with_extension(format!("db.{}-{}", label, now.format(BACKUP_TIME_FORMAT)));
+    fs::rename(db_path, &moved)?;
+
+    let journal = journal_path(db_path);
+    if journal.exists() {
+        fs::rename(&journal, journal_path(&moved))?;
+    }
+    Ok(moved)
+}
+
+fn journal_path(db_path: &Path) -> PathBuf {
+    let mut path = db_path.as_os_str().to_owned();
+    path.push("-journal");
+    PathBuf::from(path)
+}
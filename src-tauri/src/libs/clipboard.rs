@@ -1,59 +1,456 @@
-use base64::{engine::general_purpose, Engine};
+use chrono::{Local, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager, Wry};
-use tauri_plugin_clipboard::Clipboard;
+use tokio::sync::Notify;
 
-use crate::libs::constants::CLIPBOARD_MONITOR_INTERVAL_MS;
+use crate::libs::clipboard_backend::{
+    ChangeListener, ClipboardBackend, ClipboardFormats, TauriClipboard,
+};
 use crate::libs::database::DatabaseManager;
+use crate::libs::error::ClipRayError;
+use crate::libs::exclusion::ExclusionFilter;
+use crate::libs::pause::{publish_pause_status, CapturePause};
+use crate::libs::template::{Template, TemplateContext};
+use crate::libs::types::{
+    CaptureDetails, ClipboardSnapshot, ExclusionRules, OwnWritePolicy, Selection, SensitiveRules,
+    SourceApp,
+};
+
+/// Remembers what was last read from the clipboard so each copy is stored once
+#[derive(Default)]
+pub struct ClipboardMonitor {
+    /// Selection the monitored clipboard is, recorded with each item
+    selection: Selection,
+    last_snapshot: Option<ClipboardSnapshot>,
+    /// Change marker of the clipboard when it was last read
+    last_marker: Option<u64>,
+    /// Rules the filter was compiled from, to rebuild it when the config changes
+    exclusion: Option<(ExclusionRules, ExclusionFilter)>,
+    /// Same for the rules marking content as sensitive
+    sensitive: Option<(ExclusionRules, ExclusionFilter)>,
+    /// Set while capture is paused; the next poll only takes a baseline
+    resync: bool,
+}
+
+impl ClipboardMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Monitor for the primary selection or another clipboard than the default one
+    pub fn for_selection(selection: Selection) -> Self {
+        Self {
+            selection,
+            ..Self::default()
+        }
+    }
+
+    /// Read the clipboard and store anything new that is not excluded.
+    /// Sensitive content is stored with an expiry time.
+    /// Returns true when the history changed.
+    pub fn poll(&mut self, clipboard: &dyn ClipboardBackend, db: &DatabaseManager) -> bool {
+        self.poll_with(clipboard, db, &OwnWrites::default())
+    }
+
+    /// Like `poll`, but items written by `own_writes` are handled by the
+    /// `own_writes` policy of the capture settings instead of being captured.
+    pub fn poll_with(
+        &mut self,
+        clipboard: &dyn ClipboardBackend,
+        db: &DatabaseManager,
+        own_writes: &OwnWrites,
+    ) -> bool {
+        // Held while reading, so a write cannot land between the marker and the contents
+        let mut pending = own_writes.0.lock().unwrap();
+        let marker = clipboard.change_marker();
+        if marker.is_some() && marker == self.last_marker && !self.resync {
+            return false;
+        }
+        self.last_marker = marker;
+
+        let exclusion_rules = db.get_exclusion_rules().unwrap_or_else(|e| {
+            eprintln!("Failed to load exclusion rules: {}", e);
+            ExclusionRules::default()
+        });
+        let source_app = clipboard.source_app();
+        let filter = compiled_filter(&mut self.exclusion, exclusion_rules);
+        if let Some(reason) = filter.check_app(source_app.as_ref()) {
+            // Nothing is read while a blacklisted application has the focus. Without a
+            // change marker its copy cannot be told from a later one, so the next poll
+            // only takes a baseline, or it would be recorded when the focus moves on.
+            pending.remove(&self.selection);
+            self.resync = marker.is_none();
+            eprintln!("Clipboard change not recorded: {:?}", reason);
+            return false;
+        }
+
+        // Without format information, try every read and keep what succeeds
+        let formats = clipboard.available_formats().unwrap_or(ClipboardFormats {
+            text: true,
+            html: true,
+            rtf: true,
+            image: true,
+            files: true,
+            concealed: false,
+        });
+        let snapshot = read_snapshot(clipboard, &formats);
+        let own_item = pending
+            .remove(&self.selection)
+            .filter(|write| write.matches(marker, &snapshot))
+            .map(|write| write.item_id);
+        drop(pending);
+        if std::mem::take(&mut self.resync) {
+            self.last_snapshot = Some(snapshot);
+            return false;
+        }
+        if let Some(id) = own_item {
+            self.last_snapshot = Some(snapshot);
+            return match db.get_capture_settings().unwrap_or_default().own_writes {
+                OwnWritePolicy::Ignore => false,
+                OwnWritePolicy::MoveToTop => db.mark_item_used(id).unwrap_or_else(|e| {
+                    eprintln!("Failed to move item {} to the top: {}", id, e);
+                    false
+                }),
+            };
+        }
+        // After a change of the marker the same content is copied again, which counts
+        // as a use; without a marker it only means the clipboard has not changed
+        let unchanged = marker.is_none() && self.last_snapshot.as_ref() == Some(&snapshot);
+        if snapshot.is_empty() || unchanged {
+            return false;
+        }
+
+        let result = match filter.check(&snapshot, formats.concealed) {
+            Some(reason) => {
+                // Only the reason is logged; the content may be a secret
+                eprintln!("Clipboard change not recorded: {:?}", reason);
+                None
+            }
+            None => {
+                let sensitive_rules = db.get_sensitive_rules().unwrap_or_else(|e| {
+                    eprintln!("Failed to load sensitive item rules: {}", e);
+                    SensitiveRules::default()
+                });
+                let expires_at =
+                    compiled_filter(&mut self.sensitive, sensitive_rules.matching_rules())
+                        .check(&snapshot, false)
+                        .map(|_| {
+                            Utc::now()
+                                + chrono::Duration::seconds(
+                                    sensitive_rules.expire_after_secs.into(),
+                                )
+                        });
+                Some(db.add_captured_snapshot(
+                    &snapshot,
+                    &CaptureDetails {
+                        selection: self.selection,
+                        source_app,
+                        expires_at,
+                    },
+                ))
+            }
+        };
+        // Excluded copies are remembered too, so they are not checked again
+        self.last_snapshot = Some(snapshot);
+        match result {
+            Some(Ok(())) => true,
+            Some(Err(e)) => {
+                eprintln!("Failed to store clipboard contents: {}", e);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Called instead of `poll` while capture is paused. The clipboard is not read;
+    /// the first poll after resuming records what is on the clipboard as already seen,
+    /// so nothing copied during the pause is stored.
+    pub fn pause(&mut self) {
+        self.resync = true;
+    }
+}
+
+/// Items Clipray wrote to the clipboard itself, per selection, until its monitor
+/// sees the change
+#[derive(Default)]
+pub struct OwnWrites(Mutex<HashMap<Selection, OwnWrite>>);
+
+struct OwnWrite {
+    item_id: i64,
+    snapshot: ClipboardSnapshot,
+    /// Change marker the clipboard has once the write was seen
+    marker: Option<u64>,
+}
+
+impl OwnWrite {
+    // The marker is exact; the contents may come back changed, e.g. with HTML the
+    // platform rewrote, but still recognise writes to backends without markers
+    fn matches(&self, marker: Option<u64>, snapshot: &ClipboardSnapshot) -> bool {
+        (self.marker.is_some() && self.marker == marker) || &self.snapshot == snapshot
+    }
+}
+
+/// Writes to `inner` and records them in `own_writes`. Writes of something other
+/// than a history item (`item_id` is `None`) only forget the previous record.
+pub struct OwnWriteClipboard<'a> {
+    pub inner: &'a dyn ClipboardBackend,
+    pub selection: Selection,
+    pub item_id: Option<i64>,
+    pub own_writes: &'a OwnWrites,
+}
+
+impl ClipboardBackend for OwnWriteClipboard<'_> {
+    fn read_text(&self) -> Result<String, String> {
+        self.inner.read_text()
+    }
+
+    fn read_image(&self) -> Result<Vec<u8>, String> {
+        self.inner.read_image()
+    }
+
+    fn read_html(&self) -> Result<String, String> {
+        self.inner.read_html()
+    }
+
+    fn read_rtf(&self) -> Result<String, String> {
+        self.inner.read_rtf()
+    }
+
+    fn read_files(&self) -> Result<Vec<String>, String> {
+        self.inner.read_files()
+    }
+
+    fn available_formats(&self) -> Result<ClipboardFormats, String> {
+        self.inner.available_formats()
+    }
+
+    fn write(&self, snapshot: ClipboardSnapshot) -> Result<(), String> {
+        let mut pending = self.own_writes.0.lock().unwrap();
+        pending.remove(&self.selection);
+        let before = self.inner.change_marker();
+        self.inner.write(snapshot.clone())?;
+        if let Some(item_id) = self.item_id {
+            // Counters kept by listeners on other threads only see the write later;
+            // it is then the next change after the current one
+            let marker = match (before, self.inner.change_marker()) {
+                (Some(before), Some(after)) if after == before => Some(before.wrapping_add(1)),
+                (_, after) => after,
+            };
+            let write = OwnWrite {
+                item_id,
+                snapshot,
+                marker,
+            };
+            pending.insert(self.selection, write);
+        }
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        self.inner.clear()
+    }
+
+    fn watch(&self, listener: ChangeListener) -> Result<bool, String> {
+        self.inner.watch(listener)
+    }
+
+    fn change_marker(&self) -> Option<u64> {
+        self.inner.change_marker()
+    }
+
+    fn source_app(&self) -> Option<SourceApp> {
+        self.inner.source_app()
+    }
+}
+
+// Filter for `rules`, compiled again only when the rules changed
+fn compiled_filter(
+    cache: &mut Option<(ExclusionRules, ExclusionFilter)>,
+    rules: ExclusionRules,
+) -> &ExclusionFilter {
+    if cache.as_ref().map(|(current, _)| current) != Some(&rules) {
+        // Rules can come from an import or an edited config that was never validated;
+        // broken entries are skipped and the rest still apply
+        let (filter, errors) = ExclusionFilter::compile(&rules);
+        for error in errors {
+            eprintln!("{}, skipped", error);
+        }
+        *cache = Some((rules, filter));
+    }
+
+    &cache.as_ref().expect("filter was just built").1
+}
+
+/// Read every format listed in `formats`. Empty values are skipped.
+pub fn read_snapshot(
+    clipboard: &dyn ClipboardBackend,
+    formats: &ClipboardFormats,
+) -> ClipboardSnapshot {
+    ClipboardSnapshot {
+        text: formats
+            .text
+            .then(|| clipboard.read_text().ok())
+            .flatten()
+            .filter(|text| !text.is_empty()),
+        html: formats
+            .html
+            .then(|| clipboard.read_html().ok())
+            .flatten()
+            .filter(|html| !html.is_empty()),
+        rtf: formats
+            .rtf
+            .then(|| clipboard.read_rtf().ok())
+            .flatten()
+            .filter(|rtf| !rtf.is_empty()),
+        image: formats
+            .image
+            .then(|| clipboard.read_image().ok())
+            .flatten()
+            .filter(|image| !image.is_empty()),
+        files: formats
+            .files
+            .then(|| clipboard.read_files().ok())
+            .flatten()
+            .filter(|files| !files.is_empty()),
+    }
+}
 
 pub async fn monitor_clipboard(app_handle: AppHandle<Wry>) {
-    let mut last_content = String::new();
-    let mut last_image_content: Option<String> = None;
+    let clipboard = TauriClipboard::new(app_handle.clone());
+    run_monitor(&app_handle, &clipboard, Selection::Clipboard).await;
+}
+
+/// Capture the primary selection while it is enabled in the capture settings
+pub async fn monitor_primary_selection(
+    app_handle: AppHandle<Wry>,
+    selection: Arc<dyn ClipboardBackend>,
+) {
+    run_monitor(&app_handle, selection.as_ref(), Selection::Primary).await;
+}
+
+async fn run_monitor(
+    app_handle: &AppHandle<Wry>,
+    clipboard: &dyn ClipboardBackend,
+    selection: Selection,
+) {
+    // Wake up on change notifications where available, poll otherwise
+    let changed = Arc::new(Notify::new());
+    let notify = changed.clone();
+    let watching = clipboard
+        .watch(Box::new(move || notify.notify_one()))
+        .unwrap_or_else(|e| {
+            eprintln!("Clipboard change notifications unavailable, polling: {}", e);
+            false
+        });
+
+    let pause = app_handle.state::<CapturePause>();
+    let subscription = (selection != Selection::Clipboard).then(|| pause.subscribe());
+    let mut monitor = ClipboardMonitor::for_selection(selection);
     loop {
-        tokio::time::sleep(Duration::from_millis(CLIPBOARD_MONITOR_INTERVAL_MS)).await;
-        if let Some(window) = app_handle.get_webview_window("main") {
-            let clipboard = window.state::<Clipboard>();
-            // Check for text changes
-            if let Ok(current_content) = clipboard.read_text() {
-                if current_content != last_content && !current_content.is_empty() {
-                    last_content = current_content.clone();
-                    if let Some(db) = app_handle.try_state::<DatabaseManager>() {
-                        let _ = db.add_clipboard_item(&current_content, "text");
-                        let _ = window.emit("clipboard-updated", ());
-                    }
-                }
+        // Read every time, so changed settings apply from the next check
+        let settings = app_handle
+            .try_state::<DatabaseManager>()
+            .and_then(|db| db.get_capture_settings().ok())
+            .unwrap_or_default();
+        let enabled = selection == Selection::Clipboard || settings.primary_selection;
+
+        if selection == Selection::Clipboard && pause.expire(Utc::now()) {
+            publish_pause_status(app_handle, &pause.status());
+        }
+
+        if pause.is_paused() {
+            monitor.pause();
+        } else if !enabled {
+            // Whatever is selected when it gets enabled is not captured
+            monitor.pause();
+        } else if let Some(db) = app_handle.try_state::<DatabaseManager>() {
+            if monitor.poll_with(clipboard, &db, &app_handle.state::<OwnWrites>()) {
+                let _ = app_handle.emit("clipboard-updated", ());
             }
-            // Check for image changes
-            if let Ok(image_data) = clipboard.read_image_base64() {
-                if Some(&image_data) != last_image_content.as_ref() {
-                    last_image_content = Some(image_data.clone());
-                    let encoded = image_data;
-                    if let Some(db) = app_handle.try_state::<DatabaseManager>() {
-                        let _ = db.add_clipboard_item(&encoded, "image");
-                        let _ = window.emit("clipboard-updated", ());
-                    }
+        }
+
+        // Also wake up when capture is paused or resumed, and when a timed pause ends
+        let resume_in = pause
+            .status()
+            .until
+            .map(|until| (until - Utc::now()).to_std().unwrap_or_default());
+        tokio::select! {
+            _ = changed.notified(), if watching => {
+                // The selection changes with every move of the mouse while selecting;
+                // only the final selection is captured
+                if selection == Selection::Primary && enabled {
+                    wait_until_quiet(&changed, Duration::from_millis(settings.selection_debounce_ms)).await;
                 }
             }
+            _ = tokio::time::sleep(Duration::from_millis(settings.poll_interval_ms)), if !watching => {}
+            _ = async {
+                match &subscription {
+                    Some(subscription) => subscription.notified().await,
+                    None => pause.changed().await,
+                }
+            } => {}
+            _ = tokio::time::sleep(resume_in.unwrap_or_default()), if resume_in.is_some() => {}
         }
     }
 }
 
+/// Wait until `changed` has not been notified for `quiet`
+pub async fn wait_until_quiet(changed: &Notify, quiet: Duration) {
+    while tokio::time::timeout(quiet, changed.notified())
+        .await
+        .is_ok()
+    {}
+}
+
+/// Write every stored format of an item back to the clipboard
 pub fn copy_to_clipboard_impl(
-    content: String,
-    content_type: String,
-    clipboard: &Clipboard,
-) -> Result<(), String> {
-    if content_type == "text" {
-        clipboard.write_text(content).map_err(|e| e.to_string())?;
-    } else if content_type == "image" {
-        // Decode Base64 and write image to clipboard
-        let image_data = general_purpose::STANDARD
-            .decode(content)
-            .map_err(|e| e.to_string())?;
-        let image_base64 = general_purpose::STANDARD.encode(&image_data);
-        clipboard
-            .write_image_base64(image_base64)
-            .map_err(|e| e.to_string())?;
-    }
-    Ok(())
+    id: i64,
+    db: &DatabaseManager,
+    clipboard: &dyn ClipboardBackend,
+) -> Result<(), ClipRayError> {
+    let snapshot = db
+        .get_item_snapshot(id)?
+        .ok_or_else(|| ClipRayError::not_found("Item", id))?;
+    clipboard.write(snapshot).map_err(ClipRayError::Clipboard)
+}
+
+/// Expand the placeholders of a snippet and write the result to `target` as text.
+/// `fields` holds the values of its fill-in fields. `{clipboard}` is read from
+/// `clipboard`, even when the snippet is written to the primary selection.
+pub fn copy_snippet_impl(
+    id: i64,
+    fields: &HashMap<String, String>,
+    db: &DatabaseManager,
+    clipboard: &dyn ClipboardBackend,
+    target: &dyn ClipboardBackend,
+) -> Result<(), ClipRayError> {
+    let snippet = db
+        .get_snippet(id)?
+        .ok_or_else(|| ClipRayError::not_found("Snippet", id))?;
+    let template = Template::parse(&snippet.content)?;
+
+    let current = clipboard.read_text().ok();
+    let text = template.expand(&TemplateContext {
+        now: Local::now().fixed_offset(),
+        clipboard: current.as_deref(),
+        fields,
+    })?;
+
+    target
+        .write(ClipboardSnapshot {
+            text: Some(text),
+            ..Default::default()
+        })
+        .map_err(ClipRayError::Clipboard)
+}
+
+/// Names of the fill-in fields of a snippet, to ask for before copying it
+pub fn snippet_fields(id: i64, db: &DatabaseManager) -> Result<Vec<String>, ClipRayError> {
+    let snippet = db
+        .get_snippet(id)?
+        .ok_or_else(|| ClipRayError::not_found("Snippet", id))?;
+    let template = Template::parse(&snippet.content)?;
+    Ok(template.fields().into_iter().map(String::from).collect())
 }
@@ -0,0 +1,441 @@
+use clipboard_rs::{common::RustImage, Clipboard as _, ClipboardContent, RustImageData};
+use tauri::{AppHandle, Listener, Manager, Wry};
+use tauri_plugin_clipboard::Clipboard;
+
+use crate::libs::constants::CONCEALED_CLIPBOARD_FORMATS;
+use crate::libs::source_app::active_app;
+use crate::libs::types::{ClipboardSnapshot, SourceApp};
+
+#[cfg(target_os = "linux")]
+use crate::libs::selection::watch_clipboard;
+#[cfg(not(windows))]
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+#[cfg(windows)]
+use windows::Win32::System::DataExchange::GetClipboardSequenceNumber;
+
+#[cfg(all(windows, any(test, feature = "test-utils")))]
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+#[cfg(any(test, feature = "test-utils"))]
+use std::sync::{atomic::AtomicUsize, Mutex};
+
+/// Event emitted by the clipboard plugin's monitor on every clipboard change
+const CLIPBOARD_PLUGIN_UPDATE_EVENT: &str = "plugin:clipboard://clipboard-monitor/update";
+
+/// Called by a backend whenever the clipboard contents change
+pub type ChangeListener = Box<dyn Fn() + Send + Sync>;
+
+/// Formats currently offered on the clipboard
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ClipboardFormats {
+    pub text: bool,
+    pub html: bool,
+    pub rtf: bool,
+    pub image: bool,
+    pub files: bool,
+    /// The copying app asked clipboard managers not to record this content
+    pub concealed: bool,
+}
+
+/// Access to the system clipboard.
+/// Reads fail when the requested format is not on the clipboard.
+pub trait ClipboardBackend: Send + Sync {
+    fn read_text(&self) -> Result<String, String>;
+    /// Image as encoded bytes (PNG for the system clipboard)
+    fn read_image(&self) -> Result<Vec<u8>, String>;
+    fn read_html(&self) -> Result<String, String>;
+    fn read_rtf(&self) -> Result<String, String>;
+    /// File list as URIs
+    fn read_files(&self) -> Result<Vec<String>, String>;
+    fn available_formats(&self) -> Result<ClipboardFormats, String>;
+    /// Replace the clipboard with all formats of the snapshot at once
+    fn write(&self, snapshot: ClipboardSnapshot) -> Result<(), String>;
+    /// Empty the clipboard
+    fn clear(&self) -> Result<(), String>;
+
+    fn write_text(&self, text: String) -> Result<(), String> {
+        self.write(ClipboardSnapshot {
+            text: Some(text),
+            ..Default::default()
+        })
+    }
+
+    fn write_image(&self, png: Vec<u8>) -> Result<(), String> {
+        self.write(ClipboardSnapshot {
+            image: Some(png),
+            ..Default::default()
+        })
+    }
+
+    /// Register a listener for clipboard changes.
+    /// Returns false when the backend cannot notify, in which case callers have to poll.
+    fn watch(&self, listener: ChangeListener) -> Result<bool, String>;
+
+    /// Counter that changes with every copy, also of the same content again.
+    /// While it stays the same the clipboard is not read again.
+    /// `None` when the backend has no such counter; the contents are compared then.
+    fn change_marker(&self) -> Option<u64> {
+        None
+    }
+
+    /// Application the current contents were copied in, where the platform can tell
+    fn source_app(&self) -> Option<SourceApp> {
+        None
+    }
+}
+
+/// System clipboard through `tauri_plugin_clipboard`
+pub struct TauriClipboard {
+    app_handle: AppHandle<Wry>,
+    /// Changes reported to `watch` listeners, the change marker outside Windows
+    #[cfg(not(windows))]
+    changes: Arc<AtomicU64>,
+    #[cfg(not(windows))]
+    watching: AtomicBool,
+}
+
+impl TauriClipboard {
+    pub fn new(app_handle: AppHandle<Wry>) -> Self {
+        Self {
+            app_handle,
+            #[cfg(not(windows))]
+            changes: Arc::default(),
+            #[cfg(not(windows))]
+            watching: AtomicBool::new(false),
+        }
+    }
+
+    fn with_clipboard<T>(
+        &self,
+        f: impl FnOnce(&Clipboard) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let clipboard = self
+            .app_handle
+            .try_state::<Clipboard>()
+            .ok_or("Clipboard plugin is not initialized")?;
+        f(&clipboard)
+    }
+}
+
+impl ClipboardBackend for TauriClipboard {
+    fn read_text(&self) -> Result<String, String> {
+        self.with_clipboard(|clipboard| clipboard.read_text())
+    }
+
+    fn read_image(&self) -> Result<Vec<u8>, String> {
+        self.with_clipboard(|clipboard| clipboard.read_image_binary())
+    }
+
+    fn read_html(&self) -> Result<String, String> {
+        self.with_clipboard(|clipboard| clipboard.read_html())
+    }
+
+    fn read_rtf(&self) -> Result<String, String> {
+        self.with_clipboard(|clipboard| clipboard.read_rtf())
+    }
+
+    fn read_files(&self) -> Result<Vec<String>, String> {
+        self.with_clipboard(|clipboard| clipboard.read_files_uris())
+    }
+
+    fn available_formats(&self) -> Result<ClipboardFormats, String> {
+        self.with_clipboard(|clipboard| {
+            let types = clipboard.available_types()?;
+            let raw_formats = clipboard
+                .clipboard
+                .lock()
+                .map_err(|e| e.to_string())?
+                .available_formats()
+                .map_err(|e| e.to_string())?;
+            Ok(ClipboardFormats {
+                text: types.text,
+                html: types.html,
+                rtf: types.rtf,
+                image: types.image,
+                files: types.files,
+                concealed: raw_formats
+                    .iter()
+                    .any(|format| CONCEALED_CLIPBOARD_FORMATS.contains(&format.as_str())),
+            })
+        })
+    }
+
+    fn write(&self, snapshot: ClipboardSnapshot) -> Result<(), String> {
+        // The plugin only writes one format at a time (or HTML with text),
+        // so all formats are set through the underlying clipboard-rs context
+        let mut contents = Vec::new();
+        if let Some(text) = snapshot.text {
+            contents.push(ClipboardContent::Text(text));
+        }
+        if let Some(html) = snapshot.html {
+            contents.push(ClipboardContent::Html(html));
+        }
+        if let Some(rtf) = snapshot.rtf {
+            contents.push(ClipboardContent::Rtf(rtf));
+        }
+        if let Some(image) = snapshot.image {
+            let image = RustImageData::from_bytes(&image).map_err(|e| e.to_string())?;
+            contents.push(ClipboardContent::Image(image));
+        }
+        if let Some(files) = snapshot.files {
+            contents.push(ClipboardContent::Files(files));
+        }
+
+        self.with_clipboard(|clipboard| {
+            clipboard
+                .clipboard
+                .lock()
+                .map_err(|e| e.to_string())?
+                .set(contents)
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        self.with_clipboard(|clipboard| clipboard.clear())
+    }
+
+    #[cfg(windows)]
+    fn watch(&self, listener: ChangeListener) -> Result<bool, String> {
+        self.with_clipboard(|clipboard| clipboard.start_monitor(self.app_handle.clone()))?;
+        self.app_handle
+            .listen(CLIPBOARD_PLUGIN_UPDATE_EVENT, move |_| listener());
+        Ok(true)
+    }
+
+    #[cfg(not(windows))]
+    fn watch(&self, listener: ChangeListener) -> Result<bool, String> {
+        // Counted before the listener runs, so whoever it wakes sees the new marker
+        let changes = self.changes.clone();
+        let notify: Arc<dyn Fn() + Send + Sync> = Arc::new(move || {
+            changes.fetch_add(1, Ordering::SeqCst);
+            listener();
+        });
+
+        // X11 tells about every copy, also under Wayland through XWayland. The plugin's
+        // monitor is used where it cannot; the Wayland data-control protocols
+        // (`zwlr_data_control`, `ext-data-control`) are not supported yet.
+        #[cfg(target_os = "linux")]
+        {
+            let notify = notify.clone();
+            match watch_clipboard(Box::new(move || notify())) {
+                Ok(()) => {
+                    self.watching.store(true, Ordering::SeqCst);
+                    return Ok(true);
+                }
+                Err(e) => eprintln!("X11 clipboard events unavailable: {}", e),
+            }
+        }
+
+        self.with_clipboard(|clipboard| clipboard.start_monitor(self.app_handle.clone()))?;
+        self.app_handle
+            .listen(CLIPBOARD_PLUGIN_UPDATE_EVENT, move |_| notify());
+        self.watching.store(true, Ordering::SeqCst);
+        Ok(true)
+    }
+
+    fn source_app(&self) -> Option<SourceApp> {
+        active_app()
+    }
+
+    #[cfg(windows)]
+    fn change_marker(&self) -> Option<u64> {
+        // Incremented by Windows on every clipboard change
+        Some(unsafe { GetClipboardSequenceNumber() }.into())
+    }
+
+    #[cfg(not(windows))]
+    fn change_marker(&self) -> Option<u64> {
+        // Only a counter while every change is reported
+        self.watching
+            .load(Ordering::SeqCst)
+            .then(|| self.changes.load(Ordering::SeqCst))
+    }
+}
+
+/// Writes go to every backend, reads come from the first one. Used to copy an item
+/// into the clipboard and the primary selection at once.
+pub struct MultiClipboard<'a>(pub Vec<&'a dyn ClipboardBackend>);
+
+impl MultiClipboard<'_> {
+    fn first(&self) -> Result<&dyn ClipboardBackend, String> {
+        self.0
+            .first()
+            .copied()
+            .ok_or_else(|| "No clipboard to use".to_string())
+    }
+
+    // Succeeds when any backend took the change; failures of the others are logged
+    fn each(&self, f: impl Fn(&dyn ClipboardBackend) -> Result<(), String>) -> Result<(), String> {
+        let mut written = false;
+        let mut errors = Vec::new();
+        for backend in &self.0 {
+            match f(*backend) {
+                Ok(()) => written = true,
+                Err(e) => errors.push(e),
+            }
+        }
+        if written {
+            for e in errors {
+                eprintln!("{}", e);
+            }
+            Ok(())
+        } else {
+            Err(errors
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| "No clipboard to use".to_string()))
+        }
+    }
+}
+
+impl ClipboardBackend for MultiClipboard<'_> {
+    fn read_text(&self) -> Result<String, String> {
+        self.first()?.read_text()
+    }
+
+    fn read_image(&self) -> Result<Vec<u8>, String> {
+        self.first()?.read_image()
+    }
+
+    fn read_html(&self) -> Result<String, String> {
+        self.first()?.read_html()
+    }
+
+    fn read_rtf(&self) -> Result<String, String> {
+        self.first()?.read_rtf()
+    }
+
+    fn read_files(&self) -> Result<Vec<String>, String> {
+        self.first()?.read_files()
+    }
+
+    fn available_formats(&self) -> Result<ClipboardFormats, String> {
+        self.first()?.available_formats()
+    }
+
+    fn write(&self, snapshot: ClipboardSnapshot) -> Result<(), String> {
+        self.each(|backend| backend.write(snapshot.clone()))
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        self.each(|backend| backend.clear())
+    }
+
+    fn watch(&self, _listener: ChangeListener) -> Result<bool, String> {
+        Ok(false)
+    }
+
+    fn source_app(&self) -> Option<SourceApp> {
+        self.0.first()?.source_app()
+    }
+}
+
+/// In-memory clipboard for tests. Writes replace all formats, like a real copy.
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Default)]
+pub struct MemoryClipboard {
+    contents: Mutex<ClipboardSnapshot>,
+    concealed: AtomicBool,
+    listeners: Mutex<Vec<ChangeListener>>,
+    /// Incremented on every write, like a platform change counter
+    changes: AtomicU64,
+    reads: AtomicUsize,
+    source_app: Mutex<Option<SourceApp>>,
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl MemoryClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write a snapshot the way password managers do, with the concealed hint set
+    pub fn write_concealed(&self, snapshot: ClipboardSnapshot) {
+        self.replace(snapshot, true);
+    }
+
+    /// Application reported for the following copies, like switching windows
+    pub fn set_source_app(&self, source_app: Option<SourceApp>) {
+        *self.source_app.lock().unwrap() = source_app;
+    }
+
+    /// Number of formats read so far
+    pub fn read_count(&self) -> usize {
+        self.reads.load(Ordering::SeqCst)
+    }
+
+    fn replace(&self, contents: ClipboardSnapshot, concealed: bool) {
+        *self.contents.lock().unwrap() = contents;
+        self.concealed.store(concealed, Ordering::SeqCst);
+        self.changes.fetch_add(1, Ordering::SeqCst);
+        for listener in self.listeners.lock().unwrap().iter() {
+            listener();
+        }
+    }
+
+    fn read<T>(&self, format: impl FnOnce(&ClipboardSnapshot) -> Option<T>) -> Result<T, String> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        format(&self.contents.lock().unwrap()).ok_or_else(|| "Format not on clipboard".to_string())
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl ClipboardBackend for MemoryClipboard {
+    fn read_text(&self) -> Result<String, String> {
+        self.read(|contents| contents.text.clone())
+    }
+
+    fn read_image(&self) -> Result<Vec<u8>, String> {
+        self.read(|contents| contents.image.clone())
+    }
+
+    fn read_html(&self) -> Result<String, String> {
+        self.read(|contents| contents.html.clone())
+    }
+
+    fn read_rtf(&self) -> Result<String, String> {
+        self.read(|contents| contents.rtf.clone())
+    }
+
+    fn read_files(&self) -> Result<Vec<String>, String> {
+        self.read(|contents| contents.files.clone())
+    }
+
+    fn available_formats(&self) -> Result<ClipboardFormats, String> {
+        let contents = self.contents.lock().unwrap();
+        Ok(ClipboardFormats {
+            text: contents.text.is_some(),
+            html: contents.html.is_some(),
+            rtf: contents.rtf.is_some(),
+            image: contents.image.is_some(),
+            files: contents.files.is_some(),
+            concealed: self.concealed.load(Ordering::SeqCst),
+        })
+    }
+
+    fn write(&self, snapshot: ClipboardSnapshot) -> Result<(), String> {
+        self.replace(snapshot, false);
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        self.replace(ClipboardSnapshot::default(), false);
+        Ok(())
+    }
+
+    fn watch(&self, listener: ChangeListener) -> Result<bool, String> {
+        self.listeners.lock().unwrap().push(listener);
+        Ok(true)
+    }
+
+    fn change_marker(&self) -> Option<u64> {
+        Some(self.changes.load(Ordering::SeqCst))
+    }
+
+    fn source_app(&self) -> Option<SourceApp> {
+        self.source_app.lock().unwrap().clone()
+    }
+}
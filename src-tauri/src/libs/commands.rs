@@ -1,54 +1,415 @@
-use tauri::{AppHandle, Manager, State, Window, Wry};
+use base64::{engine::general_purpose, Engine};
+use chrono::{Duration, Local, Utc};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager, State, Window, Wry};
 use tauri_plugin_autostart::ManagerExt;
-use tauri_plugin_clipboard::Clipboard;
+use tauri_plugin_opener::OpenerExt;
 
-use crate::libs::clipboard::copy_to_clipboard_impl;
-use crate::libs::config::update_hotkey;
+use crate::libs::archive::{read_archive, write_archive};
+use crate::libs::backup::{create_backup, open_or_recover, RecoveryState};
+use crate::libs::clipboard::{
+    copy_snippet_impl, copy_to_clipboard_impl, snippet_fields, OwnWriteClipboard, OwnWrites,
+};
+use crate::libs::clipboard_backend::{ClipboardBackend, MultiClipboard, TauriClipboard};
+use crate::libs::config::{update_hotkey, update_pause_hotkey};
+use crate::libs::constants::{DEFAULT_MOST_USED_LIMIT, DEFAULT_SEARCH_LIMIT};
 use crate::libs::database::DatabaseManager;
-use crate::libs::types::{AppConfig, ClipboardItem};
+use crate::libs::encryption::{change_encryption, KeySource};
+use crate::libs::error::ClipRayError;
+use crate::libs::exclusion::ExclusionFilter;
+use crate::libs::janitor::enforce_retention;
+use crate::libs::migrations::MigrationError;
+use crate::libs::pause::{publish_pause_status, CapturePause};
+use crate::libs::selection::PrimarySelection;
+use crate::libs::setup::{
+    data_dir, database_path, on_database_unlocked, recover_startup as recover_startup_impl,
+    report_startup_failure, StartupFailureState,
+};
+use crate::libs::types::{
+    AppConfig, ClipboardItem, CombinedSearchResults, EncryptionMode, EncryptionStatus,
+    ExportSummary, HistoryPage, HistoryQuery, ImportMode, ImportSummary, PauseStatus,
+    RecoveryNotice, SearchResult, Selection, Snippet, SnippetDraft, SnippetFolder, StartupAction,
+    StartupFailure, Tag, WriteBack,
+};
 use crate::libs::window::{hide_window_impl, show_window_impl};
 
+/// The opened database. Only an opened database is managed, so while an encrypted
+/// database waits for its passphrase there is none and commands report it as locked.
+pub fn require_database<T>(db: Option<T>) -> Result<T, ClipRayError> {
+    db.ok_or_else(ClipRayError::locked)
+}
+
+fn database<M: Manager<Wry>>(manager: &M) -> Result<State<'_, DatabaseManager>, ClipRayError> {
+    require_database(manager.try_state::<DatabaseManager>())
+}
+
 #[tauri::command]
 /// Get clipboard history
 pub async fn get_clipboard_history(
-    db: State<'_, DatabaseManager>,
-) -> Result<Vec<ClipboardItem>, String> {
-    db.get_clipboard_history().map_err(|e| e.to_string())
+    app_handle: AppHandle<Wry>,
+) -> Result<Vec<ClipboardItem>, ClipRayError> {
+    let db = database(&app_handle)?;
+    db.get_clipboard_history()
+}
+
+/// Get a filtered page of clipboard history
+#[tauri::command]
+pub async fn query_clipboard_history(
+    query: HistoryQuery,
+    app_handle: AppHandle<Wry>,
+) -> Result<HistoryPage, ClipRayError> {
+    let db = database(&app_handle)?;
+    db.query_clipboard_history(&query)
+}
+
+/// Get the most frequently copied items
+#[tauri::command]
+pub async fn get_most_used_items(
+    limit: Option<u32>,
+    app_handle: AppHandle<Wry>,
+) -> Result<Vec<ClipboardItem>, ClipRayError> {
+    let db = database(&app_handle)?;
+    db.get_most_used_items(limit.unwrap_or(DEFAULT_MOST_USED_LIMIT))
+}
+
+/// Full-text search clipboard history
+#[tauri::command]
+pub async fn search_clipboard_history(
+    query: String,
+    limit: Option<u32>,
+    boost_pinned: Option<bool>,
+    app_handle: AppHandle<Wry>,
+) -> Result<Vec<SearchResult>, ClipRayError> {
+    let db = database(&app_handle)?;
+    db.search_clipboard_history(
+        &query,
+        limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        boost_pinned.unwrap_or(true),
+    )
 }
 
 /// Delete clipboard item
 #[tauri::command]
-pub async fn delete_clipboard_item(id: i64, db: State<'_, DatabaseManager>) -> Result<(), String> {
-    db.delete_clipboard_item(id).map_err(|e| e.to_string())
+pub async fn delete_clipboard_item(
+    id: i64,
+    app_handle: AppHandle<Wry>,
+) -> Result<(), ClipRayError> {
+    let db = database(&app_handle)?;
+    db.delete_clipboard_item(id)
 }
 
 /// Toggle pin
 #[tauri::command]
-pub async fn toggle_pin(id: i64, db: State<'_, DatabaseManager>) -> Result<(), String> {
-    db.toggle_pin(id).map_err(|e| e.to_string())
+pub async fn toggle_pin(id: i64, app_handle: AppHandle<Wry>) -> Result<(), ClipRayError> {
+    let db = database(&app_handle)?;
+    db.toggle_pin(id)
 }
 
-/// Copy to clipboard
+/// Mark an item as sensitive so it expires like matched sensitive content,
+/// or keep it for good
+#[tauri::command]
+pub async fn set_item_sensitive(
+    id: i64,
+    sensitive: bool,
+    app_handle: AppHandle<Wry>,
+) -> Result<(), ClipRayError> {
+    let db = database(&app_handle)?;
+    let expires_at = if sensitive {
+        let rules = db.get_sensitive_rules()?;
+        Some(Utc::now() + Duration::seconds(rules.expire_after_secs.into()))
+    } else {
+        None
+    };
+    if db.set_item_expiry(id, expires_at)? {
+        Ok(())
+    } else {
+        Err(ClipRayError::not_found("Item", id))
+    }
+}
+
+/// Get all tags, sorted by name
+#[tauri::command]
+pub async fn list_tags(app_handle: AppHandle<Wry>) -> Result<Vec<Tag>, ClipRayError> {
+    let db = database(&app_handle)?;
+    db.list_tags()
+}
+
+/// Create a tag
+#[tauri::command]
+pub async fn create_tag(name: String, app_handle: AppHandle<Wry>) -> Result<Tag, ClipRayError> {
+    let db = database(&app_handle)?;
+    db.create_tag(&name)
+}
+
+/// Rename a tag
+#[tauri::command]
+pub async fn rename_tag(
+    id: i64,
+    name: String,
+    app_handle: AppHandle<Wry>,
+) -> Result<(), ClipRayError> {
+    let db = database(&app_handle)?;
+    if db.rename_tag(id, &name)? {
+        Ok(())
+    } else {
+        Err(ClipRayError::not_found("Tag", id))
+    }
+}
+
+/// Delete a tag; tagged items are kept
+#[tauri::command]
+pub async fn delete_tag(id: i64, app_handle: AppHandle<Wry>) -> Result<(), ClipRayError> {
+    let db = database(&app_handle)?;
+    if db.delete_tag(id)? {
+        Ok(())
+    } else {
+        Err(ClipRayError::not_found("Tag", id))
+    }
+}
+
+/// Assign a tag to an item, or remove it
+#[tauri::command]
+pub async fn set_item_tag(
+    item_id: i64,
+    tag_id: i64,
+    tagged: bool,
+    app_handle: AppHandle<Wry>,
+) -> Result<(), ClipRayError> {
+    let db = database(&app_handle)?;
+    if db.set_item_tag(item_id, tag_id, tagged)? {
+        Ok(())
+    } else {
+        Err(ClipRayError::NotFound(format!(
+            "Item {} or tag {} not found",
+            item_id, tag_id
+        )))
+    }
+}
+
+/// Get all snippets, by folder and then by name
 #[tauri::command]
-pub async fn copy_to_clipboard(
-    content: String,
-    content_type: String,
+pub async fn list_snippets(app_handle: AppHandle<Wry>) -> Result<Vec<Snippet>, ClipRayError> {
+    let db = database(&app_handle)?;
+    db.list_snippets()
+}
+
+/// Create a snippet from scratch
+#[tauri::command]
+pub async fn create_snippet(
+    snippet: SnippetDraft,
+    app_handle: AppHandle<Wry>,
+) -> Result<Snippet, ClipRayError> {
+    let db = database(&app_handle)?;
+    db.create_snippet(&snippet)
+}
+
+/// Save the text of a history item as a snippet
+#[tauri::command]
+pub async fn create_snippet_from_item(
+    item_id: i64,
+    name: Option<String>,
+    app_handle: AppHandle<Wry>,
+) -> Result<Snippet, ClipRayError> {
+    let db = database(&app_handle)?;
+    db.create_snippet_from_item(item_id, name.as_deref())?
+        .ok_or_else(|| ClipRayError::not_found("Item", item_id))
+}
+
+/// Replace the name, abbreviation, content and folder of a snippet
+#[tauri::command]
+pub async fn update_snippet(
+    id: i64,
+    snippet: SnippetDraft,
+    app_handle: AppHandle<Wry>,
+) -> Result<Snippet, ClipRayError> {
+    let db = database(&app_handle)?;
+    db.update_snippet(id, &snippet)?
+        .ok_or_else(|| ClipRayError::not_found("Snippet", id))
+}
+
+/// Delete a snippet
+#[tauri::command]
+pub async fn delete_snippet(id: i64, app_handle: AppHandle<Wry>) -> Result<(), ClipRayError> {
+    let db = database(&app_handle)?;
+    if db.delete_snippet(id)? {
+        Ok(())
+    } else {
+        Err(ClipRayError::not_found("Snippet", id))
+    }
+}
+
+/// Get all snippet folders, sorted by name
+#[tauri::command]
+pub async fn list_snippet_folders(
+    app_handle: AppHandle<Wry>,
+) -> Result<Vec<SnippetFolder>, ClipRayError> {
+    let db = database(&app_handle)?;
+    db.list_snippet_folders()
+}
+
+/// Create a snippet folder
+#[tauri::command]
+pub async fn create_snippet_folder(
+    name: String,
+    app_handle: AppHandle<Wry>,
+) -> Result<SnippetFolder, ClipRayError> {
+    let db = database(&app_handle)?;
+    db.create_snippet_folder(&name)
+}
+
+/// Rename a snippet folder
+#[tauri::command]
+pub async fn rename_snippet_folder(
+    id: i64,
+    name: String,
+    app_handle: AppHandle<Wry>,
+) -> Result<(), ClipRayError> {
+    let db = database(&app_handle)?;
+    if db.rename_snippet_folder(id, &name)? {
+        Ok(())
+    } else {
+        Err(ClipRayError::not_found("Folder", id))
+    }
+}
+
+/// Delete a snippet folder; its snippets are kept
+#[tauri::command]
+pub async fn delete_snippet_folder(
+    id: i64,
+    app_handle: AppHandle<Wry>,
+) -> Result<(), ClipRayError> {
+    let db = database(&app_handle)?;
+    if db.delete_snippet_folder(id)? {
+        Ok(())
+    } else {
+        Err(ClipRayError::not_found("Folder", id))
+    }
+}
+
+/// Search snippets and clipboard history at once
+#[tauri::command]
+pub async fn search_everything(
+    query: String,
+    limit: Option<u32>,
+    app_handle: AppHandle<Wry>,
+) -> Result<CombinedSearchResults, ClipRayError> {
+    let db = database(&app_handle)?;
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+    Ok(CombinedSearchResults {
+        snippets: db.search_snippets(&query, limit)?,
+        history: db.search_clipboard_history(&query, limit, true)?,
+    })
+}
+
+/// Get the names of the fill-in fields of a snippet
+#[tauri::command]
+pub async fn get_snippet_fields(
+    id: i64,
+    app_handle: AppHandle<Wry>,
+) -> Result<Vec<String>, ClipRayError> {
+    let db = database(&app_handle)?;
+    snippet_fields(id, &db)
+}
+
+/// Copy a snippet to the clipboard with its placeholders expanded
+#[tauri::command]
+pub async fn copy_snippet(
+    id: i64,
+    fields: Option<HashMap<String, String>>,
     window: Window<Wry>,
-) -> Result<(), String> {
-    let clipboard = window.state::<Clipboard>();
-    copy_to_clipboard_impl(content, content_type, &clipboard)
+) -> Result<(), ClipRayError> {
+    let db = database(&window)?;
+    let clipboard = TauriClipboard::new(window.app_handle().clone());
+    with_write_target(window.app_handle(), &db, None, |target| {
+        copy_snippet_impl(id, &fields.unwrap_or_default(), &db, &clipboard, target)
+    })
 }
 
-/// Copy to clipboard and hide window
+/// Copy a snippet to the clipboard with its placeholders expanded, and hide the window
 #[tauri::command]
-pub async fn copy_and_hide(
-    content: String,
-    content_type: String,
+pub async fn copy_snippet_and_hide(
+    id: i64,
+    fields: Option<HashMap<String, String>>,
     window: Window<Wry>,
-) -> Result<(), String> {
+) -> Result<(), ClipRayError> {
+    let db = database(&window)?;
+    let clipboard = TauriClipboard::new(window.app_handle().clone());
+    with_write_target(window.app_handle(), &db, None, |target| {
+        copy_snippet_impl(id, &fields.unwrap_or_default(), &db, &clipboard, target)
+    })?;
+    hide_window_impl(&window)
+}
+
+// Run `f` with the clipboard, the primary selection or both, as chosen in the capture
+// settings. Writes of history item `item_id` are recorded, so the monitors know them.
+fn with_write_target<T>(
+    app_handle: &AppHandle<Wry>,
+    db: &DatabaseManager,
+    item_id: Option<i64>,
+    f: impl FnOnce(&dyn ClipboardBackend) -> Result<T, ClipRayError>,
+) -> Result<T, ClipRayError> {
+    let clipboard = TauriClipboard::new(app_handle.clone());
+    let write_back = db.get_capture_settings().unwrap_or_default().write_back;
+    let primary = app_handle.try_state::<PrimarySelection>();
+    let primary = primary.as_ref().map(|primary| primary.0.as_ref());
+    let targets: Vec<(Selection, &dyn ClipboardBackend)> = match (write_back, primary) {
+        (WriteBack::Primary, Some(primary)) => vec![(Selection::Primary, primary)],
+        (WriteBack::Both, Some(primary)) => vec![
+            (Selection::Clipboard, &clipboard),
+            (Selection::Primary, primary),
+        ],
+        // Without a primary selection everything goes to the clipboard
+        _ => vec![(Selection::Clipboard, &clipboard)],
+    };
+
+    let own_writes = app_handle.state::<OwnWrites>();
+    let recorded: Vec<OwnWriteClipboard> = targets
+        .into_iter()
+        .map(|(selection, inner)| OwnWriteClipboard {
+            inner,
+            selection,
+            item_id,
+            own_writes: &own_writes,
+        })
+        .collect();
+    f(&MultiClipboard(
+        recorded
+            .iter()
+            .map(|clipboard| clipboard as &dyn ClipboardBackend)
+            .collect(),
+    ))
+}
+
+/// Get the full-size image of an image item as Base64 PNG
+#[tauri::command]
+pub async fn get_full_image(id: i64, app_handle: AppHandle<Wry>) -> Result<String, ClipRayError> {
+    let db = database(&app_handle)?;
+    let image_data = db
+        .get_image_data(id)?
+        .ok_or_else(|| ClipRayError::not_found("Image", id))?;
+    Ok(general_purpose::STANDARD.encode(image_data))
+}
+
+/// Copy to clipboard
+#[tauri::command]
+pub async fn copy_to_clipboard(id: i64, window: Window<Wry>) -> Result<(), ClipRayError> {
+    let db = database(&window)?;
+    with_write_target(window.app_handle(), &db, Some(id), |clipboard| {
+        copy_to_clipboard_impl(id, &db, clipboard)
+    })
+}
+
+/// Copy to clipboard and hide window
+#[tauri::command]
+pub async fn copy_and_hide(id: i64, window: Window<Wry>) -> Result<(), ClipRayError> {
+    let db = database(&window)?;
     // Copy to clipboard
-    let clipboard = window.state::<Clipboard>();
-    copy_to_clipboard_impl(content, content_type, &clipboard)?;
+    with_write_target(window.app_handle(), &db, Some(id), |clipboard| {
+        copy_to_clipboard_impl(id, &db, clipboard)
+    })?;
 
     // Hide window
     hide_window_impl(&window)?;
@@ -58,58 +419,312 @@ pub async fn copy_and_hide(
 
 // Get configuration
 #[tauri::command]
-pub async fn get_config(db: State<'_, DatabaseManager>) -> Result<AppConfig, String> {
-    db.get_config().map_err(|e| e.to_string())
+pub async fn get_config(app_handle: AppHandle<Wry>) -> Result<AppConfig, ClipRayError> {
+    let db = database(&app_handle)?;
+    db.get_config()
 }
 
 // Update configuration
 #[tauri::command]
 pub async fn update_config(
     config: AppConfig,
-    db: State<'_, DatabaseManager>,
+    pause: State<'_, CapturePause>,
     app_handle: AppHandle<Wry>,
-) -> Result<(), String> {
+) -> Result<(), ClipRayError> {
+    let db = database(&app_handle)?;
+    apply_config(&config, &db, &pause, &app_handle)
+}
+
+// Save the configuration and bring hotkeys, autostart and the history in line with it
+fn apply_config(
+    config: &AppConfig,
+    db: &DatabaseManager,
+    pause: &CapturePause,
+    app_handle: &AppHandle<Wry>,
+) -> Result<(), ClipRayError> {
+    // Reject rules the monitor could not compile before anything is changed
+    ExclusionFilter::new(&config.exclusion_rules).map_err(ClipRayError::InvalidInput)?;
+    ExclusionFilter::new(&config.sensitive.matching_rules()).map_err(ClipRayError::InvalidInput)?;
+    config
+        .capture
+        .validate()
+        .map_err(ClipRayError::InvalidInput)?;
+
     // Get current configuration
-    let current_config = db.get_config().map_err(|e| e.to_string())?;
+    let current_config = db.get_config()?;
 
     // Re-register hotkey if changed
     if current_config.hotkey != config.hotkey {
-        update_hotkey(&app_handle, &current_config.hotkey, &config.hotkey)?;
+        update_hotkey(app_handle, &current_config.hotkey, &config.hotkey)?;
+    }
+
+    // Re-register pause hotkey if changed
+    if current_config.pause_hotkey != config.pause_hotkey {
+        update_pause_hotkey(
+            app_handle,
+            current_config.pause_hotkey.as_deref(),
+            config.pause_hotkey.as_deref(),
+        )?;
+    }
+
+    // Start or stop keeping the pause across restarts
+    if current_config.persist_pause != config.persist_pause {
+        let status = if config.persist_pause {
+            pause.status()
+        } else {
+            PauseStatus::default()
+        };
+        db.save_pause_status(&status)?;
     }
 
     // Toggle autostart if changed
     if current_config.autostart != config.autostart {
         let autostart_manager = app_handle.autolaunch();
         if config.autostart {
-            autostart_manager
-                .enable()
-                .map_err(|e| format!("Failed to enable autostart: {}", e))?;
+            autostart_manager.enable().map_err(|e| {
+                ClipRayError::Autostart(format!("Failed to enable autostart: {}", e))
+            })?;
         } else {
-            autostart_manager
-                .disable()
-                .map_err(|e| format!("Failed to disable autostart: {}", e))?;
+            autostart_manager.disable().map_err(|e| {
+                ClipRayError::Autostart(format!("Failed to disable autostart: {}", e))
+            })?;
         }
     }
 
     // Save configuration
-    db.update_config(&config).map_err(|e| e.to_string())
+    db.update_config(config)?;
+
+    // Apply stricter limits right away instead of waiting for the janitor
+    enforce_retention(app_handle, db);
+    Ok(())
+}
+
+/// Export the history and settings to an archive file, in the downloads directory
+/// unless a path is given
+#[tauri::command]
+pub async fn export_data(
+    path: Option<String>,
+    app_handle: AppHandle<Wry>,
+) -> Result<ExportSummary, ClipRayError> {
+    let db = database(&app_handle)?;
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => app_handle
+            .path()
+            .download_dir()
+            .map_err(|e| {
+                ClipRayError::File(format!("Failed to resolve downloads directory: {}", e))
+            })?
+            .join(format!(
+                "clipray-export-{}.jsonl",
+                Local::now().format("%Y%m%d-%H%M%S")
+            )),
+    };
+    let file = File::create(&path)
+        .map_err(|e| ClipRayError::File(format!("Failed to create {}: {}", path.display(), e)))?;
+    let items = write_archive(&db, file).map_err(ClipRayError::from)?;
+
+    Ok(ExportSummary {
+        path: path.display().to_string(),
+        items,
+    })
+}
+
+/// Import an archive written by `export_data`. Merging adds its items and snippets;
+/// replacing deletes the history, tags and snippets and applies the archived settings as well.
+#[tauri::command]
+pub async fn import_data(
+    path: String,
+    mode: ImportMode,
+    pause: State<'_, CapturePause>,
+    app_handle: AppHandle<Wry>,
+) -> Result<ImportSummary, ClipRayError> {
+    let db = database(&app_handle)?;
+    let file = File::open(&path)
+        .map_err(|e| ClipRayError::File(format!("Failed to open {}: {}", path, e)))?;
+    let archive = read_archive(BufReader::new(file)).map_err(ClipRayError::from)?;
+
+    // Settings first, so their history limits apply to the imported items
+    if mode == ImportMode::Replace {
+        if let Some(config) = &archive.config {
+            apply_config(config, &db, &pause, &app_handle)?;
+        }
+    }
+    let summary = db.import_archive(&archive, mode)?;
+
+    let _ = app_handle.emit("clipboard-updated", ());
+    Ok(summary)
+}
+
+/// Get whether clipboard capture is paused
+#[tauri::command]
+pub async fn get_pause_status(pause: State<'_, CapturePause>) -> Result<PauseStatus, ClipRayError> {
+    Ok(pause.status())
+}
+
+/// Pause clipboard capture, for a number of minutes or until resumed
+#[tauri::command]
+pub async fn pause_capture(
+    minutes: Option<u32>,
+    pause: State<'_, CapturePause>,
+    app_handle: AppHandle<Wry>,
+) -> Result<PauseStatus, ClipRayError> {
+    let status = pause.pause(minutes.map(|minutes| Duration::minutes(minutes.into())));
+    publish_pause_status(&app_handle, &status);
+    Ok(status)
+}
+
+/// Resume clipboard capture
+#[tauri::command]
+pub async fn resume_capture(
+    pause: State<'_, CapturePause>,
+    app_handle: AppHandle<Wry>,
+) -> Result<PauseStatus, ClipRayError> {
+    let status = pause.resume();
+    publish_pause_status(&app_handle, &status);
+    Ok(status)
+}
+
+/// Get whether the database is encrypted and still waiting for its passphrase
+#[tauri::command]
+pub async fn get_encryption_status(
+    app_handle: AppHandle<Wry>,
+) -> Result<EncryptionStatus, ClipRayError> {
+    Ok(match app_handle.try_state::<DatabaseManager>() {
+        Some(db) => EncryptionStatus {
+            locked: false,
+            mode: db.encryption_mode(),
+        },
+        None => EncryptionStatus {
+            locked: true,
+            mode: EncryptionMode::Passphrase,
+        },
+    })
+}
+
+/// Open the encrypted database with the passphrase entered at startup
+#[tauri::command]
+pub async fn unlock_database(
+    passphrase: String,
+    app_handle: AppHandle<Wry>,
+) -> Result<(), ClipRayError> {
+    if app_handle.try_state::<DatabaseManager>().is_some() {
+        return Ok(());
+    }
+
+    let db_path = database_path(&app_handle).map_err(ClipRayError::from)?;
+    let (db, notice) = open_or_recover(&db_path, Some(&passphrase)).map_err(|e| match e {
+        MigrationError::Locked => ClipRayError::WrongPassphrase,
+        e => e.into(),
+    })?;
+    if let Some(notice) = notice {
+        app_handle.state::<RecoveryState>().set(notice);
+    }
+    // The window offers the recovery actions, as for a failure at startup
+    on_database_unlocked(&app_handle, db).map_err(|e| {
+        report_startup_failure(&app_handle, &e);
+        e.into()
+    })
+}
+
+/// Get what was done about a damaged database at startup, once
+#[tauri::command]
+pub async fn take_recovery_notice(
+    recovery: State<'_, RecoveryState>,
+) -> Result<Option<RecoveryNotice>, ClipRayError> {
+    Ok(recovery.take())
+}
+
+/// Get why startup failed, while it has not been recovered from
+#[tauri::command]
+pub async fn get_startup_failure(
+    startup: State<'_, StartupFailureState>,
+) -> Result<Option<StartupFailure>, ClipRayError> {
+    Ok(startup.get())
+}
+
+/// Run a recovery action after a failed startup and start again.
+/// When it did not help, `get_startup_failure` has the new failure.
+#[tauri::command]
+pub async fn recover_startup(
+    action: StartupAction,
+    startup: State<'_, StartupFailureState>,
+    app_handle: AppHandle<Wry>,
+) -> Result<(), ClipRayError> {
+    if app_handle.try_state::<DatabaseManager>().is_some() {
+        return Ok(());
+    }
+
+    match recover_startup_impl(&app_handle, action) {
+        Ok(()) => {
+            startup.set(None);
+            Ok(())
+        }
+        Err(e) => {
+            report_startup_failure(&app_handle, &e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Show the directory holding the database, its backups and logs in the file manager
+#[tauri::command]
+pub async fn open_data_folder(app_handle: AppHandle<Wry>) -> Result<(), ClipRayError> {
+    let dir = data_dir(&app_handle).map_err(ClipRayError::from)?;
+    app_handle
+        .opener()
+        .open_path(dir.display().to_string(), None::<&str>)
+        .map_err(|e| ClipRayError::File(format!("Failed to open {}: {}", dir.display(), e)))
+}
+
+/// Encrypt the database with a passphrase or a key file, re-key it, or decrypt it
+#[tauri::command]
+pub async fn set_database_encryption(
+    mode: EncryptionMode,
+    passphrase: Option<String>,
+    app_handle: AppHandle<Wry>,
+) -> Result<EncryptionStatus, ClipRayError> {
+    let db = database(&app_handle)?;
+    let source = match mode {
+        EncryptionMode::None => KeySource::None,
+        EncryptionMode::KeyFile => KeySource::KeyFile,
+        EncryptionMode::Passphrase => match passphrase {
+            Some(passphrase) if !passphrase.is_empty() => KeySource::Passphrase(passphrase),
+            _ => {
+                return Err(ClipRayError::InvalidInput(
+                    "A passphrase is required".to_string(),
+                ))
+            }
+        },
+    };
+    change_encryption(&db, source).map_err(ClipRayError::from)?;
+
+    // The backups with the previous key are gone, so start over with one right away
+    if let Err(e) = create_backup(&db, Utc::now()) {
+        eprintln!("Failed to back up the re-encrypted database: {}", e);
+    }
+
+    Ok(EncryptionStatus {
+        locked: false,
+        mode: db.encryption_mode(),
+    })
 }
 
 // Show window
 #[tauri::command]
-pub async fn show_window(window: Window<Wry>) -> Result<(), String> {
+pub async fn show_window(window: Window<Wry>) -> Result<(), ClipRayError> {
     show_window_impl(&window)
 }
 
 // Hide window
 #[tauri::command]
-pub async fn hide_window(window: Window<Wry>) -> Result<(), String> {
+pub async fn hide_window(window: Window<Wry>) -> Result<(), ClipRayError> {
     hide_window_impl(&window)
 }
 
 // Exit application
 #[tauri::command]
-pub async fn exit_app(app_handle: AppHandle<Wry>) -> Result<(), String> {
+pub async fn exit_app(app_handle: AppHandle<Wry>) -> Result<(), ClipRayError> {
     app_handle.exit(0);
     Ok(())
 }
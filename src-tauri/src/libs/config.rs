@@ -1,14 +1,16 @@
 use tauri::{AppHandle, Emitter, Manager, Wry};
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 
+use crate::libs::error::ClipRayError;
+use crate::libs::pause::toggle_pause;
 use crate::libs::window::show_webview_window_impl;
 
-pub fn register_hotkey(app_handle: &AppHandle<Wry>, hotkey: &str) -> Result<(), String> {
+pub fn register_hotkey(app_handle: &AppHandle<Wry>, hotkey: &str) -> Result<(), ClipRayError> {
     let shortcut_manager = app_handle.global_shortcut();
-    let window = app_handle.get_webview_window("main").unwrap();
-    let shortcut = hotkey
-        .parse::<Shortcut>()
-        .map_err(|e| format!("Invalid hotkey format: {}", e))?;
+    let window = app_handle
+        .get_webview_window("main")
+        .ok_or_else(|| ClipRayError::Window("The main window is missing".to_string()))?;
+    let shortcut = parse_hotkey(hotkey)?;
 
     let window_clone = window.clone();
     shortcut_manager
@@ -17,16 +19,19 @@ pub fn register_hotkey(app_handle: &AppHandle<Wry>, hotkey: &str) -> Result<(),
             // Show with cursor-based positioning
             let _ = show_webview_window_impl(&window_clone);
         })
-        .map_err(|e| format!("Failed to set hotkey handler: {}", e))?;
+        .map_err(|e| hotkey_in_use(hotkey, e))?;
     Ok(())
 }
 
-pub fn unregister_hotkey(app_handle: &AppHandle<Wry>, hotkey: &str) -> Result<(), String> {
+pub fn unregister_hotkey(app_handle: &AppHandle<Wry>, hotkey: &str) -> Result<(), ClipRayError> {
     let shortcut_manager = app_handle.global_shortcut();
     if let Ok(shortcut) = hotkey.parse::<Shortcut>() {
         shortcut_manager
             .unregister(shortcut)
-            .map_err(|e| format!("Failed to unregister hotkey: {}", e))?;
+            .map_err(|e| ClipRayError::InvalidHotkey {
+                hotkey: hotkey.to_string(),
+                detail: format!("Failed to unregister hotkey: {}", e),
+            })?;
     }
     Ok(())
 }
@@ -35,7 +40,7 @@ pub fn update_hotkey(
     app_handle: &AppHandle<Wry>,
     old_hotkey: &str,
     new_hotkey: &str,
-) -> Result<(), String> {
+) -> Result<(), ClipRayError> {
     // Remove old hotkey
     let _ = unregister_hotkey(app_handle, old_hotkey);
 
@@ -44,3 +49,52 @@ pub fn update_hotkey(
 
     Ok(())
 }
+
+pub fn register_pause_hotkey(
+    app_handle: &AppHandle<Wry>,
+    hotkey: &str,
+) -> Result<(), ClipRayError> {
+    let shortcut = parse_hotkey(hotkey)?;
+
+    app_handle
+        .global_shortcut()
+        .on_shortcut(shortcut, |app, _shortcut, event| {
+            // Handlers also fire on release, which would undo the toggle
+            if event.state() == ShortcutState::Pressed {
+                toggle_pause(app);
+            }
+        })
+        .map_err(|e| hotkey_in_use(hotkey, e))?;
+    Ok(())
+}
+
+pub fn update_pause_hotkey(
+    app_handle: &AppHandle<Wry>,
+    old_hotkey: Option<&str>,
+    new_hotkey: Option<&str>,
+) -> Result<(), ClipRayError> {
+    if let Some(old_hotkey) = old_hotkey {
+        let _ = unregister_hotkey(app_handle, old_hotkey);
+    }
+    if let Some(new_hotkey) = new_hotkey {
+        register_pause_hotkey(app_handle, new_hotkey)?;
+    }
+    Ok(())
+}
+
+fn parse_hotkey(hotkey: &str) -> Result<Shortcut, ClipRayError> {
+    hotkey
+        .parse::<Shortcut>()
+        .map_err(|e| ClipRayError::InvalidHotkey {
+            hotkey: hotkey.to_string(),
+            detail: e.to_string(),
+        })
+}
+
+// Registration fails when another application already holds the shortcut
+fn hotkey_in_use(hotkey: &str, error: impl std::fmt::Display) -> ClipRayError {
+    ClipRayError::HotkeyInUse {
+        hotkey: hotkey.to_string(),
+        detail: error.to_string(),
+    }
+}
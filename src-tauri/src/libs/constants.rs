@@ -1,4 +1,75 @@
-/// Interval for monitoring clipboard changes
-pub const CLIPBOARD_MONITOR_INTERVAL_MS: u64 = 1000; // 1 second
+/// How often the clipboard is checked when the platform cannot notify about changes
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 1000; // 1 second
+pub const MIN_POLL_INTERVAL_MS: u64 = 100;
+pub const MAX_POLL_INTERVAL_MS: u64 = 60_000; // 1 minute
+/// Quiet time before a changed primary selection is read
+pub const DEFAULT_SELECTION_DEBOUNCE_MS: u64 = 300;
+pub const MAX_SELECTION_DEBOUNCE_MS: u64 = 5000;
+/// How long the owner of the primary selection has to answer a read
+pub const SELECTION_READ_TIMEOUT_MS: u64 = 500;
 pub const DEFAULT_MAX_HISTORY_COUNT: u32 = 50;
+/// How often the retention policy is enforced in the background
+pub const RETENTION_JANITOR_INTERVAL_SECS: u64 = 600; // 10 minutes
+/// How long sensitive items are kept by default
+pub const DEFAULT_SENSITIVE_EXPIRY_SECS: u32 = 120;
+/// How often expired sensitive items are looked for
+pub const EXPIRY_CHECK_INTERVAL_SECS: u64 = 5;
 pub const DEFAULT_HOTKEY: &str = "CommandOrControl+Shift+V";
+pub const DEFAULT_SEARCH_LIMIT: u32 = 50;
+/// bm25 scores are multiplied by this for pinned items when boosting is enabled
+pub const PINNED_RANK_BOOST: f64 = 2.0;
+/// Markers wrapped around matched terms in search snippets
+pub const SEARCH_HIGHLIGHT_START: &str = "<mark>";
+pub const SEARCH_HIGHLIGHT_END: &str = "</mark>";
+pub const SEARCH_SNIPPET_ELLIPSIS: &str = "…";
+pub const SEARCH_SNIPPET_TOKENS: u32 = 16;
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+pub const MAX_PAGE_SIZE: u32 = 500;
+/// Longest edge of generated image thumbnails, in pixels
+pub const THUMBNAIL_MAX_SIZE: u32 = 160;
+// Images are re-encoded to PNG before they are stored
+pub const IMAGE_MIME_TYPE: &str = "image/png";
+pub const DEFAULT_MOST_USED_LIMIT: u32 = 20;
+/// Snippets made from a history item are named after its first line, cut to this length
+pub const SNIPPET_NAME_MAX_CHARS: usize = 40;
+/// Format of a `{date}` placeholder in snippets without an explicit format
+pub const DEFAULT_TEMPLATE_DATE_FORMAT: &str = "%Y-%m-%d";
+/// Name written in the header of export archives
+pub const ARCHIVE_FORMAT: &str = "clipray-export";
+/// Version of the export archive layout; archives from newer versions are refused
+pub const ARCHIVE_VERSION: u32 = 1;
+/// Clipboard formats that password managers add to mark a copy as secret
+pub const CONCEALED_CLIPBOARD_FORMATS: &[&str] = &[
+    // macOS, see nspasteboard.org
+    "org.nspasteboard.ConcealedType",
+    "org.nspasteboard.TransientType",
+    // Windows
+    "ExcludeClipboardContentFromMonitorProcessing",
+    "Clipboard Viewer Ignore",
+    // KDE Klipper
+    "x-kde-passwordManagerHint",
+];
+
+/// Size of the random key stored in the database key file
+pub const DATABASE_KEY_BYTES: usize = 32;
+
+/// A backup of the history database is taken when the latest one is this old
+pub const BACKUP_INTERVAL_HOURS: i64 = 24;
+/// How often the backup task checks whether a backup is due
+pub const BACKUP_CHECK_INTERVAL_SECS: u64 = 3600; // 1 hour
+/// Number of backups kept; older ones are deleted
+pub const BACKUP_KEEP_COUNT: usize = 5;
+/// Backups live in this directory next to the database
+pub const BACKUP_DIR_NAME: &str = "backups";
+pub const BACKUP_FILE_PREFIX: &str = "clipray-";
+// Backup file names sort by the time they were taken
+pub const BACKUP_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+/// Pages copied per step of an online backup, with a short pause in between
+/// so other connections can write
+pub const BACKUP_PAGES_PER_STEP: i32 = 100;
+pub const BACKUP_STEP_PAUSE_MS: u64 = 10;
+
+/// History database file in the app data directory
+pub const DATABASE_FILE_NAME: &str = "clipray.db";
+/// Written to the data directory when startup fails, since a tray app has no console
+pub const STARTUP_LOG_FILE_NAME: &str = "startup-error.log";
@@ -1,116 +1,524 @@
-use chrono::Utc;
-use rusqlite::{Connection, Result};
-use std::path::PathBuf;
+use base64::{engine::general_purpose, Engine};
+use chrono::{DateTime, Utc};
+use rusqlite::{
+    backup::Backup,
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, Value, ValueRef},
+    Connection, OptionalExtension, Row, ToSql,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 use crate::libs::{
-    constants::{DEFAULT_HOTKEY, DEFAULT_MAX_HISTORY_COUNT},
-    types::{AppConfig, ClipboardItem, ThemeConfig, ThemePreset},
+    archive::{Archive, ArchivedItem, ArchivedSnippet},
+    constants::{
+        BACKUP_PAGES_PER_STEP, BACKUP_STEP_PAUSE_MS, DEFAULT_HOTKEY, DEFAULT_MAX_HISTORY_COUNT,
+        DEFAULT_PAGE_SIZE, IMAGE_MIME_TYPE, MAX_PAGE_SIZE, PINNED_RANK_BOOST, SEARCH_HIGHLIGHT_END,
+        SEARCH_HIGHLIGHT_START, SEARCH_SNIPPET_ELLIPSIS, SEARCH_SNIPPET_TOKENS,
+        SNIPPET_NAME_MAX_CHARS,
+    },
+    encryption::{export_database, unlock_connection, DatabaseKey},
+    error::ClipRayError,
+    hash::content_hash,
+    migrations::{run_migrations, MigrationError},
+    search::{build_fts_query, search_text},
+    template::Template,
+    thumbnail::{process_image, ProcessedImage},
+    types::{
+        AppConfig, CaptureDetails, CaptureSettings, ClipboardContent, ClipboardItem,
+        ClipboardSnapshot, ContentType, EncryptionMode, ExclusionRules, HistoryPage, HistoryQuery,
+        ImageInfo, ImportMode, ImportSummary, PauseStatus, RetentionPolicy, SearchResult,
+        Selection, SensitiveRules, Snippet, SnippetDraft, SnippetFolder, SnippetSearchResult,
+        SortOrder, SourceApp, Tag, ThemeConfig, ThemePreset,
+    },
 };
 
+/// Result of the database methods: SQLite failures and the methods' own checks
+/// are both reported as `ClipRayError`
+pub type Result<T, E = ClipRayError> = std::result::Result<T, E>;
+
+/// Open a database file and apply its key
+fn reopen(
+    db_path: &Path,
+    key: Option<&DatabaseKey>,
+) -> std::result::Result<Connection, MigrationError> {
+    let conn = Connection::open(db_path)?;
+    unlock_connection(&conn, key)?;
+    Ok(conn)
+}
+
 // Structure to manage database connections
 pub struct DatabaseManager {
     connection: Mutex<Connection>,
+    /// File the connection was opened from; `None` for in-memory databases
+    path: Option<PathBuf>,
+    /// Kept to reopen the file after a failed key change
+    key: Mutex<Option<DatabaseKey>>,
 }
 
 impl DatabaseManager {
-    pub fn new(db_path: PathBuf) -> Result<Self> {
+    pub fn new(db_path: PathBuf) -> std::result::Result<Self, MigrationError> {
+        Self::open(&db_path, None)
+    }
+
+    /// Open a database file, encrypted with `key` or unencrypted for `None`
+    pub fn open(
+        db_path: &Path,
+        key: Option<&DatabaseKey>,
+    ) -> std::result::Result<Self, MigrationError> {
         let conn = Connection::open(db_path)?;
+        unlock_connection(&conn, key)?;
+        let mut db = Self::initialize(conn)?;
+        db.path = Some(db_path.to_path_buf());
+        db.key = Mutex::new(key.cloned());
+        Ok(db)
+    }
 
-        // Create tables
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS clipboard_history (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                content TEXT NOT NULL,
-                content_type TEXT NOT NULL,
-                timestamp TEXT NOT NULL,
-                pinned BOOLEAN DEFAULT FALSE
-            )",
-            [],
-        )?;
+    pub fn path(&self) -> Option<PathBuf> {
+        self.path.clone()
+    }
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS app_config (
-                key TEXT PRIMARY KEY,
-                value TEXT NOT NULL
-            )",
-            [],
+    pub fn encryption_mode(&self) -> EncryptionMode {
+        self.key
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(DatabaseKey::mode)
+            .unwrap_or_default()
+    }
+
+    /// Re-encrypt the database with `key`, or decrypt it for `None`.
+    /// The database is exported to a new file which then replaces the current one.
+    pub fn change_key(&self, key: Option<&DatabaseKey>) -> std::result::Result<(), MigrationError> {
+        let db_path = self.path.as_ref().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "In-memory databases cannot be encrypted",
+            )
+        })?;
+        let export_path = db_path.with_extension("db.rekey");
+        let _ = std::fs::remove_file(&export_path);
+
+        let mut conn = self.connection.lock().unwrap();
+        export_database(&conn, &export_path, key)?;
+        if let Err(e) = reopen(&export_path, key) {
+            let _ = std::fs::remove_file(&export_path);
+            return Err(e);
+        }
+
+        // The current connection is kept, and put back on every error, until the new
+        // file is open. Windows cannot replace an open file, so there it is closed
+        // first and the previous file is reopened on errors instead.
+        let old = std::mem::replace(&mut *conn, Connection::open_in_memory()?);
+        #[cfg(windows)]
+        let old = match old.close() {
+            Ok(()) => None,
+            Err((old, e)) => {
+                *conn = old;
+                return Err(e.into());
+            }
+        };
+        #[cfg(not(windows))]
+        let old = Some(old);
+
+        let mut current_key = self.key.lock().unwrap();
+        let replaced = std::fs::rename(&export_path, db_path)
+            .map_err(MigrationError::from)
+            .and_then(|()| reopen(db_path, key));
+        match replaced {
+            Ok(reopened) => {
+                *conn = reopened;
+                *current_key = key.cloned();
+                Ok(())
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(&export_path);
+                *conn = match old {
+                    Some(old) => old,
+                    None => reopen(db_path, current_key.as_ref())?,
+                };
+                Err(e)
+            }
+        }
+    }
+
+    /// Copy the database to a new file at `target` with SQLite's online backup API,
+    /// encrypted with the same key. The copy is made from a separate connection, so
+    /// the database stays usable while it runs.
+    pub fn backup_to(&self, target: &Path) -> std::result::Result<(), MigrationError> {
+        let db_path = self.path.as_ref().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "In-memory databases cannot be backed up",
+            )
+        })?;
+        // Held so the key cannot change while the backup runs
+        let key = self.key.lock().unwrap();
+
+        let source = Connection::open(db_path)?;
+        unlock_connection(&source, key.as_ref())?;
+        let mut destination = Connection::open(target)?;
+        unlock_connection(&destination, key.as_ref())?;
+
+        Backup::new(&source, &mut destination)?.run_to_completion(
+            BACKUP_PAGES_PER_STEP,
+            std::time::Duration::from_millis(BACKUP_STEP_PAUSE_MS),
+            None,
         )?;
+        Ok(())
+    }
 
-        // Insert default settings
-        let _ = conn.execute(
-            "INSERT OR IGNORE INTO app_config (key, value) VALUES ('max_history_count', '50')",
-            [],
-        );
-        let _ = conn.execute(
-            "INSERT OR IGNORE INTO app_config (key, value) VALUES ('hotkey', ?1)",
-            [DEFAULT_HOTKEY],
-        );
-        let _ = conn.execute(
-            "INSERT OR IGNORE INTO app_config (key, value) VALUES ('theme_preset', 'default')",
-            [],
-        );
-        let _ = conn.execute(
-            "INSERT OR IGNORE INTO app_config (key, value) VALUES ('autostart', 'false')",
-            [],
-        );
+    /// Run SQLite's quick integrity check
+    pub fn check_integrity(&self) -> std::result::Result<(), MigrationError> {
+        let conn = self.connection.lock().unwrap();
+        let mut stmt = conn.prepare("PRAGMA quick_check")?;
+        let problems: Vec<String> = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        if problems == ["ok"] {
+            Ok(())
+        } else {
+            Err(MigrationError::Corrupt(problems.join("; ")))
+        }
+    }
+
+    /// Apply pending migrations and default settings to an opened connection
+    fn initialize(mut conn: Connection) -> std::result::Result<Self, MigrationError> {
+        run_migrations(&mut conn)?;
+
+        insert_default_config(&conn);
 
         Ok(DatabaseManager {
             connection: Mutex::new(conn),
+            path: None,
+            key: Mutex::new(None),
         })
     }
 
-    pub fn add_clipboard_item(&self, content: &str, content_type: &str) -> Result<()> {
-        // Apply history count limit (only for non-pinned items)
+    /// Add a text item, for tests; captures go through `add_captured_snapshot`.
+    /// Copying content that is already in the history bumps the existing item
+    /// (keeping its id and pin) instead of adding a new one.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn add_clipboard_item(&self, content: &str) -> Result<()> {
+        let hash = content_hash(content.as_bytes());
+        self.store_item(
+            ContentType::Text,
+            content,
+            &hash,
+            None,
+            &[],
+            &CaptureDetails::default(),
+        )
+    }
+
+    /// Add an image item, for tests. The image is stored as a PNG blob with a
+    /// thumbnail and its dimensions; the content column stays empty.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn add_image_item(&self, image_bytes: &[u8]) -> Result<()> {
+        let image = process_image(image_bytes).map_err(ClipRayError::InvalidInput)?;
+        let hash = content_hash(&image.png);
+        self.store_item(
+            ContentType::Image,
+            "",
+            &hash,
+            Some(&image),
+            &[],
+            &CaptureDetails::default(),
+        )
+    }
+
+    /// Add every representation of one copy as a single item.
+    /// The item's content is the first available of files, text, image, HTML
+    /// and RTF; the other formats are stored with it and restored on copy.
+    pub fn add_snapshot(&self, snapshot: &ClipboardSnapshot) -> Result<()> {
+        self.add_expiring_snapshot(snapshot, None)
+    }
+
+    /// Like `add_snapshot`, for sensitive content that is deleted at `expires_at`
+    pub fn add_expiring_snapshot(
+        &self,
+        snapshot: &ClipboardSnapshot,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        self.add_captured_snapshot(
+            snapshot,
+            &CaptureDetails {
+                expires_at,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Like `add_snapshot`, recording how the copy was captured
+    pub fn add_captured_snapshot(
+        &self,
+        snapshot: &ClipboardSnapshot,
+        details: &CaptureDetails,
+    ) -> Result<()> {
+        let files = snapshot.files.as_ref().map(|files| files.join("\n"));
+        let content_type = if files.is_some() {
+            ContentType::Files
+        } else if snapshot.text.is_some() {
+            ContentType::Text
+        } else if snapshot.image.is_some() {
+            ContentType::Image
+        } else if snapshot.html.is_some() {
+            ContentType::Html
+        } else if snapshot.rtf.is_some() {
+            ContentType::Rtf
+        } else {
+            return Err(ClipRayError::InvalidInput(
+                "Clipboard snapshot is empty".to_string(),
+            ));
+        };
+
+        let image = match &snapshot.image {
+            Some(bytes) => match process_image(bytes) {
+                Ok(image) => Some(image),
+                Err(e) if content_type == ContentType::Image => {
+                    return Err(ClipRayError::InvalidInput(e))
+                }
+                Err(e) => {
+                    // A broken extra image should not lose the rest of the copy
+                    eprintln!("Ignoring unreadable image format: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let mut content = String::new();
+        let mut formats = Vec::new();
+        for (format, data) in [
+            (ContentType::Files, files),
+            (ContentType::Text, snapshot.text.clone()),
+            (ContentType::Html, snapshot.html.clone()),
+            (ContentType::Rtf, snapshot.rtf.clone()),
+        ] {
+            match data {
+                Some(data) if format == content_type => content = data,
+                Some(data) => formats.push((format, data)),
+                None => {}
+            }
+        }
+
+        let hash = match &image {
+            Some(image) if content_type == ContentType::Image => content_hash(&image.png),
+            _ => content_hash(content.as_bytes()),
+        };
+        self.store_item(
+            content_type,
+            &content,
+            &hash,
+            image.as_ref(),
+            &formats,
+            details,
+        )
+    }
+
+    // Insert a new item, or bump the existing one with the same hash.
+    // Additional formats and the image are replaced by those of the latest copy.
+    // A bumped item keeps its expiry unless the new copy sets one, and takes the
+    // selection of the new copy.
+    fn store_item(
+        &self,
+        content_type: ContentType,
+        content: &str,
+        hash: &str,
+        image: Option<&ProcessedImage>,
+        formats: &[(ContentType, String)],
+        details: &CaptureDetails,
+    ) -> Result<()> {
+        // Apply history limits (pinned and tagged items are kept)
         let max_count: u32 = self.get_max_history_count()?;
+        let policy: RetentionPolicy = self.get_retention_policy()?;
+
+        let mut conn = self.connection.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+        let expires_at = details.expires_at.map(|at| at.to_rfc3339());
+        let tx = conn.transaction()?;
+
+        let id = match find_item_by_hash(&tx, content_type, hash)? {
+            Some(id) => {
+                tx.execute(
+                    "UPDATE clipboard_history
+                     SET last_used = ?1, copy_count = copy_count + 1,
+                         expires_at = COALESCE(?2, expires_at), selection = ?3
+                     WHERE id = ?4",
+                    rusqlite::params![now, expires_at, details.selection, id],
+                )?;
+                id
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO clipboard_history (content, content_type, timestamp, pinned, content_hash, last_used, expires_at, selection, search_text)
+                     VALUES (?1, ?2, ?3, FALSE, ?4, ?3, ?5, ?6, ?7)",
+                    rusqlite::params![
+                        content,
+                        content_type,
+                        now,
+                        hash,
+                        expires_at,
+                        details.selection,
+                        search_text(content_type, content)
+                    ],
+                )?;
+                tx.last_insert_rowid()
+            }
+        };
+
+        // An unknown source keeps the one from an earlier copy
+        if let Some(source_app) = &details.source_app {
+            tx.execute(
+                "UPDATE clipboard_history
+                 SET source_process = ?1, source_class = ?2, source_title = ?3
+                 WHERE id = ?4",
+                rusqlite::params![source_app.process, source_app.class, source_app.title, id],
+            )?;
+        }
+
+        tx.execute("DELETE FROM clipboard_formats WHERE item_id = ?1", [id])?;
+        for (format, data) in formats {
+            tx.execute(
+                "INSERT INTO clipboard_formats (item_id, format, data) VALUES (?1, ?2, ?3)",
+                rusqlite::params![id, format, data],
+            )?;
+        }
+
+        match image {
+            Some(image) => {
+                tx.execute(
+                    "INSERT OR REPLACE INTO clipboard_images (item_id, data, thumbnail, width, height)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![id, image.png, image.thumbnail, image.width, image.height],
+                )?;
+            }
+            None => {
+                tx.execute("DELETE FROM clipboard_images WHERE item_id = ?1", [id])?;
+            }
+        }
+
+        trim_history(&tx, max_count)?;
+        apply_retention_policy(&tx, &policy, Utc::now())?;
+        tx.commit()?;
+
+        Ok(())
+    }
 
+    /// Enforce the history limit and retention policy, as of `now`.
+    /// Returns the number of deleted items.
+    pub fn apply_retention(&self, now: DateTime<Utc>) -> Result<usize> {
+        let max_count: u32 = self.get_max_history_count()?;
+        let policy: RetentionPolicy = self.get_retention_policy()?;
+
+        let mut conn = self.connection.lock().unwrap();
+        let tx = conn.transaction()?;
+        let deleted = trim_history(&tx, max_count)? + apply_retention_policy(&tx, &policy, now)?;
+        tx.commit()?;
+
+        Ok(deleted)
+    }
+
+    /// All stored representations of an item, for writing it back to the clipboard
+    pub fn get_item_snapshot(&self, id: i64) -> Result<Option<ClipboardSnapshot>> {
         let conn = self.connection.lock().unwrap();
-        let timestamp = Utc::now().to_rfc3339();
+        Ok(item_snapshot(&conn, id)?)
+    }
 
-        // Delete if the same content already exists (to avoid duplicates)
-        conn.execute(
-            "DELETE FROM clipboard_history WHERE content = ?1 AND content_type = ?2",
-            [content, content_type],
+    /// Mark an item as sensitive until `expires_at`, or keep it for good with `None`.
+    /// Returns false when there is no such item.
+    pub fn set_item_expiry(&self, id: i64, expires_at: Option<DateTime<Utc>>) -> Result<bool> {
+        let conn = self.connection.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE clipboard_history SET expires_at = ?1 WHERE id = ?2",
+            rusqlite::params![expires_at.map(|at| at.to_rfc3339()), id],
         )?;
+        Ok(updated > 0)
+    }
 
-        // Add new item
-        conn.execute(
-            "INSERT INTO clipboard_history (content, content_type, timestamp, pinned) VALUES (?1, ?2, ?3, FALSE)",
-            [content, content_type, &timestamp],
+    /// Move an item to the top of the history as if it was copied again, without
+    /// touching its contents. Returns false when there is no such item.
+    pub fn mark_item_used(&self, id: i64) -> Result<bool> {
+        let conn = self.connection.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE clipboard_history SET last_used = ?1, copy_count = copy_count + 1 WHERE id = ?2",
+            rusqlite::params![Utc::now().to_rfc3339(), id],
         )?;
+        Ok(updated > 0)
+    }
 
-        conn.execute(
-            "DELETE FROM clipboard_history WHERE id IN (
-                SELECT id FROM clipboard_history 
-                WHERE pinned = FALSE 
-                ORDER BY timestamp DESC 
-                LIMIT -1 OFFSET ?1
-            )",
-            [max_count],
-        )?;
+    /// Delete unpinned items whose expiry time has passed by `now`.
+    /// Returns their contents, so they can also be removed from the clipboard.
+    pub fn take_expired_items(&self, now: DateTime<Utc>) -> Result<Vec<ClipboardSnapshot>> {
+        let mut conn = self.connection.lock().unwrap();
+        let tx = conn.transaction()?;
 
-        Ok(())
+        let ids: Vec<i64> = {
+            let mut stmt = tx.prepare(
+                "SELECT id FROM clipboard_history
+                 WHERE expires_at IS NOT NULL AND expires_at <= ?1 AND pinned = FALSE",
+            )?;
+            let rows = stmt.query_map([now.to_rfc3339()], |row| row.get(0))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+
+        let mut expired = Vec::new();
+        for id in ids {
+            expired.extend(item_snapshot(&tx, id)?);
+            tx.execute("DELETE FROM clipboard_history WHERE id = ?1", [id])?;
+        }
+        tx.commit()?;
+
+        Ok(expired)
+    }
+
+    /// Items copied most often, most recently used first among equals
+    pub fn get_most_used_items(&self, limit: u32) -> Result<Vec<ClipboardItem>> {
+        let conn = self.connection.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}
+             FROM {}
+             WHERE h.copy_count > 1
+             ORDER BY h.copy_count DESC, h.last_used DESC
+             LIMIT ?1",
+            ITEM_COLUMNS, ITEM_SOURCE
+        ))?;
+
+        let item_iter = stmt.query_map([limit], row_to_clipboard_item)?;
+
+        let mut items = Vec::new();
+        for item in item_iter {
+            items.push(item?);
+        }
+
+        Ok(items)
+    }
+
+    /// Full-size PNG of an image item
+    pub fn get_image_data(&self, id: i64) -> Result<Option<Vec<u8>>> {
+        let conn = self.connection.lock().unwrap();
+        Ok(conn
+            .query_row(
+                "SELECT data FROM clipboard_images WHERE item_id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .optional()?)
     }
 
     pub fn get_clipboard_history(&self) -> Result<Vec<ClipboardItem>> {
         let max_count: u32 = self.get_max_history_count()?;
         let conn = self.connection.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, content, content_type, timestamp, pinned 
-             FROM clipboard_history 
-             ORDER BY pinned DESC, timestamp DESC
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}
+             FROM {}
+             ORDER BY h.pinned DESC, h.last_used DESC
              LIMIT ?1",
-        )?;
+            ITEM_COLUMNS, ITEM_SOURCE
+        ))?;
 
-        let item_iter = stmt.query_map([max_count], |row| {
-            Ok(ClipboardItem {
-                id: row.get(0)?,
-                content: row.get(1)?,
-                content_type: row.get(2)?,
-                timestamp: row.get(3)?,
-                pinned: row.get(4)?,
-            })
-        })?;
+        let item_iter = stmt.query_map([max_count], row_to_clipboard_item)?;
 
         let mut items = Vec::new();
         for item in item_iter {
@@ -120,6 +528,157 @@ impl DatabaseManager {
         Ok(items)
     }
 
+    /// Filtered, cursor-paginated history query.
+    /// Pages are keyed on (last_used, id), so items added between calls don't
+    /// shift or duplicate entries on later pages.
+    pub fn query_clipboard_history(&self, query: &HistoryQuery) -> Result<HistoryPage> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut params: Vec<Value> = Vec::new();
+
+        if let Some(content_type) = &query.content_type {
+            conditions.push("h.content_type = ?");
+            params.push(Value::Text(content_type.as_str().to_string()));
+        }
+        if let Some(pinned) = query.pinned {
+            conditions.push("h.pinned = ?");
+            params.push(Value::Integer(pinned as i64));
+        }
+        if let Some(tag_id) = query.tag_id {
+            conditions.push("h.id IN (SELECT item_id FROM clipboard_item_tags WHERE tag_id = ?)");
+            params.push(Value::Integer(tag_id));
+        }
+        if let Some(selection) = query.selection {
+            conditions.push("h.selection = ?");
+            params.push(Value::Text(selection.as_str().to_string()));
+        }
+        if let Some(source_app) = &query.source_app {
+            conditions
+                .push("(h.source_process = ? COLLATE NOCASE OR h.source_class = ? COLLATE NOCASE)");
+            params.push(Value::Text(source_app.clone()));
+            params.push(Value::Text(source_app.clone()));
+        }
+        if let Some(since) = &query.since {
+            conditions.push("h.last_used >= ?");
+            params.push(Value::Text(normalize_timestamp(since)?));
+        }
+        if let Some(until) = &query.until {
+            conditions.push("h.last_used < ?");
+            params.push(Value::Text(normalize_timestamp(until)?));
+        }
+        if let Some(cursor) = &query.cursor {
+            let (last_used, id) = decode_cursor(cursor)?;
+            conditions.push(match query.sort {
+                SortOrder::NewestFirst => "(h.last_used < ? OR (h.last_used = ? AND h.id < ?))",
+                SortOrder::OldestFirst => "(h.last_used > ? OR (h.last_used = ? AND h.id > ?))",
+            });
+            params.push(Value::Text(last_used.clone()));
+            params.push(Value::Text(last_used));
+            params.push(Value::Integer(id));
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let order_clause = match query.sort {
+            SortOrder::NewestFirst => "ORDER BY h.last_used DESC, h.id DESC",
+            SortOrder::OldestFirst => "ORDER BY h.last_used ASC, h.id ASC",
+        };
+        // Fetch one extra row to know whether another page exists
+        params.push(Value::Integer(limit as i64 + 1));
+
+        let conn = self.connection.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}
+             FROM {}
+             {}
+             {}
+             LIMIT ?",
+            ITEM_COLUMNS, ITEM_SOURCE, where_clause, order_clause
+        ))?;
+
+        let item_iter = stmt.query_map(
+            rusqlite::params_from_iter(params.iter()),
+            row_to_clipboard_item,
+        )?;
+
+        let mut items = Vec::new();
+        for item in item_iter {
+            items.push(item?);
+        }
+
+        let next_cursor = if items.len() > limit as usize {
+            items.truncate(limit as usize);
+            items
+                .last()
+                .map(|item| encode_cursor(&item.last_used, item.id))
+        } else {
+            None
+        };
+
+        Ok(HistoryPage { items, next_cursor })
+    }
+
+    /// Full-text search over the text of items, file names included, best matches first.
+    /// When `boost_pinned` is set, pinned items rank above equally good matches.
+    pub fn search_clipboard_history(
+        &self,
+        query: &str,
+        limit: u32,
+        boost_pinned: bool,
+    ) -> Result<Vec<SearchResult>> {
+        let fts_query = match build_fts_query(query) {
+            Some(q) => q,
+            None => return Ok(Vec::new()),
+        };
+
+        let conn = self.connection.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {},
+                    snippet(clipboard_fts, 0, ?2, ?3, ?4, ?5) AS snippet,
+                    bm25(clipboard_fts) * CASE WHEN h.pinned AND ?6 THEN ?7 ELSE 1.0 END AS score
+             FROM {}
+             JOIN clipboard_fts ON clipboard_fts.rowid = h.id
+             WHERE clipboard_fts MATCH ?1
+             ORDER BY score, h.last_used DESC
+             LIMIT ?8",
+            ITEM_COLUMNS, ITEM_SOURCE
+        ))?;
+
+        let result_iter = stmt.query_map(
+            rusqlite::params![
+                fts_query,
+                SEARCH_HIGHLIGHT_START,
+                SEARCH_HIGHLIGHT_END,
+                SEARCH_SNIPPET_ELLIPSIS,
+                SEARCH_SNIPPET_TOKENS,
+                boost_pinned,
+                PINNED_RANK_BOOST,
+                limit,
+            ],
+            |row| {
+                Ok(SearchResult {
+                    item: row_to_clipboard_item(row)?,
+                    snippet: row.get("snippet")?,
+                    rank: row.get("score")?,
+                })
+            },
+        )?;
+
+        let mut results = Vec::new();
+        for result in result_iter {
+            results.push(result?);
+        }
+
+        Ok(results)
+    }
+
     pub fn delete_clipboard_item(&self, id: i64) -> Result<()> {
         let conn = self.connection.lock().unwrap();
         conn.execute("DELETE FROM clipboard_history WHERE id = ?1", [id])?;
@@ -135,16 +694,538 @@ impl DatabaseManager {
         Ok(())
     }
 
+    /// All tags, sorted by name
+    pub fn list_tags(&self) -> Result<Vec<Tag>> {
+        let conn = self.connection.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, name FROM tags ORDER BY name")?;
+        let tag_iter = stmt.query_map([], |row| {
+            Ok(Tag {
+                id: row.get(0)?,
+                name: row.get(1)?,
+            })
+        })?;
+
+        let mut tags = Vec::new();
+        for tag in tag_iter {
+            tags.push(tag?);
+        }
+
+        Ok(tags)
+    }
+
+    /// Names are trimmed and must be unique, ignoring case
+    pub fn create_tag(&self, name: &str) -> Result<Tag> {
+        let name = required_name(name, "Tag name")?;
+        let conn = self.connection.lock().unwrap();
+        conn.execute("INSERT INTO tags (name) VALUES (?1)", [&name])
+            .map_err(|e| already_exists(e, format!("A tag named '{}' already exists", name)))?;
+        Ok(Tag {
+            id: conn.last_insert_rowid(),
+            name,
+        })
+    }
+
+    /// Returns false if there is no tag with this id
+    pub fn rename_tag(&self, id: i64, name: &str) -> Result<bool> {
+        let name = required_name(name, "Tag name")?;
+        let conn = self.connection.lock().unwrap();
+        let updated = conn
+            .execute(
+                "UPDATE tags SET name = ?1 WHERE id = ?2",
+                rusqlite::params![name, id],
+            )
+            .map_err(|e| already_exists(e, format!("A tag named '{}' already exists", name)))?;
+        Ok(updated > 0)
+    }
+
+    /// Items keep existing without the tag; returns false if there is no tag with this id
+    pub fn delete_tag(&self, id: i64) -> Result<bool> {
+        let conn = self.connection.lock().unwrap();
+        Ok(conn.execute("DELETE FROM tags WHERE id = ?1", [id])? > 0)
+    }
+
+    /// Assign or remove a tag; returns false if the item or the tag does not exist
+    pub fn set_item_tag(&self, item_id: i64, tag_id: i64, tagged: bool) -> Result<bool> {
+        let conn = self.connection.lock().unwrap();
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM clipboard_history WHERE id = ?1)
+                AND EXISTS (SELECT 1 FROM tags WHERE id = ?2)",
+            [item_id, tag_id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Ok(false);
+        }
+
+        let sql = if tagged {
+            "INSERT OR IGNORE INTO clipboard_item_tags (item_id, tag_id) VALUES (?1, ?2)"
+        } else {
+            "DELETE FROM clipboard_item_tags WHERE item_id = ?1 AND tag_id = ?2"
+        };
+        conn.execute(sql, [item_id, tag_id])?;
+        Ok(true)
+    }
+
+    /// All snippets, by folder and then by name
+    pub fn list_snippets(&self) -> Result<Vec<Snippet>> {
+        let conn = self.connection.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}
+             FROM snippets s
+             LEFT JOIN snippet_folders f ON f.id = s.folder_id
+             ORDER BY f.name IS NOT NULL, f.name, s.name COLLATE NOCASE",
+            SNIPPET_COLUMNS
+        ))?;
+
+        let snippet_iter = stmt.query_map([], row_to_snippet)?;
+
+        let mut snippets = Vec::new();
+        for snippet in snippet_iter {
+            snippets.push(snippet?);
+        }
+
+        Ok(snippets)
+    }
+
+    pub fn get_snippet(&self, id: i64) -> Result<Option<Snippet>> {
+        let conn = self.connection.lock().unwrap();
+        Ok(get_snippet(&conn, id)?)
+    }
+
+    /// Abbreviations are matched ignoring case
+    pub fn find_snippet_by_abbreviation(&self, abbreviation: &str) -> Result<Option<Snippet>> {
+        let conn = self.connection.lock().unwrap();
+        Ok(conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM snippets s WHERE s.abbreviation = ?1",
+                    SNIPPET_COLUMNS
+                ),
+                [abbreviation.trim()],
+                row_to_snippet,
+            )
+            .optional()?)
+    }
+
+    pub fn create_snippet(&self, draft: &SnippetDraft) -> Result<Snippet> {
+        let conn = self.connection.lock().unwrap();
+        let draft = normalize_snippet_draft(&conn, draft)?;
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO snippets (name, abbreviation, content, folder_id, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            rusqlite::params![
+                draft.name,
+                draft.abbreviation,
+                draft.content,
+                draft.folder_id,
+                now
+            ],
+        )
+        .map_err(|e| duplicate_abbreviation(e, &draft))?;
+
+        let id = conn.last_insert_rowid();
+        get_snippet(&conn, id)?.ok_or_else(|| ClipRayError::not_found("Snippet", id))
+    }
+
+    /// Replace every field of a snippet; returns `None` if there is no snippet with this id
+    pub fn update_snippet(&self, id: i64, draft: &SnippetDraft) -> Result<Option<Snippet>> {
+        let conn = self.connection.lock().unwrap();
+        let draft = normalize_snippet_draft(&conn, draft)?;
+        conn.execute(
+            "UPDATE snippets
+             SET name = ?1, abbreviation = ?2, content = ?3, folder_id = ?4, updated_at = ?5
+             WHERE id = ?6",
+            rusqlite::params![
+                draft.name,
+                draft.abbreviation,
+                draft.content,
+                draft.folder_id,
+                Utc::now().to_rfc3339(),
+                id
+            ],
+        )
+        .map_err(|e| duplicate_abbreviation(e, &draft))?;
+
+        Ok(get_snippet(&conn, id)?)
+    }
+
+    /// Returns false if there is no snippet with this id
+    pub fn delete_snippet(&self, id: i64) -> Result<bool> {
+        let conn = self.connection.lock().unwrap();
+        Ok(conn.execute("DELETE FROM snippets WHERE id = ?1", [id])? > 0)
+    }
+
+    /// Save the text of a history item as a snippet, named after its first line unless
+    /// `name` is given. Returns `None` if there is no item with this id.
+    pub fn create_snippet_from_item(
+        &self,
+        item_id: i64,
+        name: Option<&str>,
+    ) -> Result<Option<Snippet>> {
+        let snapshot = {
+            let conn = self.connection.lock().unwrap();
+            item_snapshot(&conn, item_id)?
+        };
+        let Some(snapshot) = snapshot else {
+            return Ok(None);
+        };
+        let content = snapshot
+            .text
+            .ok_or_else(|| ClipRayError::InvalidInput(format!("Item {} has no text", item_id)))?;
+
+        let draft = SnippetDraft {
+            name: name.map_or_else(|| default_snippet_name(&content), String::from),
+            content,
+            ..Default::default()
+        };
+        self.create_snippet(&draft).map(Some)
+    }
+
+    /// Full-text search over snippet names, abbreviations and content, best matches first.
+    /// A snippet whose abbreviation is exactly the query is always the first result.
+    pub fn search_snippets(&self, query: &str, limit: u32) -> Result<Vec<SnippetSearchResult>> {
+        let exact = self.find_snippet_by_abbreviation(query)?;
+
+        let mut results = Vec::new();
+        if let Some(fts_query) = build_fts_query(query) {
+            let conn = self.connection.lock().unwrap();
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {},
+                        snippet(snippets_fts, 2, ?2, ?3, ?4, ?5) AS excerpt,
+                        bm25(snippets_fts) AS score
+                 FROM snippets s
+                 JOIN snippets_fts ON snippets_fts.rowid = s.id
+                 WHERE snippets_fts MATCH ?1
+                 ORDER BY score, s.updated_at DESC
+                 LIMIT ?6",
+                SNIPPET_COLUMNS
+            ))?;
+
+            let result_iter = stmt.query_map(
+                rusqlite::params![
+                    fts_query,
+                    SEARCH_HIGHLIGHT_START,
+                    SEARCH_HIGHLIGHT_END,
+                    SEARCH_SNIPPET_ELLIPSIS,
+                    SEARCH_SNIPPET_TOKENS,
+                    limit,
+                ],
+                |row| {
+                    Ok(SnippetSearchResult {
+                        snippet: row_to_snippet(row)?,
+                        excerpt: row.get("excerpt")?,
+                        rank: row.get("score")?,
+                    })
+                },
+            )?;
+            for result in result_iter {
+                results.push(result?);
+            }
+        }
+
+        if let Some(snippet) = exact {
+            let result = match results.iter().position(|r| r.snippet.id == snippet.id) {
+                Some(index) => results.remove(index),
+                None => SnippetSearchResult {
+                    excerpt: snippet.content.clone(),
+                    snippet,
+                    rank: 0.0,
+                },
+            };
+            results.insert(0, result);
+            results.truncate(limit as usize);
+        }
+
+        Ok(results)
+    }
+
+    /// All snippet folders, sorted by name
+    pub fn list_snippet_folders(&self) -> Result<Vec<SnippetFolder>> {
+        let conn = self.connection.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, name FROM snippet_folders ORDER BY name")?;
+        let folder_iter = stmt.query_map([], |row| {
+            Ok(SnippetFolder {
+                id: row.get(0)?,
+                name: row.get(1)?,
+            })
+        })?;
+
+        let mut folders = Vec::new();
+        for folder in folder_iter {
+            folders.push(folder?);
+        }
+
+        Ok(folders)
+    }
+
+    /// Names are trimmed and must be unique, ignoring case
+    pub fn create_snippet_folder(&self, name: &str) -> Result<SnippetFolder> {
+        let name = required_name(name, "Folder name")?;
+        let conn = self.connection.lock().unwrap();
+        conn.execute("INSERT INTO snippet_folders (name) VALUES (?1)", [&name])
+            .map_err(|e| already_exists(e, format!("A folder named '{}' already exists", name)))?;
+        Ok(SnippetFolder {
+            id: conn.last_insert_rowid(),
+            name,
+        })
+    }
+
+    /// Returns false if there is no folder with this id
+    pub fn rename_snippet_folder(&self, id: i64, name: &str) -> Result<bool> {
+        let name = required_name(name, "Folder name")?;
+        let conn = self.connection.lock().unwrap();
+        let updated = conn
+            .execute(
+                "UPDATE snippet_folders SET name = ?1 WHERE id = ?2",
+                rusqlite::params![name, id],
+            )
+            .map_err(|e| already_exists(e, format!("A folder named '{}' already exists", name)))?;
+        Ok(updated > 0)
+    }
+
+    /// Its snippets are kept outside any folder; returns false if there is no folder with this id
+    pub fn delete_snippet_folder(&self, id: i64) -> Result<bool> {
+        let conn = self.connection.lock().unwrap();
+        Ok(conn.execute("DELETE FROM snippet_folders WHERE id = ?1", [id])? > 0)
+    }
+
+    /// Every history item with all its formats, oldest first, for export.
+    /// Sensitive items waiting to expire are left out.
+    pub fn export_items(&self) -> Result<Vec<ArchivedItem>> {
+        let conn = self.connection.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, content_type, content, timestamp, last_used, copy_count, pinned,
+                    selection, source_process, source_class, source_title
+             FROM clipboard_history WHERE expires_at IS NULL ORDER BY id",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>("id")?,
+                    ArchivedItem {
+                        content_type: row.get("content_type")?,
+                        content: row.get("content")?,
+                        formats: BTreeMap::new(),
+                        image: None,
+                        timestamp: row.get("timestamp")?,
+                        last_used: row.get("last_used")?,
+                        copy_count: row.get("copy_count")?,
+                        pinned: row.get("pinned")?,
+                        tags: Vec::new(),
+                        selection: row.get("selection")?,
+                        source_app: row_to_source_app(row)?,
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut formats =
+            conn.prepare("SELECT format, data FROM clipboard_formats WHERE item_id = ?1")?;
+        let mut tags = conn.prepare(
+            "SELECT t.name FROM tags t JOIN clipboard_item_tags it ON it.tag_id = t.id
+             WHERE it.item_id = ?1 ORDER BY t.name",
+        )?;
+        let mut items = Vec::with_capacity(rows.len());
+        for (id, mut item) in rows {
+            item.formats = formats
+                .query_map([id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<_>>()?;
+            item.image = conn
+                .query_row(
+                    "SELECT data FROM clipboard_images WHERE item_id = ?1",
+                    [id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            item.tags = tags
+                .query_map([id], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+            items.push(item);
+        }
+        Ok(items)
+    }
+
+    /// Every snippet with the name of its folder, for export
+    pub fn export_snippets(&self) -> Result<Vec<ArchivedSnippet>> {
+        let conn = self.connection.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT s.name, s.abbreviation, s.content, f.name AS folder, s.created_at, s.updated_at
+             FROM snippets s LEFT JOIN snippet_folders f ON f.id = s.folder_id
+             ORDER BY s.id",
+        )?;
+        let snippets = stmt
+            .query_map([], |row| {
+                Ok(ArchivedSnippet {
+                    name: row.get("name")?,
+                    abbreviation: row.get("abbreviation")?,
+                    content: row.get("content")?,
+                    folder: row.get("folder")?,
+                    created_at: row.get("created_at")?,
+                    updated_at: row.get("updated_at")?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(snippets)
+    }
+
+    /// Add the items and snippets of an archive; `ImportMode::Replace` deletes the
+    /// history, tags and snippets first.
+    /// Items already in the history are merged: they stay pinned if either copy is, and
+    /// keep the earliest capture, the latest use and the highest copy count.
+    /// A snippet with the same name, content and folder as an existing one is skipped, and
+    /// one whose abbreviation is taken is added without it.
+    /// The history limit and retention policy apply afterwards.
+    pub fn import_archive(&self, archive: &Archive, mode: ImportMode) -> Result<ImportSummary> {
+        let max_count: u32 = self.get_max_history_count()?;
+        let policy: RetentionPolicy = self.get_retention_policy()?;
+
+        let mut conn = self.connection.lock().unwrap();
+        let tx = conn.transaction()?;
+        if mode == ImportMode::Replace {
+            tx.execute_batch(
+                "DELETE FROM clipboard_history;
+                 DELETE FROM tags;
+                 DELETE FROM snippets;
+                 DELETE FROM snippet_folders;",
+            )?;
+        }
+
+        let mut summary = ImportSummary::default();
+        for item in &archive.items {
+            let image = match &item.image {
+                Some(bytes) => match process_image(bytes) {
+                    Ok(image) => Some(image),
+                    Err(e) if item.content_type == ContentType::Image => {
+                        return Err(ClipRayError::InvalidInput(e))
+                    }
+                    Err(e) => {
+                        eprintln!("Ignoring unreadable image format: {}", e);
+                        None
+                    }
+                },
+                None if item.content_type == ContentType::Image => {
+                    return Err(ClipRayError::InvalidInput(
+                        "Image item without image data".to_string(),
+                    ))
+                }
+                None => None,
+            };
+            let hash = match &image {
+                Some(image) if item.content_type == ContentType::Image => content_hash(&image.png),
+                _ => content_hash(item.content.as_bytes()),
+            };
+            let timestamp = normalize_timestamp(&item.timestamp)?;
+            let last_used = normalize_timestamp(&item.last_used)?;
+
+            let id = match find_item_by_hash(&tx, item.content_type, &hash)? {
+                Some(id) => {
+                    tx.execute(
+                        "UPDATE clipboard_history
+                         SET pinned = (pinned OR ?1), timestamp = MIN(timestamp, ?2),
+                             last_used = MAX(last_used, ?3), copy_count = MAX(copy_count, ?4)
+                         WHERE id = ?5",
+                        rusqlite::params![item.pinned, timestamp, last_used, item.copy_count, id],
+                    )?;
+                    summary.merged += 1;
+                    id
+                }
+                None => {
+                    let source_app = item.source_app.clone().unwrap_or_default();
+                    tx.execute(
+                        "INSERT INTO clipboard_history (content, content_type, timestamp, pinned, content_hash, last_used, copy_count,
+                                                        selection, source_process, source_class, source_title, search_text)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                        rusqlite::params![
+                            item.content,
+                            item.content_type,
+                            timestamp,
+                            item.pinned,
+                            hash,
+                            last_used,
+                            item.copy_count,
+                            item.selection,
+                            source_app.process,
+                            source_app.class,
+                            source_app.title,
+                            search_text(item.content_type, &item.content)
+                        ],
+                    )?;
+                    let id = tx.last_insert_rowid();
+                    for (format, data) in &item.formats {
+                        if *format != item.content_type && *format != ContentType::Image {
+                            tx.execute(
+                                "INSERT INTO clipboard_formats (item_id, format, data) VALUES (?1, ?2, ?3)",
+                                rusqlite::params![id, format, data],
+                            )?;
+                        }
+                    }
+                    if let Some(image) = &image {
+                        tx.execute(
+                            "INSERT INTO clipboard_images (item_id, data, thumbnail, width, height)
+                             VALUES (?1, ?2, ?3, ?4, ?5)",
+                            rusqlite::params![
+                                id,
+                                image.png,
+                                image.thumbnail,
+                                image.width,
+                                image.height
+                            ],
+                        )?;
+                    }
+                    summary.imported += 1;
+                    id
+                }
+            };
+
+            for name in &item.tags {
+                let name = required_name(name, "Tag name")?;
+                tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [&name])?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO clipboard_item_tags (item_id, tag_id)
+                     SELECT ?1, id FROM tags WHERE name = ?2",
+                    rusqlite::params![id, name],
+                )?;
+            }
+        }
+
+        for name in &archive.folders {
+            import_snippet_folder(&tx, name)?;
+        }
+        for snippet in &archive.snippets {
+            if import_snippet(&tx, snippet)? {
+                summary.snippets += 1;
+            }
+        }
+
+        summary.trimmed =
+            trim_history(&tx, max_count)? + apply_retention_policy(&tx, &policy, Utc::now())?;
+        tx.commit()?;
+        Ok(summary)
+    }
+
     pub fn get_config(&self) -> Result<AppConfig> {
         let max_history_count: u32 = self.get_max_history_count()?;
         let hotkey: String = self.get_hotkey()?;
         let theme: ThemeConfig = self.get_theme()?;
         let autostart: bool = self.get_autostart()?;
+        let exclusion_rules: ExclusionRules = self.get_exclusion_rules()?;
+        let pause_hotkey: Option<String> = self.get_pause_hotkey()?;
+        let persist_pause: bool = self.get_persist_pause()?;
+        let retention: RetentionPolicy = self.get_retention_policy()?;
+        let sensitive: SensitiveRules = self.get_sensitive_rules()?;
+        let capture: CaptureSettings = self.get_capture_settings()?;
         Ok(AppConfig {
             max_history_count,
             hotkey,
             theme,
             autostart,
+            exclusion_rules,
+            pause_hotkey,
+            persist_pause,
+            retention,
+            sensitive,
+            capture,
         })
     }
 
@@ -166,9 +1247,42 @@ impl DatabaseManager {
             "UPDATE app_config SET value = ?1 WHERE key = 'autostart'",
             [config.autostart.to_string()],
         )?;
+        conn.execute(
+            "UPDATE app_config SET value = ?1 WHERE key = 'exclusion_rules'",
+            [to_json("exclusion_rules", &config.exclusion_rules)?],
+        )?;
+        conn.execute(
+            "UPDATE app_config SET value = ?1 WHERE key = 'pause_hotkey'",
+            [config.pause_hotkey.as_deref().unwrap_or_default()],
+        )?;
+        conn.execute(
+            "UPDATE app_config SET value = ?1 WHERE key = 'persist_pause'",
+            [config.persist_pause.to_string()],
+        )?;
+        conn.execute(
+            "UPDATE app_config SET value = ?1 WHERE key = 'retention_policy'",
+            [to_json("retention_policy", &config.retention)?],
+        )?;
+        conn.execute(
+            "UPDATE app_config SET value = ?1 WHERE key = 'sensitive_rules'",
+            [to_json("sensitive_rules", &config.sensitive)?],
+        )?;
+        conn.execute(
+            "UPDATE app_config SET value = ?1 WHERE key = 'capture_settings'",
+            [to_json("capture_settings", &config.capture)?],
+        )?;
         Ok(())
     }
 
+    /// Put every setting back to its default, including a saved pause
+    pub fn reset_config(&self) -> Result<()> {
+        let mut conn = self.connection.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM app_config", [])?;
+        insert_default_config(&tx);
+        Ok(tx.commit()?)
+    }
+
     pub fn get_hotkey(&self) -> Result<String> {
         let conn = self.connection.lock().unwrap();
         let hotkey: String = conn
@@ -229,52 +1343,94 @@ impl DatabaseManager {
         Ok(value_str == "true")
     }
 
-    /// Create a test database in memory for testing purposes
-    #[cfg(any(test, feature = "test-utils"))]
-    pub fn new_test() -> Result<Self> {
-        let conn = Connection::open(":memory:")?;
+    /// get pause hotkey from app_config; stored as an empty string when unset
+    fn get_pause_hotkey(&self) -> Result<Option<String>> {
+        let conn = self.connection.lock().unwrap();
+        let value_str: String = conn
+            .query_row(
+                "SELECT value FROM app_config WHERE key = 'pause_hotkey'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or_default();
+        Ok(Some(value_str).filter(|hotkey| !hotkey.is_empty()))
+    }
 
-        // Create tables
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS clipboard_history (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                content TEXT NOT NULL,
-                content_type TEXT NOT NULL,
-                timestamp TEXT NOT NULL,
-                pinned BOOLEAN DEFAULT FALSE
-            )",
-            [],
-        )?;
+    /// get persist pause setting from app_config
+    fn get_persist_pause(&self) -> Result<bool> {
+        let conn = self.connection.lock().unwrap();
+        let value_str: String = conn
+            .query_row(
+                "SELECT value FROM app_config WHERE key = 'persist_pause'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or_else(|_| "false".to_string());
+        Ok(value_str == "true")
+    }
 
+    /// Setting stored as JSON under `key`; the default when it is missing or unreadable
+    fn read_json_config<T: DeserializeOwned + Default>(&self, key: &str) -> Result<T> {
+        let conn = self.connection.lock().unwrap();
+        let value_str: Option<String> = conn
+            .query_row(
+                "SELECT value FROM app_config WHERE key = ?1",
+                [key],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        let value = value_str
+            .map(|value| {
+                serde_json::from_str(&value).unwrap_or_else(|e| {
+                    eprintln!("Failed to parse {}: {}", key, e);
+                    T::default()
+                })
+            })
+            .unwrap_or_default();
+
+        Ok(value)
+    }
+
+    /// get exclusion rules from app_config
+    pub fn get_exclusion_rules(&self) -> Result<ExclusionRules> {
+        self.read_json_config("exclusion_rules")
+    }
+
+    /// get retention policy from app_config
+    pub fn get_retention_policy(&self) -> Result<RetentionPolicy> {
+        self.read_json_config("retention_policy")
+    }
+
+    /// get sensitive item rules from app_config
+    pub fn get_sensitive_rules(&self) -> Result<SensitiveRules> {
+        self.read_json_config("sensitive_rules")
+    }
+
+    /// get clipboard capture settings from app_config
+    pub fn get_capture_settings(&self) -> Result<CaptureSettings> {
+        self.read_json_config("capture_settings")
+    }
+
+    /// Pause status saved by `save_pause_status`; not paused when none was saved
+    pub fn get_pause_status(&self) -> Result<PauseStatus> {
+        self.read_json_config("pause_status")
+    }
+
+    pub fn save_pause_status(&self, status: &PauseStatus) -> Result<()> {
+        let conn = self.connection.lock().unwrap();
         conn.execute(
-            "CREATE TABLE IF NOT EXISTS app_config (
-                key TEXT PRIMARY KEY,
-                value TEXT NOT NULL
-            )",
-            [],
+            "INSERT OR REPLACE INTO app_config (key, value) VALUES ('pause_status', ?1)",
+            [to_json("pause_status", status)?],
         )?;
+        Ok(())
+    }
 
-        // Insert default settings
-        let _ = conn.execute(
-            "INSERT OR IGNORE INTO app_config (key, value) VALUES ('max_history_count', '50')",
-            [],
-        );
-        let _ = conn.execute(
-            "INSERT OR IGNORE INTO app_config (key, value) VALUES ('hotkey', ?1)",
-            [DEFAULT_HOTKEY],
-        );
-        let _ = conn.execute(
-            "INSERT OR IGNORE INTO app_config (key, value) VALUES ('theme_preset', 'default')",
-            [],
-        );
-        let _ = conn.execute(
-            "INSERT OR IGNORE INTO app_config (key, value) VALUES ('autostart', 'false')",
-            [],
-        );
-
-        Ok(DatabaseManager {
-            connection: Mutex::new(conn),
-        })
+    /// Create a test database in memory for testing purposes
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn new_test() -> std::result::Result<Self, MigrationError> {
+        let conn = Connection::open(":memory:")?;
+        Self::initialize(conn)
     }
 
     /// Setup test data for testing
@@ -297,9 +1453,14 @@ impl DatabaseManager {
 
         conn.execute(
             "INSERT INTO clipboard_history (content, content_type, timestamp, pinned) 
-             VALUES ('test image data', 'image', '2024-01-03T00:00:00Z', FALSE)",
+             VALUES ('', 'image', '2024-01-03T00:00:00Z', FALSE)",
             [],
         )?;
+        conn.execute(
+            "INSERT INTO clipboard_images (item_id, data, thumbnail, width, height)
+             VALUES (?1, X'89504E47', X'89504E47', 1, 1)",
+            [conn.last_insert_rowid()],
+        )?;
 
         Ok(())
     }
@@ -310,3 +1471,492 @@ impl DatabaseManager {
         &self.connection
     }
 }
+
+// Settings start at their defaults; existing values are kept
+/// Setting serialized for storage under `key` in app_config
+fn to_json<T: Serialize>(key: &str, value: &T) -> Result<String> {
+    serde_json::to_string(value)
+        .map_err(|e| ClipRayError::Database(format!("Failed to serialize {}: {}", key, e)))
+}
+
+fn insert_default_config(conn: &Connection) {
+    let _ = conn.execute(
+        "INSERT OR IGNORE INTO app_config (key, value) VALUES ('max_history_count', '50')",
+        [],
+    );
+    let _ = conn.execute(
+        "INSERT OR IGNORE INTO app_config (key, value) VALUES ('hotkey', ?1)",
+        [DEFAULT_HOTKEY],
+    );
+    let _ = conn.execute(
+        "INSERT OR IGNORE INTO app_config (key, value) VALUES ('theme_preset', 'default')",
+        [],
+    );
+    let _ = conn.execute(
+        "INSERT OR IGNORE INTO app_config (key, value) VALUES ('autostart', 'false')",
+        [],
+    );
+    let _ = conn.execute(
+        "INSERT OR IGNORE INTO app_config (key, value) VALUES ('pause_hotkey', '')",
+        [],
+    );
+    let _ = conn.execute(
+        "INSERT OR IGNORE INTO app_config (key, value) VALUES ('persist_pause', 'false')",
+        [],
+    );
+    let _ = conn.execute(
+        "INSERT OR IGNORE INTO app_config (key, value) VALUES ('exclusion_rules', ?1)",
+        [serde_json::to_string(&ExclusionRules::default()).unwrap_or_default()],
+    );
+    let _ = conn.execute(
+        "INSERT OR IGNORE INTO app_config (key, value) VALUES ('retention_policy', ?1)",
+        [serde_json::to_string(&RetentionPolicy::default()).unwrap_or_default()],
+    );
+    let _ = conn.execute(
+        "INSERT OR IGNORE INTO app_config (key, value) VALUES ('sensitive_rules', ?1)",
+        [serde_json::to_string(&SensitiveRules::default()).unwrap_or_default()],
+    );
+    let _ = conn.execute(
+        "INSERT OR IGNORE INTO app_config (key, value) VALUES ('capture_settings', ?1)",
+        [serde_json::to_string(&CaptureSettings::default()).unwrap_or_default()],
+    );
+}
+
+// Columns read by row_to_clipboard_item; `h` is clipboard_history and `i` is clipboard_images
+const ITEM_COLUMNS: &str = "h.id, h.content, h.content_type, h.timestamp, h.pinned,
+    h.last_used, h.copy_count, h.expires_at, h.selection,
+    h.source_process, h.source_class, h.source_title,
+    (SELECT GROUP_CONCAT(tag_id) FROM clipboard_item_tags WHERE item_id = h.id) AS tag_ids,
+    i.width AS image_width, i.height AS image_height, i.thumbnail AS image_thumbnail";
+const ITEM_SOURCE: &str = "clipboard_history h LEFT JOIN clipboard_images i ON i.item_id = h.id";
+
+fn row_to_source_app(row: &Row) -> rusqlite::Result<Option<SourceApp>> {
+    let source_app = SourceApp {
+        process: row.get("source_process")?,
+        class: row.get("source_class")?,
+        title: row.get("source_title")?,
+    };
+    Ok((source_app != SourceApp::default()).then_some(source_app))
+}
+
+fn row_to_clipboard_item(row: &Row) -> rusqlite::Result<ClipboardItem> {
+    let content_type: ContentType = row.get("content_type")?;
+    let content = match content_type {
+        ContentType::Text => ClipboardContent::Text {
+            content: row.get("content")?,
+        },
+        ContentType::Html => ClipboardContent::Html {
+            content: row.get("content")?,
+        },
+        ContentType::Rtf => ClipboardContent::Rtf {
+            content: row.get("content")?,
+        },
+        ContentType::Files => ClipboardContent::Files {
+            files: row
+                .get::<_, String>("content")?
+                .lines()
+                .map(String::from)
+                .collect(),
+        },
+        ContentType::Image => {
+            let thumbnail: Vec<u8> = row.get("image_thumbnail")?;
+            ClipboardContent::Image(ImageInfo {
+                width: row.get("image_width")?,
+                height: row.get("image_height")?,
+                mime: IMAGE_MIME_TYPE.to_string(),
+                thumbnail: general_purpose::STANDARD.encode(thumbnail),
+            })
+        }
+    };
+
+    Ok(ClipboardItem {
+        id: row.get("id")?,
+        content,
+        timestamp: row.get("timestamp")?,
+        pinned: row.get("pinned")?,
+        last_used: row.get("last_used")?,
+        copy_count: row.get("copy_count")?,
+        expires_at: row.get("expires_at")?,
+        tag_ids: parse_tag_ids(row.get("tag_ids")?),
+        selection: row.get("selection")?,
+        source_app: row_to_source_app(row)?,
+    })
+}
+
+// All stored representations of an item
+fn item_snapshot(conn: &Connection, id: i64) -> rusqlite::Result<Option<ClipboardSnapshot>> {
+    let item: Option<(String, ContentType)> = conn
+        .query_row(
+            "SELECT content, content_type FROM clipboard_history WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((content, content_type)) = item else {
+        return Ok(None);
+    };
+
+    let mut snapshot = ClipboardSnapshot::default();
+    set_snapshot_format(&mut snapshot, content_type, content);
+
+    let mut stmt = conn.prepare("SELECT format, data FROM clipboard_formats WHERE item_id = ?1")?;
+    let formats = stmt.query_map([id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    for format in formats {
+        let (format, data) = format?;
+        set_snapshot_format(&mut snapshot, format, data);
+    }
+
+    snapshot.image = conn
+        .query_row(
+            "SELECT data FROM clipboard_images WHERE item_id = ?1",
+            [id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    Ok(Some(snapshot))
+}
+
+fn find_item_by_hash(
+    conn: &Connection,
+    content_type: ContentType,
+    hash: &str,
+) -> rusqlite::Result<Option<i64>> {
+    conn.query_row(
+        "SELECT id FROM clipboard_history WHERE content_type = ?1 AND content_hash = ?2 LIMIT 1",
+        rusqlite::params![content_type, hash],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+// Images are not stored as text; they are read from clipboard_images
+fn set_snapshot_format(snapshot: &mut ClipboardSnapshot, format: ContentType, data: String) {
+    match format {
+        ContentType::Text => snapshot.text = Some(data),
+        ContentType::Html => snapshot.html = Some(data),
+        ContentType::Rtf => snapshot.rtf = Some(data),
+        ContentType::Files => snapshot.files = Some(data.lines().map(String::from).collect()),
+        ContentType::Image => {}
+    }
+}
+
+impl ToSql for ContentType {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for ContentType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let value = value.as_str()?;
+        ContentType::from_str(value)
+            .ok_or_else(|| FromSqlError::Other(format!("Unknown content type '{}'", value).into()))
+    }
+}
+
+impl ToSql for Selection {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for Selection {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let value = value.as_str()?;
+        Selection::from_str(value)
+            .ok_or_else(|| FromSqlError::Other(format!("Unknown selection '{}'", value).into()))
+    }
+}
+
+// Comma-separated ids from GROUP_CONCAT, NULL when the item has no tags
+fn parse_tag_ids(value: Option<String>) -> Vec<i64> {
+    let mut ids: Vec<i64> = value
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|id| id.parse().ok())
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+fn required_name(name: &str, what: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ClipRayError::InvalidInput(format!(
+            "{} cannot be empty",
+            what
+        )));
+    }
+    Ok(name.to_string())
+}
+
+// Turn a UNIQUE constraint failure into a readable error
+fn already_exists(error: rusqlite::Error, message: String) -> ClipRayError {
+    match error.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::ConstraintViolation) => ClipRayError::AlreadyExists(message),
+        _ => error.into(),
+    }
+}
+
+// Trimmed and validated fields of a snippet, with an empty abbreviation meaning none
+fn normalize_snippet_draft(conn: &Connection, draft: &SnippetDraft) -> Result<SnippetDraft> {
+    let name = required_name(&draft.name, "Snippet name")?;
+    if draft.content.is_empty() {
+        return Err(ClipRayError::InvalidInput(
+            "Snippet content cannot be empty".to_string(),
+        ));
+    }
+    Template::parse(&draft.content)
+        .map_err(|e| ClipRayError::InvalidInput(format!("Invalid placeholder: {}", e)))?;
+    let abbreviation = draft
+        .abbreviation
+        .as_deref()
+        .map(str::trim)
+        .filter(|abbreviation| !abbreviation.is_empty());
+    if abbreviation.is_some_and(|abbreviation| abbreviation.contains(char::is_whitespace)) {
+        return Err(ClipRayError::InvalidInput(
+            "Abbreviations cannot contain spaces".to_string(),
+        ));
+    }
+    if let Some(folder_id) = draft.folder_id {
+        if !snippet_folder_exists(conn, folder_id)? {
+            return Err(ClipRayError::not_found("Folder", folder_id));
+        }
+    }
+
+    Ok(SnippetDraft {
+        name,
+        abbreviation: abbreviation.map(String::from),
+        content: draft.content.clone(),
+        folder_id: draft.folder_id,
+    })
+}
+
+/// Id of the folder named `name`, created if there is none
+fn import_snippet_folder(conn: &Connection, name: &str) -> Result<i64> {
+    let name = required_name(name, "Folder name")?;
+    conn.execute(
+        "INSERT OR IGNORE INTO snippet_folders (name) VALUES (?1)",
+        [&name],
+    )?;
+    Ok(conn.query_row(
+        "SELECT id FROM snippet_folders WHERE name = ?1",
+        [&name],
+        |row| row.get(0),
+    )?)
+}
+
+/// Returns false if the same snippet is already there
+fn import_snippet(conn: &Connection, snippet: &ArchivedSnippet) -> Result<bool> {
+    let folder_id = match &snippet.folder {
+        Some(folder) => Some(import_snippet_folder(conn, folder)?),
+        None => None,
+    };
+    let mut draft = normalize_snippet_draft(
+        conn,
+        &SnippetDraft {
+            name: snippet.name.clone(),
+            abbreviation: snippet.abbreviation.clone(),
+            content: snippet.content.clone(),
+            folder_id,
+        },
+    )?;
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM snippets WHERE name = ?1 AND content = ?2 AND folder_id IS ?3)",
+        rusqlite::params![draft.name, draft.content, draft.folder_id],
+        |row| row.get(0),
+    )?;
+    if exists {
+        return Ok(false);
+    }
+    if let Some(abbreviation) = &draft.abbreviation {
+        let taken: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM snippets WHERE abbreviation = ?1)",
+            [abbreviation],
+            |row| row.get(0),
+        )?;
+        if taken {
+            eprintln!(
+                "Importing snippet '{}' without its abbreviation '{}', which is taken",
+                draft.name, abbreviation
+            );
+            draft.abbreviation = None;
+        }
+    }
+
+    conn.execute(
+        "INSERT INTO snippets (name, abbreviation, content, folder_id, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            draft.name,
+            draft.abbreviation,
+            draft.content,
+            draft.folder_id,
+            normalize_timestamp(&snippet.created_at)?,
+            normalize_timestamp(&snippet.updated_at)?
+        ],
+    )?;
+    Ok(true)
+}
+
+fn snippet_folder_exists(conn: &Connection, id: i64) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM snippet_folders WHERE id = ?1)",
+        [id],
+        |row| row.get(0),
+    )
+}
+
+fn duplicate_abbreviation(error: rusqlite::Error, draft: &SnippetDraft) -> ClipRayError {
+    already_exists(
+        error,
+        format!(
+            "Another snippet already uses the abbreviation '{}'",
+            draft.abbreviation.as_deref().unwrap_or_default()
+        ),
+    )
+}
+
+const SNIPPET_COLUMNS: &str =
+    "s.id, s.name, s.abbreviation, s.content, s.folder_id, s.created_at, s.updated_at";
+
+fn row_to_snippet(row: &Row) -> rusqlite::Result<Snippet> {
+    Ok(Snippet {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        abbreviation: row.get("abbreviation")?,
+        content: row.get("content")?,
+        folder_id: row.get("folder_id")?,
+        created_at: row.get("created_at")?,
+        updated_at: row.get("updated_at")?,
+    })
+}
+
+fn get_snippet(conn: &Connection, id: i64) -> rusqlite::Result<Option<Snippet>> {
+    conn.query_row(
+        &format!("SELECT {} FROM snippets s WHERE s.id = ?1", SNIPPET_COLUMNS),
+        [id],
+        row_to_snippet,
+    )
+    .optional()
+}
+
+// Name for a snippet made from a history item: its first non-empty line, shortened
+fn default_snippet_name(content: &str) -> String {
+    let line = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("Snippet");
+    if line.chars().count() <= SNIPPET_NAME_MAX_CHARS {
+        return line.to_string();
+    }
+    let mut name: String = line.chars().take(SNIPPET_NAME_MAX_CHARS).collect();
+    name.push('…');
+    name
+}
+
+// Count limits never remove pinned or tagged items
+const TRIMMABLE_ITEMS: &str =
+    "pinned = FALSE AND id NOT IN (SELECT item_id FROM clipboard_item_tags)";
+
+// Drop the least recently used trimmable items beyond the history limit
+fn trim_history(conn: &Connection, max_count: u32) -> rusqlite::Result<usize> {
+    conn.execute(
+        &format!(
+            "DELETE FROM clipboard_history WHERE id IN (
+            SELECT id FROM clipboard_history
+            WHERE {}
+            ORDER BY last_used DESC
+            LIMIT -1 OFFSET ?1
+        )",
+            TRIMMABLE_ITEMS
+        ),
+        [max_count],
+    )
+}
+
+// Delete unpinned items outside the retention policy; returns how many were deleted.
+// Tagged items are kept by the count and size limits, but not by the age limit.
+// Tagged items do not count towards the per-type limits
+fn apply_retention_policy(
+    conn: &Connection,
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    let mut deleted = 0;
+
+    if let Some(days) = policy.max_age_days {
+        let cutoff = now - chrono::Duration::days(days.into());
+        deleted += conn.execute(
+            "DELETE FROM clipboard_history WHERE pinned = FALSE AND last_used < ?1",
+            [cutoff.to_rfc3339()],
+        )?;
+    }
+
+    for (content_type, max_count) in &policy.max_items_per_type {
+        deleted += conn.execute(
+            &format!(
+                "DELETE FROM clipboard_history WHERE id IN (
+                SELECT id FROM clipboard_history
+                WHERE {} AND content_type = ?1
+                ORDER BY last_used DESC, id DESC
+                LIMIT -1 OFFSET ?2
+            )",
+                TRIMMABLE_ITEMS
+            ),
+            rusqlite::params![content_type, max_count],
+        )?;
+    }
+
+    if let Some(max_bytes) = policy.max_total_bytes {
+        // Keep the most recently used items that fit next to the pinned and tagged ones
+        deleted += conn.execute(
+            "WITH sizes AS (
+                SELECT h.id, h.last_used,
+                    h.pinned OR h.id IN (SELECT item_id FROM clipboard_item_tags) AS kept,
+                    length(CAST(h.content AS BLOB))
+                    + COALESCE((SELECT SUM(length(CAST(f.data AS BLOB)))
+                                FROM clipboard_formats f WHERE f.item_id = h.id), 0)
+                    + COALESCE((SELECT length(i.data) + length(i.thumbnail)
+                                FROM clipboard_images i WHERE i.item_id = h.id), 0) AS size
+                FROM clipboard_history h
+            ),
+            trimmable AS (
+                SELECT id, SUM(size) OVER (ORDER BY last_used DESC, id DESC) AS running_size
+                FROM sizes WHERE kept = FALSE
+            )
+            DELETE FROM clipboard_history WHERE id IN (
+                SELECT id FROM trimmable
+                WHERE running_size
+                    + (SELECT COALESCE(SUM(size), 0) FROM sizes WHERE kept = TRUE) > ?1
+            )",
+            [i64::try_from(max_bytes).unwrap_or(i64::MAX)],
+        )?;
+    }
+
+    Ok(deleted)
+}
+
+// Stored timestamps are UTC RFC 3339, so bounds must use the same form to compare as text
+fn normalize_timestamp(value: &str) -> Result<String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc).to_rfc3339())
+        .map_err(|e| ClipRayError::InvalidInput(format!("Invalid timestamp '{}': {}", value, e)))
+}
+
+fn encode_cursor(timestamp: &str, id: i64) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(format!("{}|{}", timestamp, id))
+}
+
+fn decode_cursor(cursor: &str) -> Result<(String, i64)> {
+    let invalid = || ClipRayError::InvalidInput(format!("Invalid cursor '{}'", cursor));
+    let bytes = general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .map_err(|_| invalid())?;
+    let decoded = String::from_utf8(bytes).map_err(|_| invalid())?;
+    let (timestamp, id) = decoded.rsplit_once('|').ok_or_else(invalid)?;
+    let id = id.parse::<i64>().map_err(|_| invalid())?;
+    Ok((timestamp.to_string(), id))
+}
@@ -0,0 +1,227 @@
+use rusqlite::{params, Connection, ErrorCode};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::libs::backup::delete_backups;
+use crate::libs::constants::DATABASE_KEY_BYTES;
+use crate::libs::database::DatabaseManager;
+use crate::libs::migrations::{get_schema_version, MigrationError};
+use crate::libs::types::EncryptionMode;
+
+/// Key an encrypted database is opened with
+#[derive(Clone)]
+pub enum DatabaseKey {
+    /// SQLCipher derives the encryption key from the passphrase
+    Passphrase(String),
+    /// Random key kept in a key file next to the database
+    Raw([u8; DATABASE_KEY_BYTES]),
+}
+
+// Keys must not end up in logs
+impl fmt::Debug for DatabaseKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatabaseKey::Passphrase(_) => write!(f, "DatabaseKey::Passphrase(..)"),
+            DatabaseKey::Raw(_) => write!(f, "DatabaseKey::Raw(..)"),
+        }
+    }
+}
+
+impl DatabaseKey {
+    /// New random key for a key file
+    pub fn generate() -> io::Result<Self> {
+        let mut key = [0u8; DATABASE_KEY_BYTES];
+        getrandom::getrandom(&mut key)?;
+        Ok(DatabaseKey::Raw(key))
+    }
+
+    pub fn mode(&self) -> EncryptionMode {
+        match self {
+            DatabaseKey::Passphrase(_) => EncryptionMode::Passphrase,
+            DatabaseKey::Raw(_) => EncryptionMode::KeyFile,
+        }
+    }
+
+    /// Value for `PRAGMA key` and `ATTACH ... KEY`. SQLCipher uses `x'...'` as the key
+    /// itself; anything else is a passphrase the key is derived from.
+    fn sql_value(&self) -> String {
+        match self {
+            DatabaseKey::Passphrase(passphrase) => passphrase.clone(),
+            DatabaseKey::Raw(key) => format!("x'{}'", to_hex(key)),
+        }
+    }
+}
+
+/// Where the new database encryption should come from
+pub enum KeySource {
+    None,
+    Passphrase(String),
+    /// Generate a new key file
+    KeyFile,
+}
+
+pub fn key_file_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("key")
+}
+
+/// Key file written before a key change, renamed over the key file once the
+/// database has been re-encrypted with it
+fn pending_key_file_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("key.new")
+}
+
+/// Apply `key` to a freshly opened connection and check that it decrypts the database
+pub fn unlock_connection(
+    conn: &Connection,
+    key: Option<&DatabaseKey>,
+) -> Result<(), MigrationError> {
+    if let Some(key) = key {
+        conn.pragma_update(None, "key", key.sql_value())?;
+    }
+    match conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(())) {
+        Err(rusqlite::Error::SqliteFailure(e, _)) if e.code == ErrorCode::NotADatabase => {
+            Err(MigrationError::Locked)
+        }
+        result => Ok(result?),
+    }
+}
+
+/// Open the history database. Key files are tried first, then `passphrase`.
+/// Fails with `MigrationError::Locked` when the database needs a passphrase.
+pub fn open_database(
+    db_path: &Path,
+    passphrase: Option<&str>,
+) -> Result<DatabaseManager, MigrationError> {
+    open_database_file(db_path, db_path, passphrase)
+}
+
+/// Open `file` with the keys of the database at `db_path`, like `open_database`.
+/// Used for copies of the database, such as backups.
+pub fn open_database_file(
+    file: &Path,
+    db_path: &Path,
+    passphrase: Option<&str>,
+) -> Result<DatabaseManager, MigrationError> {
+    let key_path = key_file_path(db_path);
+    let pending_path = pending_key_file_path(db_path);
+
+    // A key file that does not open the database is left over from an
+    // interrupted key change and is skipped
+    for path in [&pending_path, &key_path] {
+        if !path.exists() {
+            continue;
+        }
+        match DatabaseManager::open(file, Some(&read_key_file(path)?)) {
+            Ok(db) => {
+                if path == &pending_path && file == db_path {
+                    fs::rename(&pending_path, &key_path)?;
+                }
+                return Ok(db);
+            }
+            Err(MigrationError::Locked) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    let key = passphrase.map(|passphrase| DatabaseKey::Passphrase(passphrase.to_string()));
+    DatabaseManager::open(file, key.as_ref())
+}
+
+/// Encrypt, re-key or decrypt an open database, create or remove its key file and
+/// delete the backups made with the previous key
+pub fn change_encryption(db: &DatabaseManager, source: KeySource) -> Result<(), MigrationError> {
+    let db_path = db.path().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "In-memory databases cannot be encrypted",
+        )
+    })?;
+    let key_path = key_file_path(&db_path);
+    let pending_path = pending_key_file_path(&db_path);
+
+    match source {
+        KeySource::KeyFile => {
+            let key = DatabaseKey::generate()?;
+            write_key_file(&pending_path, &key)?;
+            db.change_key(Some(&key))?;
+            fs::rename(&pending_path, &key_path)?;
+        }
+        KeySource::Passphrase(passphrase) => {
+            db.change_key(Some(&DatabaseKey::Passphrase(passphrase)))?;
+            remove_if_exists(&key_path)?;
+        }
+        KeySource::None => {
+            db.change_key(None)?;
+            remove_if_exists(&key_path)?;
+        }
+    }
+    // Backups with the previous key, or none, must not outlive the change
+    delete_backups(&db_path)?;
+    Ok(())
+}
+
+/// Copy the whole database into a new file encrypted with `key`, or unencrypted for `None`.
+/// The schema version is not part of the export and is copied separately.
+pub fn export_database(
+    conn: &Connection,
+    target: &Path,
+    key: Option<&DatabaseKey>,
+) -> rusqlite::Result<()> {
+    let version = get_schema_version(conn)?;
+    let key = key.map(DatabaseKey::sql_value).unwrap_or_default();
+
+    conn.execute(
+        "ATTACH DATABASE ?1 AS exported KEY ?2",
+        params![target.to_string_lossy(), key],
+    )?;
+    let result = conn
+        .query_row("SELECT sqlcipher_export('exported')", [], |_| Ok(()))
+        .and_then(|_| conn.pragma_update(Some("exported"), "user_version", version));
+    conn.execute("DETACH DATABASE exported", [])?;
+    result
+}
+
+pub fn read_key_file(path: &Path) -> io::Result<DatabaseKey> {
+    let text = fs::read_to_string(path)?;
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid database key file");
+
+    let text = text.trim();
+    if text.len() != DATABASE_KEY_BYTES * 2 || !text.is_ascii() {
+        return Err(invalid());
+    }
+    let mut key = [0u8; DATABASE_KEY_BYTES];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+    }
+    Ok(DatabaseKey::Raw(key))
+}
+
+fn write_key_file(path: &Path, key: &DatabaseKey) -> io::Result<()> {
+    let DatabaseKey::Raw(key) = key else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Only raw keys are stored in key files",
+        ));
+    };
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // Readable by the current user only
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    io::Write::write_all(&mut options.open(path)?, to_hex(key).as_bytes())
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,221 @@
+//! Errors returned by the Tauri commands.
+//!
+//! Every error has a stable code the window can act on, a message to show the user
+//! and, when there is more to tell, a detail with the underlying cause. It reaches
+//! the window as `{ "code": "hotkey-in-use", "message": "...", "detail": "..." }`.
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::fmt;
+use std::io;
+
+use crate::libs::archive::ArchiveError;
+use crate::libs::migrations::MigrationError;
+use crate::libs::setup::StartupError;
+use crate::libs::template::TemplateError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClipRayError {
+    /// No item, snippet, tag or folder with the requested id
+    NotFound(String),
+    /// The request was rejected; the message says why
+    InvalidInput(String),
+    /// A tag, folder or abbreviation with this name is already there
+    AlreadyExists(String),
+    /// The database is encrypted and has not been unlocked
+    DatabaseLocked,
+    /// Another connection holds a lock on the database
+    DatabaseBusy(String),
+    WrongPassphrase,
+    /// The database was written by a newer version of the application
+    DatabaseTooNew {
+        found: u32,
+        supported: u32,
+    },
+    DatabaseDamaged(String),
+    Database(String),
+    InvalidHotkey {
+        hotkey: String,
+        detail: String,
+    },
+    /// Registering the hotkey failed, usually because another application has it
+    HotkeyInUse {
+        hotkey: String,
+        detail: String,
+    },
+    Clipboard(String),
+    Window(String),
+    Autostart(String),
+    /// Reading or writing a file failed
+    File(String),
+    /// The file is not an export this version can read
+    InvalidArchive(String),
+    /// The app data directory could not be found or created
+    DataDir(String),
+}
+
+impl ClipRayError {
+    /// `what` with `id` does not exist, e.g. "Item 3 not found"
+    pub fn not_found(what: &str, id: i64) -> Self {
+        ClipRayError::NotFound(format!("{} {} not found", what, id))
+    }
+
+    /// The database has not been opened yet, usually because it waits for its passphrase
+    pub fn locked() -> Self {
+        ClipRayError::DatabaseLocked
+    }
+
+    /// Stable identifier for the window to tell errors apart
+    pub fn code(&self) -> &'static str {
+        match self {
+            ClipRayError::NotFound(_) => "not-found",
+            ClipRayError::InvalidInput(_) => "invalid-input",
+            ClipRayError::AlreadyExists(_) => "already-exists",
+            ClipRayError::DatabaseLocked => "database-locked",
+            ClipRayError::DatabaseBusy(_) => "database-busy",
+            ClipRayError::WrongPassphrase => "wrong-passphrase",
+            ClipRayError::DatabaseTooNew { .. } => "database-too-new",
+            ClipRayError::DatabaseDamaged(_) => "database-damaged",
+            ClipRayError::Database(_) => "database",
+            ClipRayError::InvalidHotkey { .. } => "invalid-hotkey",
+            ClipRayError::HotkeyInUse { .. } => "hotkey-in-use",
+            ClipRayError::Clipboard(_) => "clipboard",
+            ClipRayError::Window(_) => "window",
+            ClipRayError::Autostart(_) => "autostart",
+            ClipRayError::File(_) => "file",
+            ClipRayError::InvalidArchive(_) => "invalid-archive",
+            ClipRayError::DataDir(_) => "data-dir",
+        }
+    }
+
+    /// What went wrong, for the user
+    pub fn message(&self) -> String {
+        match self {
+            ClipRayError::NotFound(message)
+            | ClipRayError::InvalidInput(message)
+            | ClipRayError::AlreadyExists(message)
+            | ClipRayError::InvalidArchive(message)
+            | ClipRayError::DataDir(message) => message.clone(),
+            ClipRayError::DatabaseLocked => {
+                "The database is locked. Enter the passphrase to open it.".to_string()
+            }
+            ClipRayError::DatabaseBusy(_) => {
+                "The database is in use by another program. Try again.".to_string()
+            }
+            ClipRayError::WrongPassphrase => "Wrong passphrase".to_string(),
+            ClipRayError::DatabaseTooNew { found, supported } => format!(
+                "Database schema version {} is newer than the supported version {}. Please update Clipray.",
+                found, supported
+            ),
+            ClipRayError::DatabaseDamaged(_) => "The database is damaged".to_string(),
+            ClipRayError::Database(_) => "A database error occurred".to_string(),
+            ClipRayError::InvalidHotkey { hotkey, .. } => {
+                format!("'{}' is not a valid hotkey", hotkey)
+            }
+            ClipRayError::HotkeyInUse { hotkey, .. } => format!(
+                "The hotkey '{}' could not be registered. It might already be in use by another application.",
+                hotkey
+            ),
+            ClipRayError::Clipboard(_) => "The clipboard could not be accessed".to_string(),
+            ClipRayError::Window(_) => "The window could not be shown or hidden".to_string(),
+            ClipRayError::Autostart(_) => "Launch at login could not be changed".to_string(),
+            ClipRayError::File(_) => "A file could not be read or written".to_string(),
+        }
+    }
+
+    /// The underlying cause, when the message does not already say it all
+    pub fn detail(&self) -> Option<&str> {
+        match self {
+            ClipRayError::DatabaseDamaged(detail)
+            | ClipRayError::DatabaseBusy(detail)
+            | ClipRayError::Database(detail)
+            | ClipRayError::InvalidHotkey { detail, .. }
+            | ClipRayError::HotkeyInUse { detail, .. }
+            | ClipRayError::Clipboard(detail)
+            | ClipRayError::Window(detail)
+            | ClipRayError::Autostart(detail)
+            | ClipRayError::File(detail) => Some(detail),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ClipRayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.detail() {
+            Some(detail) => write!(f, "{}: {}", self.message(), detail),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl std::error::Error for ClipRayError {}
+
+impl Serialize for ClipRayError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("ClipRayError", 3)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.message())?;
+        error.serialize_field("detail", &self.detail())?;
+        error.end()
+    }
+}
+
+impl From<rusqlite::Error> for ClipRayError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase) => {
+                ClipRayError::DatabaseDamaged(e.to_string())
+            }
+            Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked) => {
+                ClipRayError::DatabaseBusy(e.to_string())
+            }
+            _ => ClipRayError::Database(e.to_string()),
+        }
+    }
+}
+
+impl From<MigrationError> for ClipRayError {
+    fn from(e: MigrationError) -> Self {
+        match e {
+            MigrationError::Sqlite(e) => e.into(),
+            MigrationError::SchemaTooNew { found, supported } => {
+                ClipRayError::DatabaseTooNew { found, supported }
+            }
+            MigrationError::Locked => ClipRayError::locked(),
+            MigrationError::Io(e) => e.into(),
+            MigrationError::Corrupt(problems) => ClipRayError::DatabaseDamaged(problems),
+        }
+    }
+}
+
+impl From<ArchiveError> for ClipRayError {
+    fn from(e: ArchiveError) -> Self {
+        match e {
+            ArchiveError::Io(e) => e.into(),
+            ArchiveError::Database(e) => e,
+            e => ClipRayError::InvalidArchive(e.to_string()),
+        }
+    }
+}
+
+impl From<TemplateError> for ClipRayError {
+    fn from(e: TemplateError) -> Self {
+        ClipRayError::InvalidInput(e.to_string())
+    }
+}
+
+impl From<StartupError> for ClipRayError {
+    fn from(e: StartupError) -> Self {
+        match e {
+            StartupError::DataDir(message) => ClipRayError::DataDir(message),
+            StartupError::Database(e) => e.into(),
+            StartupError::Config(e) => e,
+        }
+    }
+}
+
+impl From<io::Error> for ClipRayError {
+    fn from(e: io::Error) -> Self {
+        ClipRayError::File(e.to_string())
+    }
+}
@@ -0,0 +1,251 @@
+use regex::Regex;
+
+use crate::libs::types::{ClipboardSnapshot, ExclusionRules, SecretKind, SourceApp};
+
+/// Why a clipboard change was not recorded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExclusionReason {
+    /// The copying app marked the content as concealed or transient
+    Concealed,
+    TooLong,
+    /// Index into `ExclusionRules::patterns`
+    Pattern(usize),
+    Secret(SecretKind),
+    /// Index into `ExclusionRules::apps`
+    App(usize),
+}
+
+/// Compiled form of `ExclusionRules`, consulted before anything is stored
+pub struct ExclusionFilter {
+    patterns: Vec<(usize, Regex)>,
+    detectors: Vec<(SecretKind, Regex)>,
+    max_length: Option<usize>,
+    respect_concealed_hint: bool,
+    apps: Vec<(usize, AppMatcher)>,
+}
+
+impl ExclusionFilter {
+    /// Fails with a readable message when a user pattern is not a valid regex
+    /// or an app entry names nothing
+    pub fn new(rules: &ExclusionRules) -> Result<Self, String> {
+        let (filter, errors) = Self::compile(rules);
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(filter),
+        }
+    }
+
+    /// Compile the rules, leaving out invalid patterns and app entries.
+    /// Returns the messages `new` would fail with for the entries left out.
+    pub fn compile(rules: &ExclusionRules) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        let patterns = compile_entries(&rules.patterns, &mut errors, |pattern| {
+            Regex::new(pattern)
+                .map_err(|e| format!("Invalid exclusion pattern '{}': {}", pattern, e))
+        });
+        let apps = compile_entries(&rules.apps, &mut errors, AppMatcher::parse);
+
+        let detectors = rules
+            .detectors
+            .iter()
+            .map(|&kind| (kind, detector_regex(kind)))
+            .collect();
+
+        let filter = Self {
+            patterns,
+            detectors,
+            max_length: rules.max_length,
+            respect_concealed_hint: rules.respect_concealed_hint,
+            apps,
+        };
+        (filter, errors)
+    }
+
+    /// Check the application a copy was made in. Copies from unknown applications pass.
+    pub fn check_app(&self, source_app: Option<&SourceApp>) -> Option<ExclusionReason> {
+        let source_app = source_app?;
+        self.apps
+            .iter()
+            .find(|(_, matcher)| matcher.matches(source_app))
+            .map(|(index, _)| ExclusionReason::App(*index))
+    }
+
+    /// Check a clipboard snapshot against the rules.
+    /// `concealed` is the clipboard's concealed/transient hint.
+    pub fn check(&self, snapshot: &ClipboardSnapshot, concealed: bool) -> Option<ExclusionReason> {
+        if concealed && self.respect_concealed_hint {
+            return Some(ExclusionReason::Concealed);
+        }
+
+        if let (Some(max_length), Some(text)) = (self.max_length, &snapshot.text) {
+            if text.chars().count() > max_length {
+                return Some(ExclusionReason::TooLong);
+            }
+        }
+
+        // Rich formats usually repeat the plain text, but check them too
+        // in case a secret is only present in the markup
+        let texts = [&snapshot.text, &snapshot.html, &snapshot.rtf];
+        for text in texts.into_iter().flatten() {
+            if let Some((index, _)) = self.patterns.iter().find(|(_, re)| re.is_match(text)) {
+                return Some(ExclusionReason::Pattern(*index));
+            }
+            for (kind, re) in &self.detectors {
+                if detect(*kind, re, text) {
+                    return Some(ExclusionReason::Secret(*kind));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+// Compiled entries with their index. Blank lines would match everything, so they
+// are ignored; entries that fail to compile are left out and their error recorded.
+fn compile_entries<T>(
+    entries: &[String],
+    errors: &mut Vec<String>,
+    compile: impl Fn(&str) -> Result<T, String>,
+) -> Vec<(usize, T)> {
+    let mut compiled = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        if entry.trim().is_empty() {
+            continue;
+        }
+        match compile(entry) {
+            Ok(value) => compiled.push((index, value)),
+            Err(e) => errors.push(e),
+        }
+    }
+    compiled
+}
+
+fn detector_regex(kind: SecretKind) -> Regex {
+    let pattern = match kind {
+        // 13 to 19 digits, optionally grouped with spaces or dashes
+        SecretKind::CreditCard => r"\b(?:\d[ -]?){12,18}\d\b",
+        // Header and payload are base64url JSON objects, which always start with `eyJ`
+        SecretKind::Jwt => r"\beyJ[A-Za-z0-9_-]+\.eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+",
+        // Access key ids, and secret keys when they appear as an assignment
+        SecretKind::AwsKey => {
+            r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b|(?i:aws_secret_access_key)\s*[=:]\s*[A-Za-z0-9/+=]{40}"
+        }
+        SecretKind::PrivateKey => r"-----BEGIN (?:[A-Z0-9]+ )*PRIVATE KEY(?: BLOCK)?-----",
+        // Only when the code is all that was copied, e.g. `123456` or `123 456`
+        SecretKind::OneTimeCode => r"^\s*(?:\d{4,8}|\d{3}[ -]\d{3})\s*$",
+    };
+    Regex::new(pattern).expect("built-in detector pattern is valid")
+}
+
+fn detect(kind: SecretKind, re: &Regex, text: &str) -> bool {
+    match kind {
+        // Long digit runs are common (order numbers, timestamps), so require a valid checksum
+        SecretKind::CreditCard => re.find_iter(text).any(|m| luhn_valid(m.as_str())),
+        _ => re.is_match(text),
+    }
+}
+
+/// Luhn checksum over the digits of `number`, ignoring separators
+fn luhn_valid(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Which name of an application an `AppMatcher` compares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AppField {
+    Process,
+    Class,
+    /// Either of them
+    Name,
+}
+
+/// Entry of `ExclusionRules::apps`: `process:NAME`, `class:NAME`, or a plain `NAME`
+/// matching either. Case is ignored and `*` stands for any run of characters.
+/// Process names are compared without their directory and `.exe`, so `KeePass`,
+/// `keepass.exe` and `C:\Program Files\KeePass\KeePass.exe` are the same.
+#[derive(Debug, Clone)]
+pub struct AppMatcher {
+    field: AppField,
+    pattern: Regex,
+    /// The same pattern for process names, without directory and extension
+    process_pattern: Regex,
+}
+
+impl AppMatcher {
+    pub fn parse(entry: &str) -> Result<Self, String> {
+        let entry = entry.trim();
+        let (field, name) = match entry.split_once(':') {
+            Some((prefix, name)) if prefix.eq_ignore_ascii_case("process") => {
+                (AppField::Process, name.trim())
+            }
+            Some((prefix, name)) if prefix.eq_ignore_ascii_case("class") => {
+                (AppField::Class, name.trim())
+            }
+            _ => (AppField::Name, entry),
+        };
+        if name.trim_matches('*').is_empty() {
+            return Err(format!("Invalid app '{}': a name is needed", entry));
+        }
+        Ok(Self {
+            field,
+            pattern: glob_regex(name),
+            process_pattern: glob_regex(process_stem(name)),
+        })
+    }
+
+    pub fn matches(&self, source_app: &SourceApp) -> bool {
+        let process = || {
+            source_app
+                .process
+                .as_deref()
+                .is_some_and(|process| self.process_pattern.is_match(process_stem(process)))
+        };
+        let class = || {
+            source_app
+                .class
+                .as_deref()
+                .is_some_and(|class| self.pattern.is_match(class))
+        };
+        match self.field {
+            AppField::Process => process(),
+            AppField::Class => class(),
+            AppField::Name => process() || class(),
+        }
+    }
+}
+
+// Whole-name, case-insensitive match where `*` is the only special character
+fn glob_regex(glob: &str) -> Regex {
+    let parts: Vec<String> = glob.split('*').map(regex::escape).collect();
+    Regex::new(&format!("(?i)^{}$", parts.join(".*"))).expect("escaped glob is a valid regex")
+}
+
+// `C:\Apps\KeePass.exe` and `/usr/bin/keepassxc` to `KeePass` and `keepassxc`
+fn process_stem(process: &str) -> &str {
+    let file = process.rsplit(['/', '\\']).next().unwrap_or(process);
+    match file.len().checked_sub(4) {
+        Some(stem) if file.is_char_boundary(stem) && file[stem..].eq_ignore_ascii_case(".exe") => {
+            &file[..stem]
+        }
+        _ => file,
+    }
+}
@@ -0,0 +1,9 @@
+use sha2::{Digest, Sha256};
+
+/// Hex-encoded SHA-256 of a clipboard payload, used to find duplicates
+pub fn content_hash(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
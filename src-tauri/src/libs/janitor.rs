@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+use crate::libs::clipboard::read_snapshot;
+use crate::libs::clipboard_backend::{ClipboardBackend, ClipboardFormats, TauriClipboard};
+use crate::libs::constants::{EXPIRY_CHECK_INTERVAL_SECS, RETENTION_JANITOR_INTERVAL_SECS};
+use crate::libs::database::DatabaseManager;
+use crate::libs::selection::PrimarySelection;
+use crate::libs::thumbnail::process_image;
+use crate::libs::types::ClipboardSnapshot;
+
+/// Enforce the retention policy periodically, so age limits apply even when nothing is copied
+pub async fn run_janitor(app_handle: AppHandle<Wry>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(RETENTION_JANITOR_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        // The database is not available until an encrypted one is unlocked
+        if let Some(db) = app_handle.try_state::<DatabaseManager>() {
+            enforce_retention(&app_handle, &db);
+        }
+    }
+}
+
+/// Apply the retention policy now and refresh the UI if anything was deleted
+pub fn enforce_retention(app_handle: &AppHandle<Wry>, db: &DatabaseManager) {
+    match db.apply_retention(Utc::now()) {
+        Ok(0) => {}
+        Ok(_) => {
+            let _ = app_handle.emit("clipboard-updated", ());
+        }
+        Err(e) => eprintln!("Failed to apply retention policy: {}", e),
+    }
+}
+
+/// Delete sensitive items once they expire, checking every few seconds
+pub async fn run_expiry(app_handle: AppHandle<Wry>) {
+    let clipboard = TauriClipboard::new(app_handle.clone());
+    let mut interval = tokio::time::interval(Duration::from_secs(EXPIRY_CHECK_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        if let Some(db) = app_handle.try_state::<DatabaseManager>() {
+            let primary = app_handle.try_state::<PrimarySelection>();
+            let mut selections: Vec<&dyn ClipboardBackend> = vec![&clipboard];
+            if let Some(primary) = &primary {
+                selections.push(primary.0.as_ref());
+            }
+            match expire_items(&db, &selections, Utc::now()) {
+                Ok(0) => {}
+                Ok(_) => {
+                    let _ = app_handle.emit("clipboard-updated", ());
+                }
+                Err(e) => eprintln!("Failed to delete expired items: {}", e),
+            }
+        }
+    }
+}
+
+/// Delete items that expired by `now`, and empty each of `selections`, the clipboard and
+/// the primary selection, that still holds one of them. An item is looked for in every
+/// selection, since it may have been pasted into another one than it was captured from.
+/// Returns the number of deleted items.
+pub fn expire_items(
+    db: &DatabaseManager,
+    selections: &[&dyn ClipboardBackend],
+    now: DateTime<Utc>,
+) -> Result<usize, String> {
+    let expired = db.take_expired_items(now).map_err(|e| e.to_string())?;
+    if expired.is_empty() {
+        return Ok(0);
+    }
+
+    // Every selection is tried even when clearing one fails
+    let mut result = Ok(expired.len());
+    for clipboard in selections {
+        let formats = clipboard.available_formats().unwrap_or(ClipboardFormats {
+            text: true,
+            html: true,
+            rtf: true,
+            image: true,
+            files: true,
+            concealed: false,
+        });
+        let current = read_snapshot(*clipboard, &formats);
+        if expired.iter().any(|item| holds_item(&current, item)) {
+            if let Err(e) = clipboard.clear() {
+                result = result.and(Err(e));
+            }
+        }
+    }
+    result
+}
+
+// Whether the clipboard still holds an item, judged by the item's main content.
+// Stored images are re-encoded, so the clipboard image is processed the same way.
+fn holds_item(current: &ClipboardSnapshot, item: &ClipboardSnapshot) -> bool {
+    if item.files.is_some() {
+        current.files == item.files
+    } else if item.text.is_some() {
+        current.text == item.text
+    } else if let Some(image) = &item.image {
+        current
+            .image
+            .as_ref()
+            .and_then(|bytes| process_image(bytes).ok())
+            .is_some_and(|processed| &processed.png == image)
+    } else if item.html.is_some() {
+        current.html == item.html
+    } else {
+        item.rtf.is_some() && current.rtf == item.rtf
+    }
+}
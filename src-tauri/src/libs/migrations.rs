@@ -0,0 +1,597 @@
+use base64::{engine::general_purpose, Engine};
+use image::ImageFormat;
+use rusqlite::{Connection, Transaction};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::Cursor;
+
+use crate::libs::search::search_text;
+use crate::libs::types::ContentType;
+
+/// A single schema change, applied once when the database is below `version`
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub up: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+/// Ordered list of schema migrations. Append new entries at the end and never
+/// edit or reorder one that has already been released.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create clipboard_history and app_config tables",
+        up: migrate_v1_initial_schema,
+    },
+    Migration {
+        version: 2,
+        description: "add full-text search index for text items",
+        up: migrate_v2_full_text_search,
+    },
+    Migration {
+        version: 3,
+        description: "index clipboard_history for paginated queries",
+        up: migrate_v3_history_pagination_index,
+    },
+    Migration {
+        version: 4,
+        description: "store images as PNG blobs with thumbnails",
+        up: migrate_v4_image_blobs,
+    },
+    Migration {
+        version: 5,
+        description: "add content hash and usage tracking",
+        up: migrate_v5_content_hash_and_usage,
+    },
+    Migration {
+        version: 6,
+        description: "store additional clipboard formats per item",
+        up: migrate_v6_clipboard_formats,
+    },
+    Migration {
+        version: 7,
+        description: "add expiry time for sensitive items",
+        up: migrate_v7_item_expiry,
+    },
+    Migration {
+        version: 8,
+        description: "add tags for organising items",
+        up: migrate_v8_tags,
+    },
+    Migration {
+        version: 9,
+        description: "add snippet library",
+        up: migrate_v9_snippets,
+    },
+    Migration {
+        version: 10,
+        description: "record the selection items were captured from",
+        up: migrate_v10_item_selection,
+    },
+    Migration {
+        version: 11,
+        description: "record the application items were copied in",
+        up: migrate_v11_item_source_app,
+    },
+    Migration {
+        version: 12,
+        description: "index html, rtf and file items for search",
+        up: migrate_v12_search_text,
+    },
+];
+
+/// Schema version this binary understands
+pub fn latest_schema_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    Sqlite(rusqlite::Error),
+    /// The database was written by a newer version of the application
+    SchemaTooNew {
+        found: u32,
+        supported: u32,
+    },
+    /// The database is encrypted and the key is missing or wrong
+    Locked,
+    /// Reading or replacing database or key files failed
+    Io(std::io::Error),
+    /// The integrity check found damage
+    Corrupt(String),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::Sqlite(e) => write!(f, "Database error: {}", e),
+            MigrationError::SchemaTooNew { found, supported } => write!(
+                f,
+                "Database schema version {} is newer than the supported version {}. Please update Clipray.",
+                found, supported
+            ),
+            MigrationError::Locked => write!(
+                f,
+                "The database is encrypted and could not be opened with the given key"
+            ),
+            MigrationError::Io(e) => write!(f, "Database file error: {}", e),
+            MigrationError::Corrupt(problems) => write!(f, "The database is damaged: {}", problems),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(e: rusqlite::Error) -> Self {
+        MigrationError::Sqlite(e)
+    }
+}
+
+impl From<std::io::Error> for MigrationError {
+    fn from(e: std::io::Error) -> Self {
+        MigrationError::Io(e)
+    }
+}
+
+pub fn get_schema_version(conn: &Connection) -> rusqlite::Result<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Bring the database schema up to date.
+/// All pending migrations run in a single transaction, so a failure leaves the
+/// database at its previous version.
+pub fn run_migrations(conn: &mut Connection) -> Result<(), MigrationError> {
+    let current = get_schema_version(conn)?;
+    let supported = latest_schema_version();
+
+    if current > supported {
+        return Err(MigrationError::SchemaTooNew {
+            found: current,
+            supported,
+        });
+    }
+    if current == supported {
+        return Ok(());
+    }
+
+    let tx = conn.transaction()?;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        eprintln!(
+            "Applying database migration {}: {}",
+            migration.version, migration.description
+        );
+        (migration.up)(&tx)?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+    }
+    tx.commit()?;
+
+    Ok(())
+}
+
+// Databases created before versioning have these tables already, hence IF NOT EXISTS
+fn migrate_v1_initial_schema(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS clipboard_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            content TEXT NOT NULL,
+            content_type TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            pinned BOOLEAN DEFAULT FALSE
+        )",
+        [],
+    )?;
+
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS app_config (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+// Only text items are indexed; image payloads are base64 and meaningless to search
+fn migrate_v2_full_text_search(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE VIRTUAL TABLE clipboard_fts USING fts5(
+            content,
+            content = 'clipboard_history',
+            content_rowid = 'id',
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER clipboard_fts_insert AFTER INSERT ON clipboard_history
+        WHEN new.content_type = 'text'
+        BEGIN
+            INSERT INTO clipboard_fts (rowid, content) VALUES (new.id, new.content);
+        END;
+
+        CREATE TRIGGER clipboard_fts_delete AFTER DELETE ON clipboard_history
+        WHEN old.content_type = 'text'
+        BEGIN
+            INSERT INTO clipboard_fts (clipboard_fts, rowid, content)
+            VALUES ('delete', old.id, old.content);
+        END;
+
+        CREATE TRIGGER clipboard_fts_update_old BEFORE UPDATE OF content, content_type ON clipboard_history
+        WHEN old.content_type = 'text'
+        BEGIN
+            INSERT INTO clipboard_fts (clipboard_fts, rowid, content)
+            VALUES ('delete', old.id, old.content);
+        END;
+
+        CREATE TRIGGER clipboard_fts_update_new AFTER UPDATE OF content, content_type ON clipboard_history
+        WHEN new.content_type = 'text'
+        BEGIN
+            INSERT INTO clipboard_fts (rowid, content) VALUES (new.id, new.content);
+        END;
+
+        INSERT INTO clipboard_fts (rowid, content)
+        SELECT id, content FROM clipboard_history WHERE content_type = 'text';",
+    )
+}
+
+fn migrate_v3_history_pagination_index(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE INDEX idx_clipboard_history_timestamp ON clipboard_history (timestamp, id)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn migrate_v4_image_blobs(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE clipboard_images (
+            item_id INTEGER PRIMARY KEY,
+            data BLOB NOT NULL,
+            thumbnail BLOB NOT NULL,
+            width INTEGER NOT NULL,
+            height INTEGER NOT NULL
+        );
+
+        CREATE TRIGGER clipboard_images_delete AFTER DELETE ON clipboard_history
+        WHEN old.content_type = 'image'
+        BEGIN
+            DELETE FROM clipboard_images WHERE item_id = old.id;
+        END;",
+    )?;
+
+    // Move existing base64 images out of the content column
+    let legacy_images: Vec<(i64, String)> = {
+        let mut stmt =
+            tx.prepare("SELECT id, content FROM clipboard_history WHERE content_type = 'image'")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    for (id, content) in legacy_images {
+        let processed = general_purpose::STANDARD
+            .decode(&content)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| v4_process_image(&bytes));
+
+        match processed {
+            Ok(image) => {
+                tx.execute(
+                    "INSERT INTO clipboard_images (item_id, data, thumbnail, width, height)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![id, image.png, image.thumbnail, image.width, image.height],
+                )?;
+                tx.execute(
+                    "UPDATE clipboard_history SET content = '' WHERE id = ?1",
+                    [id],
+                )?;
+            }
+            Err(e) => {
+                // Undecodable images could never be displayed or pasted
+                eprintln!("Dropping unreadable image item {}: {}", id, e);
+                tx.execute("DELETE FROM clipboard_history WHERE id = ?1", [id])?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+struct V4Image {
+    png: Vec<u8>,
+    thumbnail: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+// Image processing as it was when v4 was released. Migrations keep their own copy,
+// so changes to `thumbnail::process_image` do not change how old databases migrate.
+fn v4_process_image(bytes: &[u8]) -> Result<V4Image, String> {
+    const THUMBNAIL_MAX_SIZE: u32 = 160;
+
+    let encode_png = |img: &image::DynamicImage| -> Result<Vec<u8>, String> {
+        let mut buffer = Cursor::new(Vec::new());
+        img.write_to(&mut buffer, ImageFormat::Png)
+            .map_err(|e| e.to_string())?;
+        Ok(buffer.into_inner())
+    };
+
+    let format = image::guess_format(bytes).map_err(|e| e.to_string())?;
+    let img = image::load_from_memory_with_format(bytes, format).map_err(|e| e.to_string())?;
+    let png = if format == ImageFormat::Png {
+        bytes.to_vec()
+    } else {
+        encode_png(&img)?
+    };
+    let thumbnail = if img.width() <= THUMBNAIL_MAX_SIZE && img.height() <= THUMBNAIL_MAX_SIZE {
+        png.clone()
+    } else {
+        encode_png(&img.thumbnail(THUMBNAIL_MAX_SIZE, THUMBNAIL_MAX_SIZE))?
+    };
+
+    Ok(V4Image {
+        png,
+        thumbnail,
+        width: img.width(),
+        height: img.height(),
+    })
+}
+
+// `timestamp` keeps the first capture time; `last_used` orders the history
+fn migrate_v5_content_hash_and_usage(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE clipboard_history ADD COLUMN content_hash TEXT;
+        ALTER TABLE clipboard_history ADD COLUMN copy_count INTEGER NOT NULL DEFAULT 1;
+        ALTER TABLE clipboard_history ADD COLUMN last_used TEXT;
+
+        UPDATE clipboard_history SET last_used = timestamp;
+
+        CREATE TRIGGER clipboard_history_default_last_used AFTER INSERT ON clipboard_history
+        WHEN new.last_used IS NULL
+        BEGIN
+            UPDATE clipboard_history SET last_used = new.timestamp WHERE id = new.id;
+        END;
+
+        DROP INDEX idx_clipboard_history_timestamp;
+        CREATE INDEX idx_clipboard_history_last_used ON clipboard_history (last_used, id);
+        CREATE INDEX idx_clipboard_history_hash ON clipboard_history (content_type, content_hash);",
+    )?;
+
+    let texts: Vec<(i64, String)> = {
+        let mut stmt =
+            tx.prepare("SELECT id, content FROM clipboard_history WHERE content_type != 'image'")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    for (id, content) in texts {
+        tx.execute(
+            "UPDATE clipboard_history SET content_hash = ?1 WHERE id = ?2",
+            rusqlite::params![v5_content_hash(content.as_bytes()), id],
+        )?;
+    }
+
+    let images: Vec<(i64, Vec<u8>)> = {
+        let mut stmt = tx.prepare("SELECT item_id, data FROM clipboard_images")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    for (id, data) in images {
+        tx.execute(
+            "UPDATE clipboard_history SET content_hash = ?1 WHERE id = ?2",
+            rusqlite::params![v5_content_hash(&data), id],
+        )?;
+    }
+
+    Ok(())
+}
+
+// Hex SHA-256, as `hash::content_hash` computed it when v5 was released
+fn v5_content_hash(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+// Formats other than the item's own content_type; images stay in clipboard_images
+fn migrate_v6_clipboard_formats(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE clipboard_formats (
+            item_id INTEGER NOT NULL,
+            format TEXT NOT NULL,
+            data TEXT NOT NULL,
+            PRIMARY KEY (item_id, format)
+        );
+
+        CREATE TRIGGER clipboard_formats_delete AFTER DELETE ON clipboard_history
+        BEGIN
+            DELETE FROM clipboard_formats WHERE item_id = old.id;
+        END;
+
+        -- Any item can carry an image now, not only image items
+        DROP TRIGGER clipboard_images_delete;
+        CREATE TRIGGER clipboard_images_delete AFTER DELETE ON clipboard_history
+        BEGIN
+            DELETE FROM clipboard_images WHERE item_id = old.id;
+        END;",
+    )
+}
+
+// Sensitive items are deleted once `expires_at` has passed; NULL never expires
+fn migrate_v7_item_expiry(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE clipboard_history ADD COLUMN expires_at TEXT;
+        CREATE INDEX idx_clipboard_history_expires_at ON clipboard_history (expires_at)
+        WHERE expires_at IS NOT NULL;",
+    )
+}
+
+// Tags are linked to items many-to-many; deleting either side drops the link
+fn migrate_v8_tags(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE
+        );
+
+        CREATE TABLE clipboard_item_tags (
+            item_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (item_id, tag_id)
+        );
+        CREATE INDEX idx_clipboard_item_tags_tag_id ON clipboard_item_tags (tag_id);
+
+        CREATE TRIGGER clipboard_item_tags_item_delete AFTER DELETE ON clipboard_history
+        BEGIN
+            DELETE FROM clipboard_item_tags WHERE item_id = old.id;
+        END;
+
+        CREATE TRIGGER clipboard_item_tags_tag_delete AFTER DELETE ON tags
+        BEGIN
+            DELETE FROM clipboard_item_tags WHERE tag_id = old.id;
+        END;",
+    )
+}
+
+// Snippets live apart from clipboard_history, so trimming and retention never touch them
+fn migrate_v9_snippets(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE snippet_folders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE
+        );
+
+        CREATE TABLE snippets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            abbreviation TEXT UNIQUE COLLATE NOCASE,
+            content TEXT NOT NULL,
+            folder_id INTEGER,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE INDEX idx_snippets_folder_id ON snippets (folder_id);
+
+        -- Snippets of a deleted folder are kept outside any folder
+        CREATE TRIGGER snippet_folders_delete AFTER DELETE ON snippet_folders
+        BEGIN
+            UPDATE snippets SET folder_id = NULL WHERE folder_id = old.id;
+        END;
+
+        CREATE VIRTUAL TABLE snippets_fts USING fts5(
+            name,
+            abbreviation,
+            content,
+            content = 'snippets',
+            content_rowid = 'id',
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER snippets_fts_insert AFTER INSERT ON snippets
+        BEGIN
+            INSERT INTO snippets_fts (rowid, name, abbreviation, content)
+            VALUES (new.id, new.name, new.abbreviation, new.content);
+        END;
+
+        CREATE TRIGGER snippets_fts_delete AFTER DELETE ON snippets
+        BEGIN
+            INSERT INTO snippets_fts (snippets_fts, rowid, name, abbreviation, content)
+            VALUES ('delete', old.id, old.name, old.abbreviation, old.content);
+        END;
+
+        CREATE TRIGGER snippets_fts_update AFTER UPDATE OF name, abbreviation, content ON snippets
+        BEGIN
+            INSERT INTO snippets_fts (snippets_fts, rowid, name, abbreviation, content)
+            VALUES ('delete', old.id, old.name, old.abbreviation, old.content);
+            INSERT INTO snippets_fts (rowid, name, abbreviation, content)
+            VALUES (new.id, new.name, new.abbreviation, new.content);
+        END;",
+    )
+}
+
+// Items captured before the primary selection was supported all came from the clipboard
+fn migrate_v10_item_selection(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE clipboard_history ADD COLUMN selection TEXT NOT NULL DEFAULT 'clipboard';",
+    )
+}
+
+// Unknown for items captured before, so the columns stay empty
+fn migrate_v11_item_source_app(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE clipboard_history ADD COLUMN source_process TEXT;
+        ALTER TABLE clipboard_history ADD COLUMN source_class TEXT;
+        ALTER TABLE clipboard_history ADD COLUMN source_title TEXT;",
+    )
+}
+
+// Html, rtf and file items are indexed by their text or file names, kept in search_text
+// since their content is markup or URIs. The index reads text items' content as before,
+// through a view that picks the indexed text of each item.
+fn migrate_v12_search_text(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch("ALTER TABLE clipboard_history ADD COLUMN search_text TEXT;")?;
+
+    let items: Vec<(i64, ContentType, String)> = {
+        let mut stmt = tx.prepare(
+            "SELECT id, content_type, content FROM clipboard_history
+             WHERE content_type IN ('html', 'rtf', 'files')",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    for (id, content_type, content) in items {
+        tx.execute(
+            "UPDATE clipboard_history SET search_text = ?1 WHERE id = ?2",
+            rusqlite::params![search_text(content_type, &content), id],
+        )?;
+    }
+
+    tx.execute_batch(
+        "DROP TRIGGER clipboard_fts_insert;
+        DROP TRIGGER clipboard_fts_delete;
+        DROP TRIGGER clipboard_fts_update_old;
+        DROP TRIGGER clipboard_fts_update_new;
+        DROP TABLE clipboard_fts;
+
+        CREATE VIEW clipboard_search AS
+        SELECT id, CASE WHEN content_type = 'text' THEN content ELSE search_text END AS content
+        FROM clipboard_history;
+
+        CREATE VIRTUAL TABLE clipboard_fts USING fts5(
+            content,
+            content = 'clipboard_search',
+            content_rowid = 'id',
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER clipboard_fts_insert AFTER INSERT ON clipboard_history
+        WHEN new.content_type = 'text' OR new.search_text IS NOT NULL
+        BEGIN
+            INSERT INTO clipboard_fts (rowid, content)
+            VALUES (new.id, CASE WHEN new.content_type = 'text' THEN new.content ELSE new.search_text END);
+        END;
+
+        CREATE TRIGGER clipboard_fts_delete AFTER DELETE ON clipboard_history
+        WHEN old.content_type = 'text' OR old.search_text IS NOT NULL
+        BEGIN
+            INSERT INTO clipboard_fts (clipboard_fts, rowid, content)
+            VALUES ('delete', old.id, CASE WHEN old.content_type = 'text' THEN old.content ELSE old.search_text END);
+        END;
+
+        CREATE TRIGGER clipboard_fts_update_old BEFORE UPDATE OF content, content_type, search_text ON clipboard_history
+        WHEN old.content_type = 'text' OR old.search_text IS NOT NULL
+        BEGIN
+            INSERT INTO clipboard_fts (clipboard_fts, rowid, content)
+            VALUES ('delete', old.id, CASE WHEN old.content_type = 'text' THEN old.content ELSE old.search_text END);
+        END;
+
+        CREATE TRIGGER clipboard_fts_update_new AFTER UPDATE OF content, content_type, search_text ON clipboard_history
+        WHEN new.content_type = 'text' OR new.search_text IS NOT NULL
+        BEGIN
+            INSERT INTO clipboard_fts (rowid, content)
+            VALUES (new.id, CASE WHEN new.content_type = 'text' THEN new.content ELSE new.search_text END);
+        END;
+
+        INSERT INTO clipboard_fts (rowid, content)
+        SELECT id, content FROM clipboard_search WHERE content IS NOT NULL;",
+    )
+}
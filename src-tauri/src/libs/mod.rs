@@ -1,8 +1,24 @@
+pub mod archive;
+pub mod backup;
 pub mod clipboard;
+pub mod clipboard_backend;
 pub mod commands;
 pub mod config;
 pub mod constants;
 pub mod database;
+pub mod encryption;
+pub mod error;
+pub mod exclusion;
+pub mod hash;
+pub mod janitor;
+pub mod migrations;
+pub mod pause;
+pub mod search;
+pub mod selection;
+pub mod setup;
+pub mod source_app;
+pub mod template;
+pub mod thumbnail;
 pub mod types;
 pub mod window;
 
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tokio::sync::Notify;
+
+use crate::libs::database::DatabaseManager;
+use crate::libs::types::PauseStatus;
+
+/// Capture pause shared by the clipboard monitor, commands and the pause hotkey
+#[derive(Default)]
+pub struct CapturePause {
+    status: Mutex<PauseStatus>,
+    changed: Notify,
+    /// Notified as well, for monitors other than the clipboard monitor
+    subscribers: Mutex<Vec<Arc<Notify>>>,
+}
+
+impl CapturePause {
+    pub fn new(status: PauseStatus) -> Self {
+        Self {
+            status: Mutex::new(status),
+            changed: Notify::new(),
+            subscribers: Mutex::default(),
+        }
+    }
+
+    /// Current status. A timed pause that has run out reads as not paused.
+    pub fn status(&self) -> PauseStatus {
+        let status = self.status.lock().unwrap().clone();
+        if is_expired(&status, Utc::now()) {
+            PauseStatus::default()
+        } else {
+            status
+        }
+    }
+
+    /// Replace the status with one saved by a previous run, without waking the monitor
+    pub fn restore(&self, status: PauseStatus) {
+        *self.status.lock().unwrap() = status;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.status().paused
+    }
+
+    /// Pause capture, for `duration` or until resumed
+    pub fn pause(&self, duration: Option<Duration>) -> PauseStatus {
+        self.set(PauseStatus {
+            paused: true,
+            until: duration.map(|duration| Utc::now() + duration),
+        })
+    }
+
+    pub fn resume(&self) -> PauseStatus {
+        self.set(PauseStatus::default())
+    }
+
+    /// Pause indefinitely, or resume when already paused
+    pub fn toggle(&self) -> PauseStatus {
+        if self.is_paused() {
+            self.resume()
+        } else {
+            self.pause(None)
+        }
+    }
+
+    /// End a timed pause whose time has come.
+    /// Returns true when capture was resumed by this call.
+    pub fn expire(&self, now: DateTime<Utc>) -> bool {
+        let mut status = self.status.lock().unwrap();
+        if is_expired(&status, now) {
+            *status = PauseStatus::default();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Wait until capture is paused or resumed
+    pub async fn changed(&self) {
+        self.changed.notified().await;
+    }
+
+    /// Another waiter for pause and resume, which keeps its own notification
+    pub fn subscribe(&self) -> Arc<Notify> {
+        let notify = Arc::new(Notify::new());
+        self.subscribers.lock().unwrap().push(notify.clone());
+        notify
+    }
+
+    fn set(&self, status: PauseStatus) -> PauseStatus {
+        *self.status.lock().unwrap() = status.clone();
+        self.changed.notify_one();
+        for subscriber in self.subscribers.lock().unwrap().iter() {
+            subscriber.notify_one();
+        }
+        status
+    }
+}
+
+fn is_expired(status: &PauseStatus, now: DateTime<Utc>) -> bool {
+    status.paused && status.until.is_some_and(|until| until <= now)
+}
+
+/// Save the pause status if the user chose to keep it across restarts,
+/// and tell the UI about it
+pub fn publish_pause_status(app_handle: &AppHandle<Wry>, status: &PauseStatus) {
+    if let Some(db) = app_handle.try_state::<DatabaseManager>() {
+        let result = db.get_config().and_then(|config| {
+            if config.persist_pause {
+                db.save_pause_status(status)
+            } else {
+                Ok(())
+            }
+        });
+        if let Err(e) = result {
+            eprintln!("Failed to save pause status: {}", e);
+        }
+    }
+    let _ = app_handle.emit("capture-pause-changed", status);
+}
+
+/// Toggle capture pause from the pause hotkey
+pub fn toggle_pause(app_handle: &AppHandle<Wry>) {
+    if let Some(pause) = app_handle.try_state::<CapturePause>() {
+        let status = pause.toggle();
+        publish_pause_status(app_handle, &status);
+    }
+}
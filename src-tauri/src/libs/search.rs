@@ -0,0 +1,283 @@
+use crate::libs::types::ContentType;
+
+/// Convert user input into an FTS5 MATCH expression.
+///
+/// Double-quoted parts are matched as phrases, everything else is split on
+/// whitespace and matched as prefixes, e.g. `foo "bar baz"` becomes
+/// `"foo"* "bar baz"`. All terms must match. Returns `None` when the input has
+/// no searchable terms.
+pub fn build_fts_query(input: &str) -> Option<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut in_phrase = false;
+    let mut current = String::new();
+
+    for c in input.chars() {
+        if c == '"' {
+            let text = current.trim();
+            if !text.is_empty() {
+                terms.push(if in_phrase {
+                    quote(text)
+                } else {
+                    prefix_terms(text)
+                });
+            }
+            current.clear();
+            in_phrase = !in_phrase;
+        } else {
+            current.push(c);
+        }
+    }
+
+    // An unterminated quote is treated as a phrase up to the end of input
+    let text = current.trim();
+    if !text.is_empty() {
+        terms.push(if in_phrase {
+            quote(text)
+        } else {
+            prefix_terms(text)
+        });
+    }
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+fn prefix_terms(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| format!("{}*", quote(word)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// FTS5 strings escape an embedded quote by doubling it
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// Text indexed for search in place of the content of html, rtf and file items, whose
+/// content is markup or file URIs. `None` for text items, which are indexed as they
+/// are, and for images.
+pub fn search_text(content_type: ContentType, content: &str) -> Option<String> {
+    match content_type {
+        ContentType::Html => Some(html_to_text(content)),
+        ContentType::Rtf => Some(rtf_to_text(content)),
+        ContentType::Files => Some(file_names(content)),
+        ContentType::Text | ContentType::Image => None,
+    }
+}
+
+/// Text of an HTML fragment, without tags, comments, scripts and styles
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&decode_entities(&rest[..start]));
+        // Tags separate words, e.g. between table cells
+        text.push(' ');
+        let tag = &rest[start..];
+        rest = if let Some(comment) = tag.strip_prefix("<!--") {
+            comment.find("-->").map_or("", |end| &comment[end + 3..])
+        } else {
+            let after = tag.find('>').map_or("", |end| &tag[end + 1..]);
+            let name = tag[1..]
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .next()
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            if name == "script" || name == "style" {
+                // Lowercasing ASCII keeps byte offsets
+                let close = format!("</{}", name);
+                match after.to_ascii_lowercase().find(&close) {
+                    Some(end) => {
+                        let closing = &after[end..];
+                        closing.find('>').map_or("", |end| &closing[end + 1..])
+                    }
+                    None => "",
+                }
+            } else {
+                after
+            }
+        };
+    }
+    text.push_str(&decode_entities(rest));
+    collapse_whitespace(&text)
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let entity = rest
+            .find(';')
+            .and_then(|end| decode_entity(&rest[..end]).map(|c| (c, end)));
+        match entity {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => decoded.push('&'),
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let code = name.strip_prefix('#')?;
+            let value = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            char::from_u32(value)
+        }
+    }
+}
+
+// Destinations whose text is not part of the document
+const RTF_SKIPPED_DESTINATIONS: &[&str] = &[
+    "fonttbl",
+    "colortbl",
+    "stylesheet",
+    "info",
+    "pict",
+    "header",
+    "footer",
+    "generator",
+];
+
+/// Text of an RTF document, without control words and non-text destinations.
+/// `\'hh` escapes are read as Latin-1.
+pub fn rtf_to_text(rtf: &str) -> String {
+    let mut text = String::new();
+    // Whether the text of each open group is skipped
+    let mut skipped = vec![false];
+    // Characters left to skip after a `\u` escape, which is followed by a fallback
+    let mut fallback = 0;
+    let mut chars = rtf.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => skipped.push(skipped.last().copied().unwrap_or_default()),
+            '}' => {
+                skipped.pop();
+            }
+            '\\' => match chars.next() {
+                Some(c) if c.is_ascii_alphabetic() => {
+                    let mut word = String::from(c);
+                    while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                        word.push(c);
+                    }
+                    let mut param = String::new();
+                    if let Some(c) = chars.next_if_eq(&'-') {
+                        param.push(c);
+                    }
+                    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+                        param.push(c);
+                    }
+                    // A space ends the control word and is not text
+                    chars.next_if_eq(&' ');
+
+                    match word.as_str() {
+                        "par" | "line" | "tab" | "cell" | "row" => {
+                            push_rtf_char(&mut text, &skipped, &mut 0, ' ')
+                        }
+                        "u" => {
+                            // Negative values stand for code units above 32767
+                            let unit = param.parse::<i32>().unwrap_or_default() as u16;
+                            if let Some(c) = char::from_u32(u32::from(unit)) {
+                                push_rtf_char(&mut text, &skipped, &mut 0, c);
+                            }
+                            fallback = 1;
+                        }
+                        word if RTF_SKIPPED_DESTINATIONS.contains(&word) => {
+                            if let Some(group) = skipped.last_mut() {
+                                *group = true;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Some('\'') => {
+                    let hex: String = chars.by_ref().take(2).collect();
+                    if let Ok(byte) = u8::from_str_radix(&hex, 16) {
+                        push_rtf_char(&mut text, &skipped, &mut fallback, char::from(byte));
+                    }
+                }
+                // `{\*\name ...}` is a destination readers may ignore
+                Some('*') => {
+                    if let Some(group) = skipped.last_mut() {
+                        *group = true;
+                    }
+                }
+                Some('~') => push_rtf_char(&mut text, &skipped, &mut fallback, ' '),
+                Some(c @ ('\\' | '{' | '}')) => {
+                    push_rtf_char(&mut text, &skipped, &mut fallback, c)
+                }
+                _ => {}
+            },
+            '\r' | '\n' => {}
+            c => push_rtf_char(&mut text, &skipped, &mut fallback, c),
+        }
+    }
+    collapse_whitespace(&text)
+}
+
+// Add a character of the document, unless its group is skipped or it is the fallback
+// of a `\u` escape
+fn push_rtf_char(text: &mut String, skipped: &[bool], fallback: &mut usize, c: char) {
+    if *fallback > 0 {
+        *fallback -= 1;
+    } else if !skipped.last().copied().unwrap_or_default() {
+        text.push(c);
+    }
+}
+
+/// Names of the files in newline-separated file URIs, percent-decoded
+pub fn file_names(uris: &str) -> String {
+    uris.lines()
+        .filter_map(|uri| uri.trim_end_matches('/').rsplit(['/', '\\']).next())
+        .filter(|name| !name.is_empty())
+        .map(percent_decode)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,514 @@
+//! The PRIMARY selection, which holds the text last selected with the mouse and is
+//! pasted with a middle click. It only exists on X11; Wayland compositors share it
+//! with X11 applications through XWayland. Changes of the CLIPBOARD selection are
+//! reported from here too, while its contents are left to the clipboard plugin.
+
+use std::sync::Arc;
+
+#[cfg(target_os = "linux")]
+use crate::libs::clipboard_backend::ChangeListener;
+use crate::libs::clipboard_backend::ClipboardBackend;
+
+/// Primary selection, managed when the system has one
+pub struct PrimarySelection(pub Arc<dyn ClipboardBackend>);
+
+/// Connect to the primary selection
+#[cfg(target_os = "linux")]
+pub fn open_primary_selection() -> Result<Arc<dyn ClipboardBackend>, String> {
+    let selection = x11::X11Selection::open().map_err(|e| e.to_string())?;
+    Ok(Arc::new(selection))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn open_primary_selection() -> Result<Arc<dyn ClipboardBackend>, String> {
+    Err("Only X11 and Wayland have a primary selection".to_string())
+}
+
+/// Call `listener` whenever an application takes the CLIPBOARD selection, which it does
+/// on every copy, also when the content is the same as before. Fails without an X server.
+#[cfg(target_os = "linux")]
+pub fn watch_clipboard(listener: ChangeListener) -> Result<(), String> {
+    x11::watch_clipboard(listener).map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use std::error::Error;
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::mpsc::{self, Receiver};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xfixes::{ConnectionExt as _, SelectionEventMask};
+    use x11rb::protocol::xproto::{
+        Atom, AtomEnum, ChangeWindowAttributesAux, ConnectionExt as _, CreateWindowAux, EventMask,
+        GetPropertyReply, PropMode, Property, SelectionNotifyEvent, SelectionRequestEvent, Window,
+        WindowClass, SELECTION_NOTIFY_EVENT,
+    };
+    use x11rb::protocol::Event;
+    use x11rb::rust_connection::RustConnection;
+    use x11rb::wrapper::ConnectionExt as _;
+    use x11rb::{COPY_DEPTH_FROM_PARENT, CURRENT_TIME, NONE};
+
+    use crate::libs::clipboard_backend::{ChangeListener, ClipboardBackend, ClipboardFormats};
+    use crate::libs::constants::SELECTION_READ_TIMEOUT_MS;
+    use crate::libs::source_app::active_app;
+    use crate::libs::types::{ClipboardSnapshot, SourceApp};
+
+    type X11Result<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+    const NOT_IN_SELECTION: &str = "The primary selection only holds text";
+
+    x11rb::atom_manager! {
+        Atoms: AtomsCookie {
+            TARGETS,
+            UTF8_STRING,
+            TEXT,
+            INCR,
+            TEXT_PLAIN: b"text/plain;charset=utf-8",
+            TEXT_HTML: b"text/html",
+            CLIPBOARD,
+            // Property the selection owner writes its answer to
+            CLIPRAY_SELECTION,
+        }
+    }
+
+    fn primary() -> Atom {
+        AtomEnum::PRIMARY.into()
+    }
+
+    /// Connection with a hidden window, to send and receive selection events
+    struct Endpoint {
+        conn: RustConnection,
+        window: Window,
+        atoms: Atoms,
+    }
+
+    impl Endpoint {
+        fn connect() -> X11Result<Self> {
+            let (conn, screen_num) = x11rb::connect(None)?;
+            let screen = &conn.setup().roots[screen_num];
+            let (root, visual) = (screen.root, screen.root_visual);
+            let window = conn.generate_id()?;
+            conn.create_window(
+                COPY_DEPTH_FROM_PARENT,
+                window,
+                root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                WindowClass::INPUT_OUTPUT,
+                visual,
+                &CreateWindowAux::new(),
+            )?
+            .check()?;
+            let atoms = Atoms::new(&conn)?.reply()?;
+            Ok(Self {
+                conn,
+                window,
+                atoms,
+            })
+        }
+
+        /// Receive XFixes events when `selection` gets a new owner
+        fn watch_owner_changes(&self, selection: Atom) -> X11Result<()> {
+            self.conn.xfixes_query_version(5, 0)?.reply()?;
+            self.conn
+                .xfixes_select_selection_input(
+                    self.window,
+                    selection,
+                    SelectionEventMask::SET_SELECTION_OWNER
+                        | SelectionEventMask::SELECTION_WINDOW_DESTROY
+                        | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+                )?
+                .check()?;
+            Ok(())
+        }
+    }
+
+    pub fn watch_clipboard(listener: ChangeListener) -> X11Result<()> {
+        let endpoint = Endpoint::connect()?;
+        endpoint.watch_owner_changes(endpoint.atoms.CLIPBOARD)?;
+        thread::Builder::new()
+            .name("clipboard-events".to_string())
+            .spawn(move || loop {
+                match endpoint.conn.wait_for_event() {
+                    Ok(Event::XfixesSelectionNotify(_)) => listener(),
+                    Ok(_) => {}
+                    Err(e) => {
+                        eprintln!("Lost the connection for clipboard events: {}", e);
+                        return;
+                    }
+                }
+            })?;
+        Ok(())
+    }
+
+    /// Serves the selection to other applications after a write, and reports changes
+    struct Owner {
+        endpoint: Endpoint,
+        /// Targets and their data, offered while this application owns the selection
+        contents: Mutex<Vec<(Atom, Vec<u8>)>>,
+        listeners: Mutex<Vec<ChangeListener>>,
+        /// Number of owner changes seen, once watching
+        changes: AtomicU64,
+        watching: AtomicBool,
+    }
+
+    impl Owner {
+        fn run(&self) {
+            loop {
+                let event = match self.endpoint.conn.wait_for_event() {
+                    Ok(event) => event,
+                    Err(e) => {
+                        eprintln!("Lost the connection for the primary selection: {}", e);
+                        return;
+                    }
+                };
+                match event {
+                    Event::SelectionRequest(request) => {
+                        if let Err(e) = self.answer(&request) {
+                            eprintln!("Failed to hand over the primary selection: {}", e);
+                        }
+                    }
+                    Event::SelectionClear(_) => self.contents.lock().unwrap().clear(),
+                    Event::XfixesSelectionNotify(_) => {
+                        self.changes.fetch_add(1, Ordering::SeqCst);
+                        for listener in self.listeners.lock().unwrap().iter() {
+                            listener();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        fn answer(&self, request: &SelectionRequestEvent) -> X11Result<()> {
+            let Endpoint { conn, atoms, .. } = &self.endpoint;
+            let contents = self.contents.lock().unwrap();
+            // Obsolete clients leave out the property and expect the target to be used
+            let property = if request.property == NONE {
+                request.target
+            } else {
+                request.property
+            };
+
+            let answered = if request.target == atoms.TARGETS {
+                let mut targets = vec![atoms.TARGETS];
+                targets.extend(contents.iter().map(|(target, _)| *target));
+                conn.change_property32(
+                    PropMode::REPLACE,
+                    request.requestor,
+                    property,
+                    AtomEnum::ATOM,
+                    &targets,
+                )?;
+                true
+            } else if let Some((target, data)) = contents
+                .iter()
+                .find(|(target, _)| *target == request.target)
+            {
+                conn.change_property8(
+                    PropMode::REPLACE,
+                    request.requestor,
+                    property,
+                    *target,
+                    data,
+                )?;
+                true
+            } else {
+                false
+            };
+
+            let notify = SelectionNotifyEvent {
+                response_type: SELECTION_NOTIFY_EVENT,
+                sequence: 0,
+                time: request.time,
+                requestor: request.requestor,
+                selection: request.selection,
+                target: request.target,
+                property: if answered { property } else { NONE },
+            };
+            conn.send_event(false, request.requestor, EventMask::NO_EVENT, notify)?;
+            conn.flush()?;
+            Ok(())
+        }
+
+        fn take_selection(&self, contents: Vec<(Atom, Vec<u8>)>) -> X11Result<()> {
+            let Endpoint { conn, window, .. } = &self.endpoint;
+            *self.contents.lock().unwrap() = contents;
+            conn.set_selection_owner(*window, primary(), CURRENT_TIME)?;
+            if conn.get_selection_owner(primary())?.reply()?.owner != *window {
+                return Err("Another application took the primary selection".into());
+            }
+            Ok(())
+        }
+    }
+
+    pub struct X11Selection {
+        /// Reads use their own connection, so the owner thread is free to answer
+        /// them when this application owns the selection
+        reader: Arc<Endpoint>,
+        /// Selection and property events of the reader's window. Locked for a whole
+        /// read, so the answers to concurrent reads do not mix.
+        answers: Mutex<Receiver<Event>>,
+        owner: Arc<Owner>,
+        atoms: Atoms,
+    }
+
+    impl X11Selection {
+        pub fn open() -> X11Result<Self> {
+            let reader = Arc::new(Endpoint::connect()?);
+            let atoms = reader.atoms;
+            // Parts of large selections are announced with property changes
+            reader
+                .conn
+                .change_window_attributes(
+                    reader.window,
+                    &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+                )?
+                .check()?;
+            let (sender, answers) = mpsc::channel();
+            let receiving = reader.clone();
+            thread::Builder::new()
+                .name("primary-selection-reader".to_string())
+                .spawn(move || loop {
+                    match receiving.conn.wait_for_event() {
+                        Ok(event @ (Event::SelectionNotify(_) | Event::PropertyNotify(_))) => {
+                            if sender.send(event).is_err() {
+                                return;
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            eprintln!(
+                                "Lost the connection for reading the primary selection: {}",
+                                e
+                            );
+                            return;
+                        }
+                    }
+                })?;
+
+            let owner = Arc::new(Owner {
+                endpoint: Endpoint::connect()?,
+                contents: Mutex::default(),
+                listeners: Mutex::default(),
+                changes: AtomicU64::new(0),
+                watching: AtomicBool::new(false),
+            });
+            let serving = owner.clone();
+            thread::Builder::new()
+                .name("primary-selection".to_string())
+                .spawn(move || serving.run())?;
+            Ok(Self {
+                reader,
+                answers: Mutex::new(answers),
+                owner,
+                atoms,
+            })
+        }
+
+        /// Ask the owner for the selection as `target`; `None` when it does not offer it
+        fn convert(&self, target: Atom) -> X11Result<Option<GetPropertyReply>> {
+            let answers = self.answers.lock().unwrap();
+            let Endpoint {
+                conn,
+                window,
+                atoms,
+            } = &*self.reader;
+            // Left over from reads that timed out
+            while answers.try_recv().is_ok() {}
+            conn.convert_selection(
+                *window,
+                primary(),
+                target,
+                atoms.CLIPRAY_SELECTION,
+                CURRENT_TIME,
+            )?;
+            conn.flush()?;
+
+            let timeout = Duration::from_millis(SELECTION_READ_TIMEOUT_MS);
+            let deadline = Instant::now() + timeout;
+            let property = loop {
+                match answers.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(Event::SelectionNotify(event)) if event.target == target => {
+                        break event.property
+                    }
+                    Ok(_) => {}
+                    Err(_) => {
+                        return Err("The owner of the primary selection did not answer".into())
+                    }
+                }
+            };
+            if property == NONE {
+                return Ok(None);
+            }
+            let take_property = || {
+                conn.get_property(true, *window, property, AtomEnum::ANY, 0, u32::MAX / 4)?
+                    .reply()
+            };
+            let reply = take_property()?;
+            if reply.type_ != atoms.INCR {
+                return Ok(Some(reply));
+            }
+
+            // Selections too large for one property come in parts. Deleting the property
+            // asks for the next one, and an empty part ends the transfer.
+            let mut value = Vec::new();
+            loop {
+                let deadline = Instant::now() + timeout;
+                loop {
+                    match answers.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                        Ok(Event::PropertyNotify(event))
+                            if event.atom == property && event.state == Property::NEW_VALUE =>
+                        {
+                            break
+                        }
+                        Ok(_) => {}
+                        Err(_) => {
+                            return Err(
+                                "The owner of the primary selection stopped sending it".into()
+                            )
+                        }
+                    }
+                }
+                let mut part = take_property()?;
+                if part.value.is_empty() {
+                    part.value = value;
+                    return Ok(Some(part));
+                }
+                value.append(&mut part.value);
+            }
+        }
+
+        /// Like `convert`; selections that cannot be read are logged, since the
+        /// monitor only keeps the formats that could be read
+        fn read(&self, target: Atom) -> Result<Option<GetPropertyReply>, String> {
+            self.convert(target).map_err(|e| {
+                eprintln!("Skipped reading the primary selection: {}", e);
+                e.to_string()
+            })
+        }
+
+        fn read_string(&self, target: Atom) -> Result<Option<String>, String> {
+            let reply = self.read(target)?;
+            Ok(reply.map(|reply| decode_text(&reply.value)))
+        }
+    }
+
+    // Firefox offers HTML as UTF-16 with a byte order mark, everything else uses UTF-8
+    fn decode_text(bytes: &[u8]) -> String {
+        match bytes {
+            [0xFF, 0xFE, rest @ ..] => {
+                let units: Vec<u16> = rest
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                    .collect();
+                String::from_utf16_lossy(&units)
+            }
+            _ => String::from_utf8_lossy(bytes).into_owned(),
+        }
+    }
+
+    impl ClipboardBackend for X11Selection {
+        fn read_text(&self) -> Result<String, String> {
+            for target in [self.atoms.UTF8_STRING, self.atoms.TEXT_PLAIN] {
+                if let Some(text) = self.read_string(target)? {
+                    return Ok(text);
+                }
+            }
+            // Latin-1, from old applications
+            match self.read(AtomEnum::STRING.into())? {
+                Some(reply) => Ok(reply.value.iter().map(|&byte| char::from(byte)).collect()),
+                None => Err("No text in the primary selection".to_string()),
+            }
+        }
+
+        fn read_image(&self) -> Result<Vec<u8>, String> {
+            Err(NOT_IN_SELECTION.to_string())
+        }
+
+        fn read_html(&self) -> Result<String, String> {
+            self.read_string(self.atoms.TEXT_HTML)?
+                .ok_or_else(|| "No HTML in the primary selection".to_string())
+        }
+
+        fn read_rtf(&self) -> Result<String, String> {
+            Err(NOT_IN_SELECTION.to_string())
+        }
+
+        fn read_files(&self) -> Result<Vec<String>, String> {
+            Err(NOT_IN_SELECTION.to_string())
+        }
+
+        fn available_formats(&self) -> Result<ClipboardFormats, String> {
+            let targets: Vec<Atom> = match self.read(self.atoms.TARGETS)? {
+                Some(reply) => reply.value32().into_iter().flatten().collect(),
+                // Nothing is selected
+                None => Vec::new(),
+            };
+            let text_targets = [
+                self.atoms.UTF8_STRING,
+                self.atoms.TEXT_PLAIN,
+                self.atoms.TEXT,
+                AtomEnum::STRING.into(),
+            ];
+            Ok(ClipboardFormats {
+                text: targets.iter().any(|target| text_targets.contains(target)),
+                html: targets.contains(&self.atoms.TEXT_HTML),
+                ..Default::default()
+            })
+        }
+
+        fn write(&self, snapshot: ClipboardSnapshot) -> Result<(), String> {
+            let mut contents = Vec::new();
+            if let Some(text) = snapshot.text {
+                contents.push((self.atoms.UTF8_STRING, text.clone().into_bytes()));
+                contents.push((self.atoms.TEXT_PLAIN, text.into_bytes()));
+            }
+            if let Some(html) = snapshot.html {
+                contents.push((self.atoms.TEXT_HTML, html.into_bytes()));
+            }
+            if contents.is_empty() {
+                return Err(NOT_IN_SELECTION.to_string());
+            }
+            self.owner
+                .take_selection(contents)
+                .map_err(|e| e.to_string())
+        }
+
+        fn clear(&self) -> Result<(), String> {
+            let conn = &self.owner.endpoint.conn;
+            self.owner.contents.lock().unwrap().clear();
+            conn.set_selection_owner(NONE, primary(), CURRENT_TIME)
+                .map_err(|e| e.to_string())?;
+            conn.flush().map_err(|e| e.to_string())
+        }
+
+        fn watch(&self, listener: ChangeListener) -> Result<bool, String> {
+            self.owner
+                .endpoint
+                .watch_owner_changes(primary())
+                .map_err(|e| e.to_string())?;
+            self.owner.listeners.lock().unwrap().push(listener);
+            self.owner.watching.store(true, Ordering::SeqCst);
+            Ok(true)
+        }
+
+        fn change_marker(&self) -> Option<u64> {
+            // Only exact while owner changes are reported
+            self.owner
+                .watching
+                .load(Ordering::SeqCst)
+                .then(|| self.owner.changes.load(Ordering::SeqCst))
+        }
+
+        fn source_app(&self) -> Option<SourceApp> {
+            active_app()
+        }
+    }
+}
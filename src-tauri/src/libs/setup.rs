@@ -0,0 +1,258 @@
+use chrono::Utc;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::libs::backup::{move_aside, open_or_recover, RecoveryState};
+use crate::libs::config::{register_hotkey, register_pause_hotkey};
+use crate::libs::constants::{DATABASE_FILE_NAME, STARTUP_LOG_FILE_NAME};
+use crate::libs::database::DatabaseManager;
+use crate::libs::error::ClipRayError;
+use crate::libs::migrations::MigrationError;
+use crate::libs::pause::CapturePause;
+use crate::libs::types::{StartupAction, StartupErrorCode, StartupFailure};
+use crate::libs::window::show_webview_window_impl;
+
+#[derive(Debug)]
+pub enum StartupError {
+    /// The app data directory could not be resolved or created
+    DataDir(String),
+    Database(MigrationError),
+    /// The settings could not be read from the database
+    Config(ClipRayError),
+}
+
+impl fmt::Display for StartupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartupError::DataDir(message) => write!(f, "{}", message),
+            StartupError::Database(e) => write!(f, "{}", e),
+            StartupError::Config(e) => write!(f, "Failed to load settings: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for StartupError {}
+
+impl StartupError {
+    pub fn code(&self) -> StartupErrorCode {
+        match self {
+            StartupError::DataDir(_) => StartupErrorCode::DataDir,
+            StartupError::Database(MigrationError::SchemaTooNew { .. }) => {
+                StartupErrorCode::DatabaseTooNew
+            }
+            StartupError::Database(_) => StartupErrorCode::Database,
+            StartupError::Config(_) => StartupErrorCode::Config,
+        }
+    }
+
+    /// Recovery actions that can help with this error
+    pub fn actions(&self) -> Vec<StartupAction> {
+        match self {
+            StartupError::DataDir(_) => vec![StartupAction::Retry],
+            StartupError::Database(_) => {
+                vec![StartupAction::Retry, StartupAction::MoveDatabaseAside]
+            }
+            StartupError::Config(_) => vec![
+                StartupAction::Retry,
+                StartupAction::ResetConfig,
+                StartupAction::MoveDatabaseAside,
+            ],
+        }
+    }
+
+    pub fn to_failure(&self, data_dir: Option<&Path>) -> StartupFailure {
+        StartupFailure {
+            code: self.code(),
+            message: self.to_string(),
+            data_dir: data_dir.map(|dir| dir.display().to_string()),
+            actions: self.actions(),
+        }
+    }
+}
+
+/// Why startup failed, kept for the window to show until a recovery action works
+#[derive(Default)]
+pub struct StartupFailureState {
+    failure: Mutex<Option<StartupFailure>>,
+    /// The settings of the encrypted database are reset once it is unlocked
+    config_reset_pending: AtomicBool,
+}
+
+impl StartupFailureState {
+    pub fn get(&self) -> Option<StartupFailure> {
+        self.failure.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, failure: Option<StartupFailure>) {
+        *self.failure.lock().unwrap() = failure;
+    }
+
+    pub fn defer_config_reset(&self) {
+        self.config_reset_pending.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether a reset was deferred, clearing the request
+    pub fn take_config_reset(&self) -> bool {
+        self.config_reset_pending.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// Directory holding the database, its backups and the startup log, creating it if needed
+pub fn data_dir(app_handle: &AppHandle<Wry>) -> Result<PathBuf, StartupError> {
+    let dir = app_handle.path().app_data_dir().map_err(|e| {
+        StartupError::DataDir(format!("Failed to resolve app data directory: {}", e))
+    })?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| StartupError::DataDir(format!("Failed to create {}: {}", dir.display(), e)))?;
+    Ok(dir)
+}
+
+/// Location of the history database, creating its directory if needed
+pub fn database_path(app_handle: &AppHandle<Wry>) -> Result<PathBuf, StartupError> {
+    Ok(data_dir(app_handle)?.join(DATABASE_FILE_NAME))
+}
+
+/// Open the history database and finish startup. An encrypted database is left
+/// for the window to unlock.
+pub fn open_history(app_handle: &AppHandle<Wry>) -> Result<(), StartupError> {
+    let db_path = database_path(app_handle)?;
+    match open_or_recover(&db_path, None) {
+        Ok((db, notice)) => {
+            if let Some(notice) = notice {
+                app_handle.state::<RecoveryState>().set(notice);
+            }
+            on_database_ready(app_handle, db)
+        }
+        // History stays unavailable until the passphrase is entered in the window
+        Err(MigrationError::Locked) => {
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = show_webview_window_impl(&window);
+            }
+            Ok(())
+        }
+        Err(e) => Err(StartupError::Database(e)),
+    }
+}
+
+/// Finish startup once the encrypted database was unlocked, first resetting the settings
+/// if that was asked for while it was locked
+pub fn on_database_unlocked(
+    app_handle: &AppHandle<Wry>,
+    db: DatabaseManager,
+) -> Result<(), StartupError> {
+    if app_handle
+        .state::<StartupFailureState>()
+        .take_config_reset()
+    {
+        db.reset_config().map_err(StartupError::Config)?;
+    }
+    on_database_ready(app_handle, db)
+}
+
+/// Finish startup once the database is open, either right away or after it was unlocked
+pub fn on_database_ready(
+    app_handle: &AppHandle<Wry>,
+    db: DatabaseManager,
+) -> Result<(), StartupError> {
+    // Load configuration and setup hotkey
+    let config = db.get_config().map_err(StartupError::Config)?;
+
+    // Restore capture pause if the user chose to keep it
+    if config.persist_pause {
+        let status = db.get_pause_status().unwrap_or_default();
+        app_handle.state::<CapturePause>().restore(status);
+    }
+
+    app_handle.manage(db);
+
+    // Register hotkey (application continues even if this fails)
+    if let Err(e) = register_hotkey(app_handle, &config.hotkey) {
+        eprintln!("Failed to register hotkey '{}': {}", config.hotkey, e);
+        eprintln!("The hotkey might already be in use by another application.");
+        eprintln!("You can change the hotkey in the settings.");
+    } else {
+        println!("Hotkey '{}' registered successfully!", config.hotkey);
+    }
+
+    if let Some(pause_hotkey) = &config.pause_hotkey {
+        if let Err(e) = register_pause_hotkey(app_handle, pause_hotkey) {
+            eprintln!("Failed to register pause hotkey '{}': {}", pause_hotkey, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Log a failed startup and show it in the window, which offers the recovery actions.
+/// The tray and the window keep running so the user can act on it.
+pub fn report_startup_failure(app_handle: &AppHandle<Wry>, error: &StartupError) -> StartupFailure {
+    eprintln!("Startup failed: {}", error);
+    let data_dir = app_handle.path().app_data_dir().ok();
+    if let Some(dir) = &data_dir {
+        if let Err(e) = log_startup_failure(dir, error) {
+            eprintln!("Failed to write the startup log: {}", e);
+        }
+    }
+
+    let failure = error.to_failure(data_dir.as_deref());
+    app_handle
+        .state::<StartupFailureState>()
+        .set(Some(failure.clone()));
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = show_webview_window_impl(&window);
+    }
+    failure
+}
+
+/// Append the error to the startup log in `data_dir`
+pub fn log_startup_failure(data_dir: &Path, error: &StartupError) -> io::Result<()> {
+    fs::create_dir_all(data_dir)?;
+    let mut log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(data_dir.join(STARTUP_LOG_FILE_NAME))?;
+    writeln!(log, "{} {}", Utc::now().to_rfc3339(), error)
+}
+
+/// Put the settings of the database at `db_path` back to their defaults. Returns false
+/// without changing anything when the database is encrypted and has to be unlocked first.
+pub fn reset_config(db_path: &Path) -> Result<bool, StartupError> {
+    match open_or_recover(db_path, None) {
+        Ok((db, _)) => {
+            db.reset_config().map_err(StartupError::Config)?;
+            Ok(true)
+        }
+        Err(MigrationError::Locked) => Ok(false),
+        Err(e) => Err(StartupError::Database(e)),
+    }
+}
+
+/// Run a recovery action, then try to start again. Settings of an encrypted database
+/// are reset after the passphrase is entered.
+pub fn recover_startup(
+    app_handle: &AppHandle<Wry>,
+    action: StartupAction,
+) -> Result<(), StartupError> {
+    match action {
+        StartupAction::Retry => {}
+        StartupAction::ResetConfig => {
+            if !reset_config(&database_path(app_handle)?)? {
+                app_handle
+                    .state::<StartupFailureState>()
+                    .defer_config_reset();
+            }
+        }
+        StartupAction::MoveDatabaseAside => {
+            let db_path = database_path(app_handle)?;
+            let moved = move_aside(&db_path, "moved", Utc::now())
+                .map_err(|e| StartupError::Database(e.into()))?;
+            eprintln!("Moved the database to {}", moved.display());
+        }
+    }
+    open_history(app_handle)
+}
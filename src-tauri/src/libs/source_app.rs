@@ -0,0 +1,211 @@
+//! The application a copy was made in. Clipboards do not record who wrote to
+//! them, so the application with the focused window at the time of the change is
+//! taken instead: `_NET_ACTIVE_WINDOW` on X11, the foreground window on Windows.
+//! Other platforms do not report a source.
+
+use crate::libs::types::SourceApp;
+
+/// Application with the focused window, if the platform tells
+#[cfg(target_os = "linux")]
+pub fn active_app() -> Option<SourceApp> {
+    x11::active_app()
+}
+
+#[cfg(windows)]
+pub fn active_app() -> Option<SourceApp> {
+    win32::active_app()
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn active_app() -> Option<SourceApp> {
+    None
+}
+
+// Window titles and class names can be long; what is stored is cut to this many characters
+#[cfg(any(target_os = "linux", windows))]
+const MAX_NAME_LENGTH: usize = 256;
+
+#[cfg(any(target_os = "linux", windows))]
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.chars().take(MAX_NAME_LENGTH).collect())
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use std::error::Error;
+    use std::fs;
+    use std::sync::{Mutex, OnceLock};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt as _, Window};
+    use x11rb::rust_connection::RustConnection;
+
+    use super::non_empty;
+    use crate::libs::types::SourceApp;
+
+    type X11Result<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+    x11rb::atom_manager! {
+        Atoms: AtomsCookie {
+            _NET_ACTIVE_WINDOW,
+            _NET_WM_NAME,
+            _NET_WM_PID,
+            UTF8_STRING,
+        }
+    }
+
+    struct Display {
+        conn: RustConnection,
+        root: Window,
+        atoms: Atoms,
+    }
+
+    // Connected on first use; stays `None` without an X server
+    static DISPLAY: OnceLock<Option<Mutex<Display>>> = OnceLock::new();
+
+    fn connect() -> X11Result<Display> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let root = conn.setup().roots[screen_num].root;
+        let atoms = Atoms::new(&conn)?.reply()?;
+        Ok(Display { conn, root, atoms })
+    }
+
+    pub fn active_app() -> Option<SourceApp> {
+        let display = DISPLAY
+            .get_or_init(|| connect().ok().map(Mutex::new))
+            .as_ref()?;
+        let display = display.lock().unwrap();
+        let window = display.active_window().ok()??;
+        Some(SourceApp {
+            process: display.process_name(window),
+            class: display.class(window),
+            title: display.title(window),
+        })
+    }
+
+    impl Display {
+        fn property(&self, window: Window, property: Atom, type_: Atom) -> X11Result<Vec<u8>> {
+            let reply = self
+                .conn
+                .get_property(false, window, property, type_, 0, 1024)?
+                .reply()?;
+            Ok(reply.value)
+        }
+
+        fn active_window(&self) -> X11Result<Option<Window>> {
+            let reply = self
+                .conn
+                .get_property(
+                    false,
+                    self.root,
+                    self.atoms._NET_ACTIVE_WINDOW,
+                    AtomEnum::WINDOW,
+                    0,
+                    1,
+                )?
+                .reply()?;
+            let window = reply.value32().and_then(|mut values| values.next());
+            Ok(window.filter(|&window| window != x11rb::NONE))
+        }
+
+        // `WM_CLASS` holds the instance and the class name, each ending in a NUL
+        fn class(&self, window: Window) -> Option<String> {
+            let value = self
+                .property(window, AtomEnum::WM_CLASS.into(), AtomEnum::STRING.into())
+                .ok()?;
+            let mut names = value
+                .split(|&byte| byte == 0)
+                .filter(|name| !name.is_empty());
+            let instance = names.next()?;
+            let class = names.next().unwrap_or(instance);
+            non_empty(String::from_utf8_lossy(class).into_owned())
+        }
+
+        fn title(&self, window: Window) -> Option<String> {
+            let title = self
+                .property(window, self.atoms._NET_WM_NAME, self.atoms.UTF8_STRING)
+                .ok()
+                .filter(|title| !title.is_empty())
+                .or_else(|| {
+                    self.property(window, AtomEnum::WM_NAME.into(), AtomEnum::ANY.into())
+                        .ok()
+                })?;
+            non_empty(String::from_utf8_lossy(&title).into_owned())
+        }
+
+        // Only right for local clients, which is where the clipboard is used
+        fn process_name(&self, window: Window) -> Option<String> {
+            let reply = self
+                .conn
+                .get_property(
+                    false,
+                    window,
+                    self.atoms._NET_WM_PID,
+                    AtomEnum::CARDINAL,
+                    0,
+                    1,
+                )
+                .ok()?
+                .reply()
+                .ok()?;
+            let pid = reply.value32()?.next()?;
+            non_empty(fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?)
+        }
+    }
+}
+
+#[cfg(windows)]
+mod win32 {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetClassNameW, GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId,
+    };
+
+    use super::non_empty;
+    use crate::libs::types::SourceApp;
+
+    pub fn active_app() -> Option<SourceApp> {
+        unsafe {
+            let window = GetForegroundWindow();
+            if window.is_invalid() {
+                return None;
+            }
+
+            let mut buffer = [0u16; 512];
+            let length = GetClassNameW(window, &mut buffer);
+            let class = non_empty(String::from_utf16_lossy(&buffer[..length.max(0) as usize]));
+            let length = GetWindowTextW(window, &mut buffer);
+            let title = non_empty(String::from_utf16_lossy(&buffer[..length.max(0) as usize]));
+
+            let mut pid = 0;
+            GetWindowThreadProcessId(window, Some(&mut pid));
+            Some(SourceApp {
+                process: process_name(pid),
+                class,
+                title,
+            })
+        }
+    }
+
+    // File name of the executable, e.g. `KeePassXC.exe`
+    unsafe fn process_name(pid: u32) -> Option<String> {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buffer = [0u16; 1024];
+        let mut length = buffer.len() as u32;
+        let result = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut length,
+        );
+        let _ = CloseHandle(process);
+        result.ok()?;
+        let path = String::from_utf16_lossy(&buffer[..length as usize]);
+        non_empty(path.rsplit('\\').next()?.to_string())
+    }
+}
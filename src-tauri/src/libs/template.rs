@@ -0,0 +1,206 @@
+//! Snippet templates: `{date}`, `{date:FORMAT}`, `{clipboard}`, `{uuid}` and named
+//! fill-in fields written as `{field:Name}`.
+//!
+//! Anything else in braces is plain text, so code snippets keep working unchanged.
+//! To write a placeholder literally, double its opening brace: `{{date}` gives `{date}`.
+
+use chrono::{format::Item, format::StrftimeItems, DateTime, FixedOffset};
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+use crate::libs::constants::DEFAULT_TEMPLATE_DATE_FORMAT;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Text(String),
+    /// strftime-style format understood by chrono
+    Date(String),
+    Clipboard,
+    Uuid,
+    Field(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    InvalidDateFormat(String),
+    EmptyFieldName,
+    MissingField(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::InvalidDateFormat(format) => {
+                write!(f, "Invalid date format '{}'", format)
+            }
+            TemplateError::EmptyFieldName => write!(f, "Fill-in fields need a name"),
+            TemplateError::MissingField(name) => write!(f, "No value for field '{}'", name),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// Values placeholders are replaced with
+pub struct TemplateContext<'a> {
+    pub now: DateTime<FixedOffset>,
+    /// Current clipboard text; `{clipboard}` is empty without it
+    pub clipboard: Option<&'a str>,
+    pub fields: &'a HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find('{') {
+            text.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            // `{{name}` is an escaped placeholder, written out with a single brace
+            if let Some(escaped) = rest.strip_prefix("{{") {
+                if let Some((body, _)) = placeholder_body(escaped) {
+                    if parse_placeholder(body) != Ok(None) {
+                        text.push('{');
+                        text.push_str(body);
+                        text.push('}');
+                        rest = &escaped[body.len() + 1..];
+                        continue;
+                    }
+                }
+            }
+
+            match placeholder_body(&rest[1..]) {
+                Some((body, after)) => match parse_placeholder(body)? {
+                    Some(segment) => {
+                        if !text.is_empty() {
+                            segments.push(Segment::Text(std::mem::take(&mut text)));
+                        }
+                        segments.push(segment);
+                        rest = after;
+                    }
+                    None => {
+                        text.push('{');
+                        rest = &rest[1..];
+                    }
+                },
+                None => {
+                    text.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+
+        text.push_str(rest);
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        Ok(Template { segments })
+    }
+
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Names of the fill-in fields, in order of first use
+    pub fn fields(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for segment in &self.segments {
+            if let Segment::Field(name) = segment {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+
+    pub fn expand(&self, context: &TemplateContext) -> Result<String, TemplateError> {
+        let mut output = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => output.push_str(text),
+                Segment::Date(format) => {
+                    // The format was validated while parsing
+                    write!(output, "{}", context.now.format(format))
+                        .map_err(|_| TemplateError::InvalidDateFormat(format.clone()))?;
+                }
+                Segment::Clipboard => output.push_str(context.clipboard.unwrap_or_default()),
+                Segment::Uuid => output.push_str(&new_uuid()),
+                Segment::Field(name) => output.push_str(
+                    context
+                        .fields
+                        .get(name)
+                        .ok_or_else(|| TemplateError::MissingField(name.clone()))?,
+                ),
+            }
+        }
+        Ok(output)
+    }
+}
+
+// Text up to the closing brace, and what follows it. Placeholders never span lines.
+fn placeholder_body(input: &str) -> Option<(&str, &str)> {
+    let end = input.find(['}', '{', '\n'])?;
+    if input.as_bytes()[end] != b'}' {
+        return None;
+    }
+    Some((&input[..end], &input[end + 1..]))
+}
+
+// `None` when the text in braces is not a placeholder
+fn parse_placeholder(body: &str) -> Result<Option<Segment>, TemplateError> {
+    let (name, argument) = match body.split_once(':') {
+        Some((name, argument)) => (name, Some(argument)),
+        None => (body, None),
+    };
+
+    let segment = match (name, argument) {
+        ("date", None) => Segment::Date(DEFAULT_TEMPLATE_DATE_FORMAT.to_string()),
+        ("date", Some(format)) => {
+            let valid = !format.is_empty()
+                && StrftimeItems::new(format).all(|item| !matches!(item, Item::Error));
+            if !valid {
+                return Err(TemplateError::InvalidDateFormat(format.to_string()));
+            }
+            Segment::Date(format.to_string())
+        }
+        ("clipboard", None) => Segment::Clipboard,
+        ("uuid", None) => Segment::Uuid,
+        ("field", Some(field)) => {
+            let field = field.trim();
+            if field.is_empty() {
+                return Err(TemplateError::EmptyFieldName);
+            }
+            Segment::Field(field.to_string())
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(segment))
+}
+
+// Random (version 4) UUID in its usual hyphenated form
+fn new_uuid() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("no random number source");
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        clipboard::ClipboardMonitor,
+        clipboard_backend::{ClipboardBackend, MemoryClipboard, MultiClipboard},
+        database::DatabaseManager,
+        exclusion::{AppMatcher, ExclusionFilter, ExclusionReason},
+        types::{AppConfig, ClipboardContent, ExclusionRules, SourceApp},
+    };
+    use serial_test::serial;
+
+    fn app(process: &str, class: &str) -> SourceApp {
+        SourceApp {
+            process: Some(process.to_string()),
+            class: Some(class.to_string()),
+            title: None,
+        }
+    }
+
+    fn matches(entry: &str, source_app: &SourceApp) -> bool {
+        AppMatcher::parse(entry).unwrap().matches(source_app)
+    }
+
+    fn blacklist(db: &DatabaseManager, apps: &[&str]) {
+        db.update_config(&AppConfig {
+            exclusion_rules: ExclusionRules {
+                apps: apps.iter().map(|app| app.to_string()).collect(),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_plain_name_matches_process_or_class() {
+        let remmina = app("remmina", "org.remmina.Remmina");
+        assert!(matches("remmina", &remmina));
+        assert!(matches("ORG.REMMINA.REMMINA", &remmina));
+        assert!(matches("  Remmina  ", &remmina));
+        // Whole names only
+        assert!(!matches("remmin", &remmina));
+        assert!(!matches("Remmina.exe.bak", &remmina));
+    }
+
+    #[test]
+    fn test_qualified_names() {
+        let keepass = app("keepassxc", "KeePassXC-Browser");
+        assert!(matches("process:keepassxc", &keepass));
+        assert!(matches("Process: KeePassXC", &keepass));
+        assert!(!matches("class:keepassxc", &keepass));
+        assert!(matches("class:keepassxc-browser", &keepass));
+        assert!(!matches("process:keepassxc-browser", &keepass));
+
+        // Only the known names are compared
+        let class_only = SourceApp {
+            class: Some("Mstsc".to_string()),
+            ..Default::default()
+        };
+        assert!(matches("mstsc", &class_only));
+        assert!(!matches("process:mstsc", &class_only));
+    }
+
+    #[test]
+    fn test_wildcards() {
+        let remmina = app("remmina", "org.remmina.Remmina");
+        assert!(matches("class:org.remmina.*", &remmina));
+        assert!(matches("*remmina", &remmina));
+        assert!(matches("rem*na", &remmina));
+        assert!(!matches("class:com.*", &remmina));
+        // Everything else is literal
+        assert!(!matches("class:org?remmina?Remmina", &remmina));
+        assert!(!matches("class:org.remmina.[R]emmina", &remmina));
+    }
+
+    #[test]
+    fn test_process_names_ignore_directory_and_extension() {
+        let keepass = app("KeePass.exe", "WindowsForms10.Window.8.app");
+        assert!(matches("keepass", &keepass));
+        assert!(matches("KEEPASS.EXE", &keepass));
+        assert!(matches(r"C:\Program Files\KeePass\KeePass.exe", &keepass));
+        assert!(matches("process:/opt/keepass/keepass", &keepass));
+        assert!(matches("keep*.exe", &keepass));
+        assert!(!matches("class:keepass", &keepass));
+    }
+
+    #[test]
+    fn test_entries_without_a_name_are_rejected() {
+        for entry in ["process:", "class:  ", "*", "process:**"] {
+            let error = AppMatcher::parse(entry).unwrap_err();
+            assert!(error.contains("Invalid app"), "{}", error);
+        }
+
+        let rules = ExclusionRules {
+            apps: vec!["keepassxc".to_string(), "class:".to_string()],
+            ..Default::default()
+        };
+        assert!(ExclusionFilter::new(&rules).is_err());
+
+        // Blank lines are skipped, and indices still point into the list
+        let rules = ExclusionRules {
+            apps: vec!["".to_string(), "process:keepassxc".to_string()],
+            ..Default::default()
+        };
+        let filter = ExclusionFilter::new(&rules).unwrap();
+        assert_eq!(
+            filter.check_app(Some(&app("keepassxc", "KeePassXC"))),
+            Some(ExclusionReason::App(1))
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_blacklisted_app_is_not_read() {
+        let db = DatabaseManager::new_test().unwrap();
+        blacklist(&db, &["process:keepassxc"]);
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        clipboard.set_source_app(Some(app("keepassxc", "KeePassXC")));
+        clipboard.write_text("hunter2".to_string()).unwrap();
+        assert!(!monitor.poll(&clipboard, &db));
+        assert_eq!(clipboard.read_count(), 0);
+
+        // Switching windows afterwards does not record it either
+        clipboard.set_source_app(Some(app("firefox", "Firefox")));
+        assert!(!monitor.poll(&clipboard, &db));
+        assert_eq!(clipboard.read_count(), 0);
+
+        clipboard.write_text("kept".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].content, ClipboardContent::text("kept"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_blacklisted_copy_without_change_marker() {
+        let db = DatabaseManager::new_test().unwrap();
+        blacklist(&db, &["class:keepassxc"]);
+        let memory = MemoryClipboard::new();
+        // No change marker, so every poll has to look at the contents
+        let clipboard = MultiClipboard(vec![&memory]);
+        let mut monitor = ClipboardMonitor::new();
+
+        memory.set_source_app(Some(app("keepassxc", "KeePassXC")));
+        memory.write_text("hunter2".to_string()).unwrap();
+        assert!(!monitor.poll(&clipboard, &db));
+        assert!(!monitor.poll(&clipboard, &db));
+        assert_eq!(memory.read_count(), 0);
+
+        memory.set_source_app(Some(app("firefox", "Firefox")));
+        assert!(!monitor.poll(&clipboard, &db));
+        assert!(db.get_clipboard_history().unwrap().is_empty());
+
+        memory.write_text("kept".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        assert_eq!(db.get_clipboard_history().unwrap().len(), 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_invalid_entries_in_a_stored_config_are_skipped() {
+        let db = DatabaseManager::new_test().unwrap();
+        // Stored without validation, like an imported or hand-edited config
+        db.update_config(&AppConfig {
+            exclusion_rules: ExclusionRules {
+                patterns: vec!["(unclosed".to_string(), "^secret".to_string()],
+                apps: vec!["*".to_string(), "process:keepassxc".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        // The valid entries still apply, with their own indices
+        let (filter, errors) = ExclusionFilter::compile(&db.get_exclusion_rules().unwrap());
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            filter.check_app(Some(&app("keepassxc", "KeePassXC"))),
+            Some(ExclusionReason::App(1))
+        );
+
+        clipboard.set_source_app(Some(app("keepassxc", "KeePassXC")));
+        clipboard.write_text("hunter2".to_string()).unwrap();
+        assert!(!monitor.poll(&clipboard, &db));
+
+        clipboard.set_source_app(Some(app("firefox", "Firefox")));
+        clipboard.write_text("secret token".to_string()).unwrap();
+        assert!(!monitor.poll(&clipboard, &db));
+        clipboard.write_text("kept".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        assert_eq!(db.get_clipboard_history().unwrap().len(), 1);
+    }
+}
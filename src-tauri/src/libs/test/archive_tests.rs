@@ -0,0 +1,462 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        archive::{read_archive, write_archive, ArchiveError},
+        constants::ARCHIVE_VERSION,
+        database::DatabaseManager,
+        test::fixtures::make_png,
+        types::{
+            AppConfig, CaptureDetails, ClipboardContent, ClipboardItem, ClipboardSnapshot,
+            HistoryQuery, ImportMode, ImportSummary, Selection, SnippetDraft, SourceApp,
+        },
+    };
+    use chrono::{Duration, Utc};
+    use serial_test::serial;
+    use std::io::Cursor;
+
+    fn export(db: &DatabaseManager) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        write_archive(db, &mut buffer).unwrap();
+        buffer
+    }
+
+    fn import(db: &DatabaseManager, archive: &[u8], mode: ImportMode) -> ImportSummary {
+        let archive = read_archive(Cursor::new(archive)).unwrap();
+        db.import_archive(&archive, mode).unwrap()
+    }
+
+    fn tag_names(db: &DatabaseManager, item: &ClipboardItem) -> Vec<String> {
+        let tags = db.list_tags().unwrap();
+        let mut names: Vec<String> = item
+            .tag_ids
+            .iter()
+            .filter_map(|id| tags.iter().find(|tag| tag.id == *id))
+            .map(|tag| tag.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    // Everything that should survive an export, without database ids
+    fn portable_history(
+        db: &DatabaseManager,
+    ) -> Vec<(ClipboardContent, String, String, u32, bool, Vec<String>)> {
+        db.get_clipboard_history()
+            .unwrap()
+            .into_iter()
+            .map(|item| {
+                let tags = tag_names(db, &item);
+                (
+                    item.content,
+                    item.timestamp,
+                    item.last_used,
+                    item.copy_count,
+                    item.pinned,
+                    tags,
+                )
+            })
+            .collect()
+    }
+
+    fn find(db: &DatabaseManager, content: &str) -> ClipboardItem {
+        db.get_clipboard_history()
+            .unwrap()
+            .into_iter()
+            .find(|item| item.content == ClipboardContent::text(content))
+            .expect("item is in the history")
+    }
+
+    #[test]
+    #[serial]
+    fn test_round_trip() {
+        let source = DatabaseManager::new_test().unwrap();
+        source.add_clipboard_item("plain").unwrap();
+        source.add_clipboard_item("plain").unwrap();
+        source
+            .add_snapshot(&ClipboardSnapshot {
+                text: Some("bold".to_string()),
+                html: Some("<b>bold</b>".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        source.add_image_item(&make_png(300, 200)).unwrap();
+        source
+            .add_snapshot(&ClipboardSnapshot {
+                files: Some(vec!["file:///tmp/a.txt".to_string()]),
+                ..Default::default()
+            })
+            .unwrap();
+        let work = source.create_tag("work").unwrap();
+        let plain = find(&source, "plain");
+        source.set_item_tag(plain.id, work.id, true).unwrap();
+        source.toggle_pin(plain.id).unwrap();
+
+        let target = DatabaseManager::new_test().unwrap();
+        let summary = import(&target, &export(&source), ImportMode::Replace);
+        assert_eq!(
+            summary,
+            ImportSummary {
+                imported: 4,
+                merged: 0,
+                trimmed: 0,
+                snippets: 0
+            }
+        );
+        assert_eq!(portable_history(&target), portable_history(&source));
+
+        // Extra formats and full images come along
+        let bold = find(&target, "bold");
+        let snapshot = target.get_item_snapshot(bold.id).unwrap().unwrap();
+        assert_eq!(snapshot.html.as_deref(), Some("<b>bold</b>"));
+        let image_id = target
+            .get_clipboard_history()
+            .unwrap()
+            .into_iter()
+            .find(|item| matches!(item.content, ClipboardContent::Image(_)))
+            .unwrap()
+            .id;
+        assert_eq!(
+            target.get_image_data(image_id).unwrap(),
+            Some(make_png(300, 200))
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_selection_and_source_app_round_trip() {
+        let source = DatabaseManager::new_test().unwrap();
+        let source_app = SourceApp {
+            process: Some("firefox".to_string()),
+            class: Some("Navigator".to_string()),
+            title: Some("Clipray".to_string()),
+        };
+        source
+            .add_captured_snapshot(
+                &ClipboardSnapshot {
+                    text: Some("selected".to_string()),
+                    ..Default::default()
+                },
+                &CaptureDetails {
+                    selection: Selection::Primary,
+                    source_app: Some(source_app.clone()),
+                    expires_at: None,
+                },
+            )
+            .unwrap();
+        source.add_clipboard_item("copied").unwrap();
+
+        let target = DatabaseManager::new_test().unwrap();
+        import(&target, &export(&source), ImportMode::Replace);
+
+        let selected = find(&target, "selected");
+        assert_eq!(selected.selection, Selection::Primary);
+        assert_eq!(selected.source_app, Some(source_app));
+        let copied = find(&target, "copied");
+        assert_eq!(copied.selection, Selection::Clipboard);
+        assert_eq!(copied.source_app, None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_snippets_round_trip() {
+        let source = DatabaseManager::new_test().unwrap();
+        let work = source.create_snippet_folder("Work").unwrap();
+        source.create_snippet_folder("Empty").unwrap();
+        source
+            .create_snippet(&SnippetDraft {
+                name: "Signature".to_string(),
+                abbreviation: Some("sig".to_string()),
+                content: "Regards, {clipboard}".to_string(),
+                folder_id: Some(work.id),
+            })
+            .unwrap();
+        source
+            .create_snippet(&SnippetDraft {
+                name: "Address".to_string(),
+                content: "1 Main Street".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        // Without database ids, which differ between the two
+        let portable = |db: &DatabaseManager| {
+            let folders = db.list_snippet_folders().unwrap();
+            let mut snippets: Vec<_> = db
+                .list_snippets()
+                .unwrap()
+                .into_iter()
+                .map(|snippet| {
+                    let folder = folders
+                        .iter()
+                        .find(|folder| Some(folder.id) == snippet.folder_id)
+                        .map(|folder| folder.name.clone());
+                    (
+                        snippet.name,
+                        snippet.abbreviation,
+                        snippet.content,
+                        folder,
+                        snippet.created_at,
+                    )
+                })
+                .collect();
+            snippets.sort();
+            let mut names: Vec<String> = folders.into_iter().map(|folder| folder.name).collect();
+            names.sort();
+            (names, snippets)
+        };
+
+        let target = DatabaseManager::new_test().unwrap();
+        let summary = import(&target, &export(&source), ImportMode::Merge);
+        assert_eq!(summary.snippets, 2);
+        assert_eq!(portable(&target), portable(&source));
+
+        // Merging again adds nothing
+        let summary = import(&target, &export(&source), ImportMode::Merge);
+        assert_eq!(summary.snippets, 0);
+        assert_eq!(portable(&target), portable(&source));
+    }
+
+    #[test]
+    #[serial]
+    fn test_taken_abbreviation_is_dropped_on_merge() {
+        let source = DatabaseManager::new_test().unwrap();
+        source
+            .create_snippet(&SnippetDraft {
+                name: "Imported".to_string(),
+                abbreviation: Some("sig".to_string()),
+                content: "from the archive".to_string(),
+                folder_id: None,
+            })
+            .unwrap();
+        let target = DatabaseManager::new_test().unwrap();
+        let existing = target
+            .create_snippet(&SnippetDraft {
+                name: "Existing".to_string(),
+                abbreviation: Some("SIG".to_string()),
+                content: "already here".to_string(),
+                folder_id: None,
+            })
+            .unwrap();
+
+        import(&target, &export(&source), ImportMode::Merge);
+
+        let snippets = target.list_snippets().unwrap();
+        assert_eq!(snippets.len(), 2);
+        let imported = snippets.iter().find(|s| s.name == "Imported").unwrap();
+        assert_eq!(imported.abbreviation, None);
+        assert_eq!(
+            target.find_snippet_by_abbreviation("sig").unwrap(),
+            Some(existing)
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_archive_layout() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_image_item(&make_png(8, 8)).unwrap();
+        db.add_snapshot(&ClipboardSnapshot {
+            text: Some("with image".to_string()),
+            image: Some(make_png(8, 8)),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let archive = String::from_utf8(export(&db)).unwrap();
+        let records: Vec<serde_json::Value> = archive
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let types: Vec<&str> = records
+            .iter()
+            .map(|record| record["type"].as_str().unwrap())
+            .collect();
+        // The shared image is written once, before its items
+        assert_eq!(types, vec!["header", "config", "image", "item", "item"]);
+        assert_eq!(records[0]["format"], "clipray-export");
+        assert_eq!(records[0]["version"], ARCHIVE_VERSION);
+        assert_eq!(records[3]["image"], records[2]["key"]);
+        assert_eq!(records[4]["image"], records[2]["key"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_is_exported() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.update_config(&AppConfig {
+            max_history_count: 7,
+            hotkey: "Alt+V".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let archive = read_archive(Cursor::new(export(&db))).unwrap();
+        let config = archive.config.expect("config is in the archive");
+        assert_eq!(config.max_history_count, 7);
+        assert_eq!(config.hotkey, "Alt+V");
+    }
+
+    #[test]
+    #[serial]
+    fn test_merge_deduplicates() {
+        let source = DatabaseManager::new_test().unwrap();
+        source.add_clipboard_item("shared").unwrap();
+        source.add_clipboard_item("shared").unwrap();
+        source.add_clipboard_item("shared").unwrap();
+        source.toggle_pin(find(&source, "shared").id).unwrap();
+        source.add_clipboard_item("only in archive").unwrap();
+        let archive = export(&source);
+
+        let target = DatabaseManager::new_test().unwrap();
+        target.add_clipboard_item("shared").unwrap();
+        target.add_clipboard_item("only here").unwrap();
+        let before = find(&target, "shared");
+
+        let summary = import(&target, &archive, ImportMode::Merge);
+        assert_eq!(summary.imported, 1);
+        assert_eq!(summary.merged, 1);
+        assert_eq!(target.get_clipboard_history().unwrap().len(), 3);
+
+        let shared = find(&target, "shared");
+        assert_eq!(shared.id, before.id, "the existing item is kept");
+        assert!(shared.pinned);
+        assert_eq!(shared.copy_count, 3);
+        assert_eq!(shared.timestamp, find(&source, "shared").timestamp);
+        assert_eq!(shared.last_used, before.last_used);
+
+        // Importing the same archive again changes nothing
+        let summary = import(&target, &archive, ImportMode::Merge);
+        assert_eq!(summary.imported, 0);
+        assert_eq!(summary.merged, 2);
+        assert_eq!(target.get_clipboard_history().unwrap().len(), 3);
+    }
+
+    #[test]
+    #[serial]
+    fn test_replace_deletes_history() {
+        let source = DatabaseManager::new_test().unwrap();
+        source.add_clipboard_item("imported").unwrap();
+        let target = DatabaseManager::new_test().unwrap();
+        target.add_clipboard_item("pinned before").unwrap();
+        target
+            .toggle_pin(find(&target, "pinned before").id)
+            .unwrap();
+
+        let unused = target.create_tag("unused").unwrap();
+        target
+            .set_item_tag(find(&target, "pinned before").id, unused.id, true)
+            .unwrap();
+        target.create_snippet_folder("Old folder").unwrap();
+        target
+            .create_snippet(&SnippetDraft {
+                name: "Old".to_string(),
+                content: "old snippet".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        import(&target, &export(&source), ImportMode::Replace);
+
+        let history = target.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].content, ClipboardContent::text("imported"));
+        // No tag is left behind without its items, and snippets are replaced too
+        assert!(target.list_tags().unwrap().is_empty());
+        assert!(target.list_snippets().unwrap().is_empty());
+        assert!(target.list_snippet_folders().unwrap().is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_sensitive_items_are_not_exported() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item("kept").unwrap();
+        db.add_clipboard_item("one-time code").unwrap();
+        let id = find(&db, "one-time code").id;
+        db.set_item_expiry(id, Some(Utc::now() + Duration::minutes(1)))
+            .unwrap();
+
+        let archive = read_archive(Cursor::new(export(&db))).unwrap();
+        assert_eq!(archive.items.len(), 1);
+        assert_eq!(archive.items[0].content, "kept");
+    }
+
+    #[test]
+    #[serial]
+    fn test_history_limit_applies_to_imports() {
+        let source = DatabaseManager::new_test().unwrap();
+        for i in 0..5 {
+            source.add_clipboard_item(&i.to_string()).unwrap();
+        }
+        source.toggle_pin(find(&source, "0").id).unwrap();
+        let target = DatabaseManager::new_test().unwrap();
+        target
+            .update_config(&AppConfig {
+                max_history_count: 2,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let summary = import(&target, &export(&source), ImportMode::Merge);
+        assert_eq!(summary.imported, 5);
+        assert_eq!(summary.trimmed, 2);
+        let page = target
+            .query_clipboard_history(&HistoryQuery::default())
+            .unwrap();
+        let texts: Vec<&str> = page
+            .items
+            .iter()
+            .filter_map(|item| item.content.as_text())
+            .collect();
+        assert_eq!(texts, vec!["4", "3", "0"]);
+    }
+
+    #[test]
+    fn test_invalid_archives_are_rejected() {
+        let read = |text: &str| read_archive(Cursor::new(text.as_bytes().to_vec()));
+
+        assert!(matches!(read(""), Err(ArchiveError::NotAnArchive)));
+        assert!(matches!(
+            read("{\"content\": \"not an export\"}\n"),
+            Err(ArchiveError::NotAnArchive)
+        ));
+
+        let newer = format!(
+            "{{\"type\":\"header\",\"format\":\"clipray-export\",\"version\":{},\"app_version\":\"9.0.0\",\"exported_at\":\"2030-01-01T00:00:00Z\"}}\n",
+            ARCHIVE_VERSION + 1
+        );
+        assert!(matches!(
+            read(&newer),
+            Err(ArchiveError::VersionTooNew { .. })
+        ));
+
+        let header = "{\"type\":\"header\",\"format\":\"clipray-export\",\"version\":1,\"app_version\":\"0.1.8\",\"exported_at\":\"2024-01-01T00:00:00Z\"}\n";
+        let missing_image = format!(
+            "{}{{\"type\":\"item\",\"content_type\":\"image\",\"image\":\"abc\",\"timestamp\":\"2024-01-01T00:00:00Z\",\"last_used\":\"2024-01-01T00:00:00Z\"}}\n",
+            header
+        );
+        let error = read(&missing_image).unwrap_err();
+        assert!(
+            matches!(error, ArchiveError::InvalidRecord { line: 2, .. }),
+            "{}",
+            error
+        );
+        assert!(matches!(
+            read(&format!("{}not json\n", header)),
+            Err(ArchiveError::InvalidRecord { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_records_are_skipped() {
+        let archive = "{\"type\":\"header\",\"format\":\"clipray-export\",\"version\":1,\"app_version\":\"0.1.8\",\"exported_at\":\"2024-01-01T00:00:00Z\"}\n\
+            {\"type\":\"something-new\",\"value\":1}\n\
+            \n\
+            {\"type\":\"item\",\"content_type\":\"text\",\"content\":\"hello\",\"timestamp\":\"2024-01-01T00:00:00Z\",\"last_used\":\"2024-01-02T00:00:00+02:00\"}\n";
+        let archive = read_archive(Cursor::new(archive.as_bytes().to_vec())).unwrap();
+        assert!(archive.config.is_none());
+        assert_eq!(archive.items.len(), 1);
+        assert_eq!(archive.items[0].copy_count, 1, "defaults fill in");
+        assert!(!archive.items[0].pinned);
+    }
+}
@@ -0,0 +1,296 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        backup::{
+            backup_dir, backup_time, create_backup, is_backup_due, list_backups, open_or_recover,
+        },
+        constants::BACKUP_KEEP_COUNT,
+        database::DatabaseManager,
+        encryption::{change_encryption, open_database, open_database_file, KeySource},
+        migrations::MigrationError,
+        types::ClipboardContent,
+    };
+    use chrono::{DateTime, Duration, Utc};
+    use serial_test::serial;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn start() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-05-01T08:00:00Z")
+            .unwrap()
+            .to_utc()
+    }
+
+    fn texts(db: &DatabaseManager) -> Vec<String> {
+        db.get_clipboard_history()
+            .unwrap()
+            .into_iter()
+            .filter_map(|item| item.content.as_text().map(String::from))
+            .collect()
+    }
+
+    // Overwrite everything after the file header, so the schema can no longer be read
+    fn damage(db_path: &Path) {
+        let mut bytes = fs::read(db_path).unwrap();
+        for byte in bytes.iter_mut().skip(100) {
+            *byte = 0xAB;
+        }
+        fs::write(db_path, bytes).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_backups_are_rotated() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = open_database(&db_path, None).unwrap();
+        db.add_clipboard_item("saved").unwrap();
+
+        for hour in 0..7 {
+            create_backup(&db, start() + Duration::hours(hour)).unwrap();
+        }
+
+        let backups = list_backups(&db_path).unwrap();
+        assert_eq!(backups.len(), BACKUP_KEEP_COUNT);
+        assert_eq!(backup_time(&backups[0]), Some(start() + Duration::hours(6)));
+        assert_eq!(backup_time(&backups[4]), Some(start() + Duration::hours(2)));
+        assert_eq!(
+            backups[0].file_name().unwrap().to_str().unwrap(),
+            "clipray-20240501-140000.db"
+        );
+
+        let backup = open_database_file(&backups[0], &db_path, None).unwrap();
+        backup.check_integrity().unwrap();
+        assert_eq!(texts(&backup), vec!["saved"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_failed_rotation_keeps_the_new_backup() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = open_database(&db_path, None).unwrap();
+        // A directory with a backup's name cannot be deleted as a file
+        let stale = backup_dir(&db_path).join("clipray-20240101-000000.db");
+        fs::create_dir_all(&stale).unwrap();
+
+        for hour in 0..BACKUP_KEEP_COUNT as i64 {
+            create_backup(&db, start() + Duration::hours(hour)).unwrap();
+        }
+
+        let backups = list_backups(&db_path).unwrap();
+        assert_eq!(backups.len(), BACKUP_KEEP_COUNT + 1);
+        assert_eq!(backups.last(), Some(&stale));
+        assert_eq!(
+            backup_time(&backups[0]),
+            Some(start() + Duration::hours(BACKUP_KEEP_COUNT as i64 - 1))
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_backup_is_due() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = open_database(&db_path, None).unwrap();
+        assert!(is_backup_due(&db_path, start()));
+
+        create_backup(&db, start()).unwrap();
+        assert!(!is_backup_due(&db_path, start() + Duration::hours(1)));
+        assert!(is_backup_due(&db_path, start() + Duration::hours(24)));
+    }
+
+    #[test]
+    #[serial]
+    fn test_in_memory_databases_are_not_backed_up() {
+        let db = DatabaseManager::new_test().unwrap();
+        assert!(create_backup(&db, start()).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_backups_keep_the_encryption() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = open_database(&db_path, None).unwrap();
+        db.add_clipboard_item("very private clipboard text")
+            .unwrap();
+        create_backup(&db, start()).unwrap();
+        change_encryption(&db, KeySource::Passphrase("secret".to_string())).unwrap();
+
+        // The plaintext backup is deleted with the key change
+        assert!(list_backups(&db_path).unwrap().is_empty());
+        let backup = create_backup(&db, start() + Duration::hours(1)).unwrap();
+        assert_eq!(list_backups(&db_path).unwrap(), vec![backup.clone()]);
+        let bytes = fs::read(&backup).unwrap();
+        assert!(!bytes.windows(12).any(|window| window == b"very private"));
+
+        assert!(matches!(
+            open_database_file(&backup, &db_path, None),
+            Err(MigrationError::Locked)
+        ));
+        let restored = open_database_file(&backup, &db_path, Some("secret")).unwrap();
+        assert_eq!(texts(&restored), vec!["very private clipboard text"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_backups_that_cannot_be_deleted_fail_the_key_change() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = open_database(&db_path, None).unwrap();
+        db.add_clipboard_item("kept").unwrap();
+        // A directory with a backup's name cannot be deleted as a file
+        fs::create_dir_all(backup_dir(&db_path).join("clipray-20240101-000000.db")).unwrap();
+
+        assert!(matches!(
+            change_encryption(&db, KeySource::Passphrase("secret".to_string())),
+            Err(MigrationError::Io(_))
+        ));
+        // The key change itself is done and the database keeps working
+        assert_eq!(texts(&db), vec!["kept"]);
+        drop(db);
+        assert!(open_database(&db_path, Some("secret")).is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn test_key_file_backups() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = open_database(&db_path, None).unwrap();
+        change_encryption(&db, KeySource::KeyFile).unwrap();
+        db.add_clipboard_item("kept").unwrap();
+
+        let backup = create_backup(&db, start()).unwrap();
+        let restored = open_database_file(&backup, &db_path, None).unwrap();
+        assert_eq!(texts(&restored), vec!["kept"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_healthy_database_opens_as_usual() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        open_database(&db_path, None)
+            .unwrap()
+            .add_clipboard_item("hello")
+            .unwrap();
+
+        let (db, notice) = open_or_recover(&db_path, None).unwrap();
+        assert_eq!(notice, None);
+        assert_eq!(texts(&db), vec!["hello"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_damaged_database_is_restored_from_backup() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = open_database(&db_path, None).unwrap();
+        db.add_clipboard_item("before backup").unwrap();
+        create_backup(&db, start()).unwrap();
+        db.add_clipboard_item("after backup").unwrap();
+        drop(db);
+        damage(&db_path);
+
+        let (db, notice) = open_or_recover(&db_path, None).unwrap();
+        let notice = notice.expect("the user is told");
+        assert_eq!(notice.backup_time, Some(start().to_rfc3339()));
+        assert!(Path::new(&notice.damaged_copy).exists());
+        assert_ne!(Path::new(&notice.damaged_copy), db_path);
+        assert_eq!(texts(&db), vec!["before backup"]);
+
+        // The backup itself is left in place
+        assert_eq!(list_backups(&db_path).unwrap().len(), 1);
+        assert!(!db_path.with_extension("db.restore").exists());
+    }
+
+    #[test]
+    #[serial]
+    fn test_newest_usable_backup_is_restored() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = open_database(&db_path, None).unwrap();
+        db.add_clipboard_item("older").unwrap();
+        create_backup(&db, start()).unwrap();
+        db.add_clipboard_item("newer").unwrap();
+        let newest = create_backup(&db, start() + Duration::hours(1)).unwrap();
+        drop(db);
+        damage(&newest);
+        damage(&db_path);
+
+        let (db, notice) = open_or_recover(&db_path, None).unwrap();
+        assert_eq!(notice.unwrap().backup_time, Some(start().to_rfc3339()));
+        assert_eq!(texts(&db), vec!["older"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_damaged_database_without_backup_starts_over() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        open_database(&db_path, None)
+            .unwrap()
+            .add_clipboard_item("lost")
+            .unwrap();
+        damage(&db_path);
+
+        let (db, notice) = open_or_recover(&db_path, None).unwrap();
+        let notice = notice.unwrap();
+        assert_eq!(notice.backup_time, None);
+        assert!(Path::new(&notice.damaged_copy).exists());
+        assert!(db.get_clipboard_history().unwrap().is_empty());
+        db.add_clipboard_item("new").unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_unreadable_file_without_backup_is_left_alone() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = open_database(&db_path, None).unwrap();
+        change_encryption(&db, KeySource::Passphrase("secret".to_string())).unwrap();
+        db.add_clipboard_item("encrypted").unwrap();
+        drop(db);
+
+        // Without the passphrase an encrypted database cannot be told from a damaged one
+        assert!(matches!(
+            open_or_recover(&db_path, None),
+            Err(MigrationError::Locked)
+        ));
+        let (db, notice) = open_or_recover(&db_path, Some("secret")).unwrap();
+        assert_eq!(notice, None);
+        assert_eq!(
+            db.get_clipboard_history().unwrap()[0].content,
+            ClipboardContent::text("encrypted")
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_locked_database_is_not_replaced_by_a_plaintext_backup() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = open_database(&db_path, None).unwrap();
+        db.add_clipboard_item("old plaintext").unwrap();
+        let backup = create_backup(&db, start()).unwrap();
+        let backup_bytes = fs::read(&backup).unwrap();
+        change_encryption(&db, KeySource::Passphrase("secret".to_string())).unwrap();
+        db.add_clipboard_item("encrypted").unwrap();
+        drop(db);
+        // Left over, for example by a key change from an older version
+        fs::write(&backup, &backup_bytes).unwrap();
+        let database_bytes = fs::read(&db_path).unwrap();
+
+        // The backup opens without a key, but is never restored over the locked database
+        assert!(matches!(
+            open_or_recover(&db_path, None),
+            Err(MigrationError::Locked)
+        ));
+        assert_eq!(fs::read(&db_path).unwrap(), database_bytes);
+        assert_eq!(fs::read(&backup).unwrap(), backup_bytes);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use crate::libs::{database::DatabaseManager, types::*};
+    use crate::libs::{database::DatabaseManager, test::fixtures::make_png, types::*};
     use serial_test::serial;
 
     #[tokio::test]
@@ -9,9 +9,8 @@ mod tests {
         let db = DatabaseManager::new_test().unwrap();
 
         // Test adding items
-        db.add_clipboard_item("integration test 1", "text").unwrap();
-        db.add_clipboard_item("integration test 2", "image")
-            .unwrap();
+        db.add_clipboard_item("integration test 1").unwrap();
+        db.add_image_item(&make_png(2, 2)).unwrap();
 
         let history = db.get_clipboard_history().unwrap();
         assert_eq!(history.len(), 2);
@@ -51,6 +50,7 @@ mod tests {
                 preset: ThemePreset::DeepPurple,
             },
             autostart: false,
+            ..Default::default()
         };
 
         db.update_config(&new_config).unwrap();
@@ -74,22 +74,22 @@ mod tests {
                 preset: ThemePreset::Default,
             },
             autostart: false,
+            ..Default::default()
         };
         db.update_config(&config).unwrap();
 
         // Add 5 items
         for i in 1..=5 {
-            db.add_clipboard_item(&format!("item {}", i), "text")
-                .unwrap();
+            db.add_clipboard_item(&format!("item {}", i)).unwrap();
         }
 
         let history = db.get_clipboard_history().unwrap();
         assert_eq!(history.len(), 3);
 
         // Check that the latest items are kept
-        assert_eq!(history[0].content, "item 5");
-        assert_eq!(history[1].content, "item 4");
-        assert_eq!(history[2].content, "item 3");
+        assert_eq!(history[0].content, ClipboardContent::text("item 5"));
+        assert_eq!(history[1].content, ClipboardContent::text("item 4"));
+        assert_eq!(history[2].content, ClipboardContent::text("item 3"));
     }
 
     #[tokio::test]
@@ -105,18 +105,19 @@ mod tests {
                 preset: ThemePreset::Default,
             },
             autostart: false,
+            ..Default::default()
         };
         db.update_config(&config).unwrap();
 
         // Add and pin first item
-        db.add_clipboard_item("pinned item", "text").unwrap();
+        db.add_clipboard_item("pinned item").unwrap();
         let history = db.get_clipboard_history().unwrap();
         let pinned_id = history[0].id;
         db.toggle_pin(pinned_id).unwrap();
 
         // Add more items
-        db.add_clipboard_item("item 2", "text").unwrap();
-        db.add_clipboard_item("item 3", "text").unwrap();
+        db.add_clipboard_item("item 2").unwrap();
+        db.add_clipboard_item("item 3").unwrap();
 
         let final_history = db.get_clipboard_history().unwrap();
 
@@ -126,7 +127,10 @@ mod tests {
 
         // Pinned item should be first (due to ORDER BY pinned DESC)
         assert!(final_history[0].pinned);
-        assert_eq!(final_history[0].content, "pinned item");
+        assert_eq!(
+            final_history[0].content,
+            ClipboardContent::text("pinned item")
+        );
     }
 
     #[tokio::test]
@@ -135,16 +139,16 @@ mod tests {
         let db = DatabaseManager::new_test().unwrap();
 
         // Add same content multiple times
-        db.add_clipboard_item("duplicate", "text").unwrap();
-        db.add_clipboard_item("other content", "text").unwrap();
-        db.add_clipboard_item("duplicate", "text").unwrap(); // Should replace the first one
+        db.add_clipboard_item("duplicate").unwrap();
+        db.add_clipboard_item("other content").unwrap();
+        db.add_clipboard_item("duplicate").unwrap(); // Should replace the first one
 
         let history = db.get_clipboard_history().unwrap();
         assert_eq!(history.len(), 2);
 
         // "duplicate" should be at the top as it was added last
-        assert_eq!(history[0].content, "duplicate");
-        assert_eq!(history[1].content, "other content");
+        assert_eq!(history[0].content, ClipboardContent::text("duplicate"));
+        assert_eq!(history[1].content, ClipboardContent::text("other content"));
     }
 
     #[tokio::test]
@@ -152,9 +156,9 @@ mod tests {
     async fn test_mixed_content_types_handling() {
         let db = DatabaseManager::new_test().unwrap();
 
-        db.add_clipboard_item("text content", "text").unwrap();
-        db.add_clipboard_item("image data", "image").unwrap();
-        db.add_clipboard_item("another text", "text").unwrap();
+        db.add_clipboard_item("text content").unwrap();
+        db.add_image_item(&make_png(2, 2)).unwrap();
+        db.add_clipboard_item("another text").unwrap();
 
         let history = db.get_clipboard_history().unwrap();
         assert_eq!(history.len(), 3);
@@ -162,11 +166,11 @@ mod tests {
         // Check content types
         let text_items: Vec<_> = history
             .iter()
-            .filter(|item| item.content_type == "text")
+            .filter(|item| item.content.content_type() == ContentType::Text)
             .collect();
         let image_items: Vec<_> = history
             .iter()
-            .filter(|item| item.content_type == "image")
+            .filter(|item| item.content.content_type() == ContentType::Image)
             .collect();
 
         assert_eq!(text_items.len(), 2);
@@ -194,6 +198,7 @@ mod tests {
                     preset: theme.clone(),
                 },
                 autostart: false,
+                ..Default::default()
             };
 
             db.update_config(&config).unwrap();
@@ -226,12 +231,12 @@ mod tests {
 
         // Test with large content
         let large_content = "a".repeat(10000);
-        db.add_clipboard_item(&large_content, "text").unwrap();
+        db.add_clipboard_item(&large_content).unwrap();
 
         let history = db.get_clipboard_history().unwrap();
         assert_eq!(history.len(), 1);
-        assert_eq!(history[0].content.len(), 10000);
-        assert_eq!(history[0].content, large_content);
+        assert_eq!(history[0].content.as_text().unwrap().len(), 10000);
+        assert_eq!(history[0].content, ClipboardContent::text(large_content));
     }
 
     #[tokio::test]
@@ -241,11 +246,11 @@ mod tests {
 
         // Test with special characters
         let special_content = "Hello 世界! 🌍 @#$%^&*()";
-        db.add_clipboard_item(special_content, "text").unwrap();
+        db.add_clipboard_item(special_content).unwrap();
 
         let history = db.get_clipboard_history().unwrap();
         assert_eq!(history.len(), 1);
-        assert_eq!(history[0].content, special_content);
+        assert_eq!(history[0].content, ClipboardContent::text(special_content));
     }
 
     #[tokio::test]
@@ -254,8 +259,8 @@ mod tests {
         let db = DatabaseManager::new_test().unwrap();
 
         // Add some initial data
-        db.add_clipboard_item("item 1", "text").unwrap();
-        db.add_clipboard_item("item 2", "text").unwrap();
+        db.add_clipboard_item("item 1").unwrap();
+        db.add_clipboard_item("item 2").unwrap();
 
         let history = db.get_clipboard_history().unwrap();
         let item1_id = history[1].id;
@@ -263,7 +268,7 @@ mod tests {
 
         // Perform multiple operations
         db.toggle_pin(item1_id).unwrap();
-        db.add_clipboard_item("item 3", "text").unwrap();
+        db.add_clipboard_item("item 3").unwrap();
         db.toggle_pin(item2_id).unwrap();
 
         let final_history = db.get_clipboard_history().unwrap();
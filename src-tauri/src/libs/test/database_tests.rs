@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests {
-    use crate::libs::{constants::DEFAULT_HOTKEY, database::DatabaseManager, types::*};
+    use crate::libs::{
+        constants::DEFAULT_HOTKEY, database::DatabaseManager, test::fixtures::make_png, types::*,
+    };
     use serial_test::serial;
 
     #[test]
@@ -21,13 +23,13 @@ mod tests {
     fn test_add_clipboard_item() {
         let db = DatabaseManager::new_test().unwrap();
 
-        let result = db.add_clipboard_item("test content", "text");
+        let result = db.add_clipboard_item("test content");
         assert!(result.is_ok());
 
         let history = db.get_clipboard_history().unwrap();
         assert_eq!(history.len(), 1);
-        assert_eq!(history[0].content, "test content");
-        assert_eq!(history[0].content_type, "text");
+        assert_eq!(history[0].content, ClipboardContent::text("test content"));
+        assert_eq!(history[0].content.content_type(), ContentType::Text);
         assert!(!history[0].pinned);
     }
 
@@ -37,12 +39,15 @@ mod tests {
         let db = DatabaseManager::new_test().unwrap();
 
         // Add same content twice
-        db.add_clipboard_item("duplicate content", "text").unwrap();
-        db.add_clipboard_item("duplicate content", "text").unwrap();
+        db.add_clipboard_item("duplicate content").unwrap();
+        db.add_clipboard_item("duplicate content").unwrap();
 
         let history = db.get_clipboard_history().unwrap();
         assert_eq!(history.len(), 1); // Should only have one item
-        assert_eq!(history[0].content, "duplicate content");
+        assert_eq!(
+            history[0].content,
+            ClipboardContent::text("duplicate content")
+        );
     }
 
     #[test]
@@ -56,13 +61,14 @@ mod tests {
 
         // Check if pinned items come first
         assert!(history[0].pinned);
-        assert_eq!(history[0].content, "test content 2");
+        assert_eq!(history[0].content, ClipboardContent::text("test content 2"));
 
         // Check ordering by timestamp for non-pinned items
         assert!(!history[1].pinned);
-        assert_eq!(history[1].content, "test image data"); // Latest non-pinned
+        assert_eq!(history[1].content.content_type(), ContentType::Image); // Latest non-pinned
         assert!(!history[2].pinned);
-        assert_eq!(history[2].content, "test content 1"); // Oldest non-pinned
+        assert_eq!(history[2].content, ClipboardContent::text("test content 1"));
+        // Oldest non-pinned
     }
 
     #[test]
@@ -147,18 +153,19 @@ mod tests {
                 preset: ThemePreset::Default,
             },
             autostart: false,
+            ..Default::default()
         };
         db.update_config(&config).unwrap();
 
         // Add 3 items
-        db.add_clipboard_item("item1", "text").unwrap();
-        db.add_clipboard_item("item2", "text").unwrap();
-        db.add_clipboard_item("item3", "text").unwrap();
+        db.add_clipboard_item("item1").unwrap();
+        db.add_clipboard_item("item2").unwrap();
+        db.add_clipboard_item("item3").unwrap();
 
         let history = db.get_clipboard_history().unwrap();
         assert_eq!(history.len(), 2);
-        assert_eq!(history[0].content, "item3"); // Latest first
-        assert_eq!(history[1].content, "item2");
+        assert_eq!(history[0].content, ClipboardContent::text("item3")); // Latest first
+        assert_eq!(history[1].content, ClipboardContent::text("item2"));
     }
 
     #[test]
@@ -174,17 +181,18 @@ mod tests {
                 preset: ThemePreset::Default,
             },
             autostart: false,
+            ..Default::default()
         };
         db.update_config(&config).unwrap();
 
         // Add item and pin it
-        db.add_clipboard_item("pinned item", "text").unwrap();
+        db.add_clipboard_item("pinned item").unwrap();
         let history = db.get_clipboard_history().unwrap();
         let pinned_id = history[0].id;
         db.toggle_pin(pinned_id).unwrap();
 
         // Add new item
-        db.add_clipboard_item("new item", "text").unwrap();
+        db.add_clipboard_item("new item").unwrap();
 
         let final_history = db.get_clipboard_history().unwrap();
         // The get_clipboard_history method applies the limit, so it will only return 1 item
@@ -193,7 +201,10 @@ mod tests {
 
         // Check that the pinned item is preserved (it should be returned first due to ORDER BY pinned DESC)
         assert!(final_history[0].pinned);
-        assert_eq!(final_history[0].content, "pinned item");
+        assert_eq!(
+            final_history[0].content,
+            ClipboardContent::text("pinned item")
+        );
 
         // Verify that both items exist in the database by checking without limit
         let conn = db.get_connection().lock().unwrap();
@@ -223,6 +234,7 @@ mod tests {
                 preset: ThemePreset::DeepPurple,
             },
             autostart: false,
+            ..Default::default()
         };
 
         let result = db.update_config(&new_config);
@@ -251,6 +263,7 @@ mod tests {
                 preset: ThemePreset::Default,
             },
             autostart: false,
+            ..Default::default()
         };
         db.update_config(&config).unwrap();
 
@@ -274,6 +287,7 @@ mod tests {
                 preset: ThemePreset::Default,
             },
             autostart: false,
+            ..Default::default()
         };
         db.update_config(&config).unwrap();
 
@@ -297,6 +311,7 @@ mod tests {
                 preset: ThemePreset::MidnightBlue,
             },
             autostart: false,
+            ..Default::default()
         };
         db.update_config(&config).unwrap();
 
@@ -309,23 +324,24 @@ mod tests {
     fn test_different_content_types() {
         let db = DatabaseManager::new_test().unwrap();
 
-        db.add_clipboard_item("text content", "text").unwrap();
-        db.add_clipboard_item("image data", "image").unwrap();
+        db.add_clipboard_item("text content").unwrap();
+        db.add_image_item(&make_png(3, 2)).unwrap();
 
         let history = db.get_clipboard_history().unwrap();
         assert_eq!(history.len(), 2);
 
         let text_item = history
             .iter()
-            .find(|item| item.content_type == "text")
+            .find(|item| item.content.content_type() == ContentType::Text)
             .unwrap();
         let image_item = history
             .iter()
-            .find(|item| item.content_type == "image")
+            .find(|item| item.content.content_type() == ContentType::Image)
             .unwrap();
 
-        assert_eq!(text_item.content, "text content");
-        assert_eq!(image_item.content, "image data");
+        assert_eq!(text_item.content, ClipboardContent::text("text content"));
+        let image = image_item.content.as_image().unwrap();
+        assert_eq!((image.width, image.height), (3, 2));
     }
 
     #[test]
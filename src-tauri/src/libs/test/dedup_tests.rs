@@ -0,0 +1,168 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        database::DatabaseManager,
+        hash::content_hash,
+        test::fixtures::make_png,
+        types::{ClipboardContent, ClipboardSnapshot},
+    };
+    use rusqlite::Connection;
+    use serial_test::serial;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        assert_eq!(
+            content_hash(b"hello"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_ne!(content_hash(b"hello"), content_hash(b"hello "));
+    }
+
+    #[test]
+    #[serial]
+    fn test_recopy_keeps_id_and_counts_usage() {
+        let db = DatabaseManager::new_test().unwrap();
+
+        db.add_clipboard_item("repeated").unwrap();
+        let first = db.get_clipboard_history().unwrap()[0].clone();
+
+        db.add_clipboard_item("repeated").unwrap();
+        db.add_clipboard_item("repeated").unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].id, first.id);
+        assert_eq!(history[0].copy_count, 3);
+        assert_eq!(history[0].timestamp, first.timestamp);
+        assert!(history[0].last_used > first.last_used);
+    }
+
+    #[test]
+    #[serial]
+    fn test_recopy_moves_item_to_top() {
+        let db = DatabaseManager::new_test().unwrap();
+
+        db.add_clipboard_item("older").unwrap();
+        db.add_clipboard_item("newer").unwrap();
+        db.add_clipboard_item("older").unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].content, ClipboardContent::text("older"));
+        assert_eq!(history[1].content, ClipboardContent::text("newer"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_recopy_keeps_pin() {
+        let db = DatabaseManager::new_test().unwrap();
+
+        db.add_clipboard_item("pinned text").unwrap();
+        let id = db.get_clipboard_history().unwrap()[0].id;
+        db.toggle_pin(id).unwrap();
+
+        db.add_clipboard_item("pinned text").unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].id, id);
+        assert!(history[0].pinned);
+        assert_eq!(history[0].copy_count, 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_same_content_different_type_is_separate() {
+        let db = DatabaseManager::new_test().unwrap();
+
+        db.add_clipboard_item("same").unwrap();
+        db.add_snapshot(&ClipboardSnapshot {
+            html: Some("same".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(db.get_clipboard_history().unwrap().len(), 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_image_recopy_is_deduplicated() {
+        let db = DatabaseManager::new_test().unwrap();
+        let png = make_png(12, 8);
+
+        db.add_image_item(&png).unwrap();
+        db.add_image_item(&png).unwrap();
+        db.add_image_item(&make_png(8, 12)).unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 2);
+        let repeated = history
+            .iter()
+            .find(|item| item.content.as_image().unwrap().width == 12)
+            .unwrap();
+        assert_eq!(repeated.copy_count, 2);
+        assert_eq!(db.get_image_data(repeated.id).unwrap(), Some(png));
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_most_used_items() {
+        let db = DatabaseManager::new_test().unwrap();
+
+        for _ in 0..3 {
+            db.add_clipboard_item("frequent").unwrap();
+        }
+        for _ in 0..2 {
+            db.add_clipboard_item("occasional").unwrap();
+        }
+        db.add_clipboard_item("once").unwrap();
+
+        let items = db.get_most_used_items(10).unwrap();
+        let contents: Vec<&str> = items
+            .iter()
+            .map(|item| item.content.as_text().unwrap())
+            .collect();
+        assert_eq!(contents, vec!["frequent", "occasional"]);
+
+        assert_eq!(db.get_most_used_items(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_migration_backfills_hash_and_last_used() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE clipboard_history (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    content TEXT NOT NULL,
+                    content_type TEXT NOT NULL,
+                    timestamp TEXT NOT NULL,
+                    pinned BOOLEAN DEFAULT FALSE
+                );
+                CREATE TABLE app_config (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+                INSERT INTO clipboard_history (content, content_type, timestamp, pinned)
+                VALUES ('kept from before', 'text', '2024-01-01T00:00:00Z', FALSE);",
+            )
+            .unwrap();
+        }
+
+        let db = DatabaseManager::new(db_path).unwrap();
+        let before = db.get_clipboard_history().unwrap();
+        assert_eq!(before[0].last_used, "2024-01-01T00:00:00Z");
+        assert_eq!(before[0].copy_count, 1);
+
+        // The existing row is found by its backfilled hash
+        db.add_clipboard_item("kept from before").unwrap();
+        let after = db.get_clipboard_history().unwrap();
+        assert_eq!(after.len(), 1);
+        assert_eq!(after[0].id, before[0].id);
+        assert_eq!(after[0].copy_count, 2);
+        assert_eq!(after[0].timestamp, "2024-01-01T00:00:00Z");
+    }
+}
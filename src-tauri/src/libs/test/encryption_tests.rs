@@ -0,0 +1,205 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        database::DatabaseManager,
+        encryption::{change_encryption, key_file_path, open_database, read_key_file, KeySource},
+        migrations::{get_schema_version, latest_schema_version, MigrationError},
+        types::{ClipboardContent, EncryptionMode},
+    };
+    use serial_test::serial;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    const SECRET: &str = "very private clipboard text";
+
+    fn passphrase(passphrase: &str) -> KeySource {
+        KeySource::Passphrase(passphrase.to_string())
+    }
+
+    fn plaintext_database(db_path: &Path) -> DatabaseManager {
+        let db = open_database(db_path, None).unwrap();
+        db.add_clipboard_item(SECRET).unwrap();
+        db
+    }
+
+    fn assert_history(db: &DatabaseManager) {
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].content, ClipboardContent::text(SECRET));
+    }
+
+    fn file_contains(path: &Path, needle: &str) -> bool {
+        let bytes = std::fs::read(path).unwrap();
+        bytes
+            .windows(needle.len())
+            .any(|window| window == needle.as_bytes())
+    }
+
+    #[test]
+    #[serial]
+    fn test_encrypts_existing_database_in_place() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = plaintext_database(&db_path);
+        assert!(file_contains(&db_path, SECRET));
+
+        change_encryption(&db, passphrase("correct horse")).unwrap();
+        assert_eq!(db.encryption_mode(), EncryptionMode::Passphrase);
+        // The open manager keeps working on the encrypted file
+        assert_history(&db);
+        drop(db);
+
+        assert!(!file_contains(&db_path, SECRET));
+        assert!(!db_path.with_extension("db.rekey").exists());
+        assert!(matches!(
+            open_database(&db_path, None),
+            Err(MigrationError::Locked)
+        ));
+
+        let db = open_database(&db_path, Some("correct horse")).unwrap();
+        assert_eq!(db.encryption_mode(), EncryptionMode::Passphrase);
+        assert_history(&db);
+    }
+
+    #[test]
+    #[serial]
+    fn test_wrong_passphrase_is_locked() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = plaintext_database(&db_path);
+        change_encryption(&db, passphrase("correct horse")).unwrap();
+        drop(db);
+
+        assert!(matches!(
+            open_database(&db_path, Some("battery staple")),
+            Err(MigrationError::Locked)
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_key_rotation() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = plaintext_database(&db_path);
+
+        change_encryption(&db, passphrase("first")).unwrap();
+        change_encryption(&db, passphrase("second")).unwrap();
+        drop(db);
+
+        assert!(matches!(
+            open_database(&db_path, Some("first")),
+            Err(MigrationError::Locked)
+        ));
+        assert_history(&open_database(&db_path, Some("second")).unwrap());
+    }
+
+    #[test]
+    #[serial]
+    fn test_key_file_opens_without_passphrase() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = plaintext_database(&db_path);
+
+        change_encryption(&db, KeySource::KeyFile).unwrap();
+        assert_eq!(db.encryption_mode(), EncryptionMode::KeyFile);
+        drop(db);
+
+        let key_path = key_file_path(&db_path);
+        assert!(read_key_file(&key_path).is_ok());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&key_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert!(!file_contains(&db_path, SECRET));
+
+        let db = open_database(&db_path, None).unwrap();
+        assert_eq!(db.encryption_mode(), EncryptionMode::KeyFile);
+        assert_history(&db);
+
+        // Switching to a passphrase removes the key file
+        change_encryption(&db, passphrase("correct horse")).unwrap();
+        drop(db);
+        assert!(!key_path.exists());
+        assert_history(&open_database(&db_path, Some("correct horse")).unwrap());
+    }
+
+    #[test]
+    #[serial]
+    fn test_interrupted_key_file_change_is_completed() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = plaintext_database(&db_path);
+        change_encryption(&db, KeySource::KeyFile).unwrap();
+        drop(db);
+
+        // Simulate a crash after re-encrypting but before the new key file was renamed
+        let key_path = key_file_path(&db_path);
+        let pending_path = db_path.with_extension("key.new");
+        std::fs::rename(&key_path, &pending_path).unwrap();
+        std::fs::write(&key_path, "00".repeat(32)).unwrap();
+
+        assert_history(&open_database(&db_path, None).unwrap());
+        assert!(!pending_path.exists());
+        assert!(read_key_file(&key_path).is_ok());
+        assert_history(&open_database(&db_path, None).unwrap());
+    }
+
+    #[test]
+    #[serial]
+    fn test_decrypt_back_to_plaintext() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = plaintext_database(&db_path);
+        change_encryption(&db, passphrase("correct horse")).unwrap();
+
+        change_encryption(&db, KeySource::None).unwrap();
+        assert_eq!(db.encryption_mode(), EncryptionMode::None);
+        drop(db);
+
+        assert!(file_contains(&db_path, SECRET));
+        let db = open_database(&db_path, None).unwrap();
+        assert_eq!(db.encryption_mode(), EncryptionMode::None);
+        assert_history(&db);
+    }
+
+    #[test]
+    #[serial]
+    fn test_encrypted_database_keeps_search_and_schema() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let db = plaintext_database(&db_path);
+        change_encryption(&db, passphrase("correct horse")).unwrap();
+        drop(db);
+
+        let db = open_database(&db_path, Some("correct horse")).unwrap();
+        {
+            let conn = db.get_connection().lock().unwrap();
+            assert_eq!(get_schema_version(&conn).unwrap(), latest_schema_version());
+        }
+
+        let results = db.search_clipboard_history("private", 10, true).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.content, ClipboardContent::text(SECRET));
+
+        // New items are indexed in the encrypted file as well
+        db.add_clipboard_item("another entry").unwrap();
+        assert_eq!(
+            db.search_clipboard_history("another", 10, true)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_in_memory_database_cannot_be_encrypted() {
+        let db = DatabaseManager::new_test().unwrap();
+
+        assert!(change_encryption(&db, passphrase("correct horse")).is_err());
+        assert_eq!(db.encryption_mode(), EncryptionMode::None);
+    }
+}
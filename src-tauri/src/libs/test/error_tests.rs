@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        archive::ArchiveError, commands::require_database, database::DatabaseManager,
+        error::ClipRayError, migrations::MigrationError, setup::StartupError,
+        template::TemplateError, types::SnippetDraft,
+    };
+    use serial_test::serial;
+    use std::io;
+
+    fn sqlite_failure(code: i32) -> rusqlite::Error {
+        rusqlite::Error::SqliteFailure(rusqlite::ffi::Error::new(code), None)
+    }
+
+    #[test]
+    #[serial]
+    fn test_database_validation_errors_keep_their_type() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.create_tag("work").unwrap();
+
+        let duplicate = db.create_tag("Work").unwrap_err();
+        assert_eq!(
+            duplicate,
+            ClipRayError::AlreadyExists("A tag named 'Work' already exists".to_string())
+        );
+        assert_eq!(duplicate.code(), "already-exists");
+
+        let empty = db.create_tag(" ").unwrap_err();
+        assert_eq!(empty.code(), "invalid-input");
+
+        let missing_folder = db
+            .create_snippet(&SnippetDraft {
+                name: "Greeting".to_string(),
+                abbreviation: None,
+                content: "Hello".to_string(),
+                folder_id: Some(42),
+            })
+            .unwrap_err();
+        assert_eq!(
+            missing_folder,
+            ClipRayError::NotFound("Folder 42 not found".to_string())
+        );
+
+        // Logged errors read as the message alone
+        assert_eq!(
+            db.create_tag("work").unwrap_err().to_string(),
+            "A tag named 'work' already exists"
+        );
+    }
+
+    #[test]
+    fn test_database_failures() {
+        let error = ClipRayError::from(rusqlite::Error::QueryReturnedNoRows);
+        assert_eq!(error.code(), "database");
+        assert_eq!(error.message(), "A database error occurred");
+        assert!(error.detail().is_some());
+
+        let corrupt = ClipRayError::from(sqlite_failure(rusqlite::ffi::SQLITE_CORRUPT));
+        assert_eq!(corrupt.code(), "database-damaged");
+        let not_a_database = ClipRayError::from(sqlite_failure(rusqlite::ffi::SQLITE_NOTADB));
+        assert_eq!(not_a_database.code(), "database-damaged");
+
+        // Locks held by other connections are not the passphrase lock
+        for code in [rusqlite::ffi::SQLITE_BUSY, rusqlite::ffi::SQLITE_LOCKED] {
+            let busy = ClipRayError::from(sqlite_failure(code));
+            assert_eq!(busy.code(), "database-busy");
+            assert!(busy.detail().is_some());
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_commands_before_the_database_is_open() {
+        let missing: Option<&DatabaseManager> = None;
+        let Err(error) = require_database(missing) else {
+            panic!("no database is not an open database");
+        };
+        assert_eq!(error, ClipRayError::locked());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap()["code"],
+            "database-locked"
+        );
+
+        let db = DatabaseManager::new_test().unwrap();
+        assert!(require_database(Some(&db)).is_ok());
+    }
+
+    #[test]
+    fn test_migration_and_startup_errors() {
+        assert_eq!(
+            ClipRayError::from(MigrationError::Locked),
+            ClipRayError::DatabaseLocked
+        );
+        assert_eq!(
+            ClipRayError::from(MigrationError::SchemaTooNew {
+                found: 12,
+                supported: 9
+            }),
+            ClipRayError::DatabaseTooNew {
+                found: 12,
+                supported: 9
+            }
+        );
+        assert_eq!(
+            ClipRayError::from(MigrationError::Corrupt("page 3".to_string())),
+            ClipRayError::DatabaseDamaged("page 3".to_string())
+        );
+        assert_eq!(
+            ClipRayError::from(MigrationError::Io(io::Error::other("disk full"))),
+            ClipRayError::File("disk full".to_string())
+        );
+        assert_eq!(
+            ClipRayError::from(StartupError::DataDir("No home".to_string())),
+            ClipRayError::DataDir("No home".to_string())
+        );
+        assert_eq!(
+            ClipRayError::from(StartupError::Database(MigrationError::Locked)),
+            ClipRayError::DatabaseLocked
+        );
+    }
+
+    #[test]
+    fn test_archive_and_template_errors() {
+        let error = ClipRayError::from(ArchiveError::NotAnArchive);
+        assert_eq!(error.code(), "invalid-archive");
+        assert_eq!(error.message(), "The file is not a Clipray export");
+
+        let io_error = ClipRayError::from(ArchiveError::Io(io::Error::other("denied")));
+        assert_eq!(io_error, ClipRayError::File("denied".to_string()));
+
+        let template = ClipRayError::from(TemplateError::MissingField("Name".to_string()));
+        assert_eq!(
+            template,
+            ClipRayError::InvalidInput("No value for field 'Name'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_serialized_for_the_window() {
+        let error = ClipRayError::HotkeyInUse {
+            hotkey: "Alt+V".to_string(),
+            detail: "already registered".to_string(),
+        };
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], "hotkey-in-use");
+        assert_eq!(json["message"], error.message());
+        assert!(json["message"].as_str().unwrap().contains("'Alt+V'"));
+        assert_eq!(json["detail"], "already registered");
+
+        let json = serde_json::to_value(ClipRayError::not_found("Item", 3)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "code": "not-found",
+                "message": "Item 3 not found",
+                "detail": null,
+            })
+        );
+    }
+
+    #[test]
+    fn test_display_includes_the_detail() {
+        assert_eq!(
+            ClipRayError::Clipboard("no display".to_string()).to_string(),
+            "The clipboard could not be accessed: no display"
+        );
+        assert_eq!(
+            ClipRayError::WrongPassphrase.to_string(),
+            "Wrong passphrase"
+        );
+    }
+}
@@ -0,0 +1,16 @@
+// Shared helpers for the test modules
+use image::{Rgba, RgbaImage};
+use std::io::Cursor;
+
+/// Solid-colour PNG of the given size
+pub fn make_png(width: u32, height: u32) -> Vec<u8> {
+    let mut buffer = Cursor::new(Vec::new());
+    image::DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+        width,
+        height,
+        Rgba([200, 30, 30, 255]),
+    ))
+    .write_to(&mut buffer, image::ImageFormat::Png)
+    .unwrap();
+    buffer.into_inner()
+}
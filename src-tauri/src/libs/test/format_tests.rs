@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        clipboard::{copy_to_clipboard_impl, ClipboardMonitor},
+        clipboard_backend::{ClipboardBackend, MemoryClipboard},
+        database::DatabaseManager,
+        test::fixtures::make_png,
+        types::{ClipboardContent, ClipboardSnapshot},
+    };
+    use serial_test::serial;
+
+    fn rich_text() -> ClipboardSnapshot {
+        ClipboardSnapshot {
+            text: Some("Hello world".to_string()),
+            html: Some("<b>Hello</b> world".to_string()),
+            rtf: Some("{\\rtf1 {\\b Hello} world}".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn count_rows(db: &DatabaseManager, table: &str) -> i64 {
+        let conn = db.get_connection().lock().unwrap();
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    #[serial]
+    fn test_rich_text_is_stored_as_one_item() {
+        let db = DatabaseManager::new_test().unwrap();
+
+        db.add_snapshot(&rich_text()).unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].content, ClipboardContent::text("Hello world"));
+        assert_eq!(
+            db.get_item_snapshot(history[0].id).unwrap(),
+            Some(rich_text())
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_file_list_takes_precedence() {
+        let db = DatabaseManager::new_test().unwrap();
+        let snapshot = ClipboardSnapshot {
+            text: Some("a.txt\nb.txt".to_string()),
+            files: Some(vec![
+                "file:///home/user/a.txt".to_string(),
+                "file:///home/user/b.txt".to_string(),
+            ]),
+            ..Default::default()
+        };
+
+        db.add_snapshot(&snapshot).unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(
+            history[0].content,
+            ClipboardContent::Files {
+                files: snapshot.files.clone().unwrap()
+            }
+        );
+        assert_eq!(db.get_item_snapshot(history[0].id).unwrap(), Some(snapshot));
+    }
+
+    #[test]
+    #[serial]
+    fn test_html_only_item() {
+        let db = DatabaseManager::new_test().unwrap();
+
+        db.add_snapshot(&ClipboardSnapshot {
+            html: Some("<i>only html</i>".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(
+            history[0].content,
+            ClipboardContent::Html {
+                content: "<i>only html</i>".to_string()
+            }
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_text_with_image_keeps_both() {
+        let db = DatabaseManager::new_test().unwrap();
+        let png = make_png(5, 5);
+        let snapshot = ClipboardSnapshot {
+            text: Some("cell".to_string()),
+            image: Some(png.clone()),
+            ..Default::default()
+        };
+
+        db.add_snapshot(&snapshot).unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].content, ClipboardContent::text("cell"));
+        assert_eq!(db.get_item_snapshot(history[0].id).unwrap(), Some(snapshot));
+
+        // Deleting the item removes its image and formats too
+        db.delete_clipboard_item(history[0].id).unwrap();
+        assert_eq!(count_rows(&db, "clipboard_images"), 0);
+        assert_eq!(count_rows(&db, "clipboard_formats"), 0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_unreadable_extra_image_is_dropped() {
+        let db = DatabaseManager::new_test().unwrap();
+
+        db.add_snapshot(&ClipboardSnapshot {
+            text: Some("still stored".to_string()),
+            image: Some(b"broken".to_vec()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history[0].content, ClipboardContent::text("still stored"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_recopy_replaces_formats() {
+        let db = DatabaseManager::new_test().unwrap();
+
+        db.add_snapshot(&rich_text()).unwrap();
+        let restyled = ClipboardSnapshot {
+            text: Some("Hello world".to_string()),
+            html: Some("<i>Hello</i> world".to_string()),
+            ..Default::default()
+        };
+        db.add_snapshot(&restyled).unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].copy_count, 2);
+        assert_eq!(db.get_item_snapshot(history[0].id).unwrap(), Some(restyled));
+    }
+
+    #[test]
+    #[serial]
+    fn test_empty_snapshot_is_rejected() {
+        let db = DatabaseManager::new_test().unwrap();
+
+        assert!(db.add_snapshot(&ClipboardSnapshot::default()).is_err());
+        assert!(db.get_clipboard_history().unwrap().is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_copy_restores_all_formats() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        clipboard.write(rich_text()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        let item = db.get_clipboard_history().unwrap()[0].clone();
+
+        clipboard.write_text("something else".to_string()).unwrap();
+        copy_to_clipboard_impl(item.id, &db, &clipboard).unwrap();
+
+        assert_eq!(clipboard.read_text().unwrap(), "Hello world");
+        assert_eq!(clipboard.read_html().unwrap(), "<b>Hello</b> world");
+        assert!(clipboard.read_rtf().unwrap().starts_with("{\\rtf1"));
+    }
+}
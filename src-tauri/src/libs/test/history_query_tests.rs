@@ -0,0 +1,220 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{database::DatabaseManager, types::*};
+    use serial_test::serial;
+
+    /// Insert items with fixed timestamps: day N of January 2024
+    fn insert_items(db: &DatabaseManager, items: &[(&str, &str, u32, bool)]) {
+        let conn = db.get_connection().lock().unwrap();
+        for (content, content_type, day, pinned) in items {
+            conn.execute(
+                "INSERT INTO clipboard_history (content, content_type, timestamp, pinned)
+                 VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![
+                    content,
+                    content_type,
+                    format!("2024-01-{:02}T00:00:00+00:00", day),
+                    pinned
+                ],
+            )
+            .unwrap();
+        }
+    }
+
+    fn contents(page: &HistoryPage) -> Vec<&str> {
+        page.items
+            .iter()
+            .map(|item| item.content.as_text().unwrap())
+            .collect()
+    }
+
+    #[test]
+    #[serial]
+    fn test_query_default_returns_newest_first() {
+        let db = DatabaseManager::new_test().unwrap();
+        insert_items(
+            &db,
+            &[
+                ("a", "text", 1, false),
+                ("b", "text", 2, true),
+                ("c", "text", 3, false),
+            ],
+        );
+
+        let page = db
+            .query_clipboard_history(&HistoryQuery::default())
+            .unwrap();
+        assert_eq!(contents(&page), vec!["c", "b", "a"]);
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn test_query_oldest_first() {
+        let db = DatabaseManager::new_test().unwrap();
+        insert_items(&db, &[("a", "text", 1, false), ("b", "text", 2, false)]);
+
+        let query = HistoryQuery {
+            sort: SortOrder::OldestFirst,
+            ..Default::default()
+        };
+        let page = db.query_clipboard_history(&query).unwrap();
+        assert_eq!(contents(&page), vec!["a", "b"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_query_cursor_pagination() {
+        let db = DatabaseManager::new_test().unwrap();
+        insert_items(
+            &db,
+            &[
+                ("1", "text", 1, false),
+                ("2", "text", 2, false),
+                ("3", "text", 3, false),
+                ("4", "text", 4, false),
+                ("5", "text", 5, false),
+            ],
+        );
+
+        let mut query = HistoryQuery {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let mut pages = Vec::new();
+        loop {
+            let page = db.query_clipboard_history(&query).unwrap();
+            pages.push(contents(&page).join(","));
+            match page.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => break,
+            }
+        }
+
+        assert_eq!(pages, vec!["5,4", "3,2", "1"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_query_cursor_with_equal_timestamps() {
+        let db = DatabaseManager::new_test().unwrap();
+        insert_items(
+            &db,
+            &[
+                ("x", "text", 1, false),
+                ("y", "text", 1, false),
+                ("z", "text", 1, false),
+            ],
+        );
+
+        let first = db
+            .query_clipboard_history(&HistoryQuery {
+                limit: Some(2),
+                ..Default::default()
+            })
+            .unwrap();
+        let second = db
+            .query_clipboard_history(&HistoryQuery {
+                limit: Some(2),
+                cursor: first.next_cursor.clone(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        // Ties are broken by id, so nothing is skipped or repeated
+        assert_eq!(contents(&first), vec!["z", "y"]);
+        assert_eq!(contents(&second), vec!["x"]);
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn test_query_filter_by_content_type_and_pinned() {
+        let db = DatabaseManager::new_test().unwrap();
+        insert_items(
+            &db,
+            &[
+                ("text pinned", "text", 1, true),
+                ("text", "text", 2, false),
+                ("html", "html", 3, false),
+            ],
+        );
+
+        let html = db
+            .query_clipboard_history(&HistoryQuery {
+                content_type: Some(ContentType::Html),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(contents(&html), vec!["html"]);
+
+        let pinned = db
+            .query_clipboard_history(&HistoryQuery {
+                pinned: Some(true),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(contents(&pinned), vec!["text pinned"]);
+
+        let unpinned_text = db
+            .query_clipboard_history(&HistoryQuery {
+                content_type: Some(ContentType::Text),
+                pinned: Some(false),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(contents(&unpinned_text), vec!["text"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_query_timestamp_range() {
+        let db = DatabaseManager::new_test().unwrap();
+        insert_items(
+            &db,
+            &[
+                ("d1", "text", 1, false),
+                ("d2", "text", 2, false),
+                ("d3", "text", 3, false),
+            ],
+        );
+
+        let page = db
+            .query_clipboard_history(&HistoryQuery {
+                since: Some("2024-01-02T00:00:00Z".to_string()),
+                until: Some("2024-01-03T09:00:00+09:00".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        // `until` is exclusive and normalized to UTC (2024-01-03T00:00:00Z)
+        assert_eq!(contents(&page), vec!["d2"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_query_rejects_invalid_input() {
+        let db = DatabaseManager::new_test().unwrap();
+
+        let bad_cursor = db.query_clipboard_history(&HistoryQuery {
+            cursor: Some("not a cursor".to_string()),
+            ..Default::default()
+        });
+        assert!(bad_cursor.is_err());
+
+        let bad_timestamp = db.query_clipboard_history(&HistoryQuery {
+            since: Some("yesterday".to_string()),
+            ..Default::default()
+        });
+        assert!(bad_timestamp.is_err());
+    }
+
+    #[test]
+    fn test_history_query_deserializes_partial_json() {
+        let query: HistoryQuery =
+            serde_json::from_str(r#"{"pinned": true, "sort": "oldest-first"}"#).unwrap();
+        assert_eq!(query.pinned, Some(true));
+        assert_eq!(query.sort, SortOrder::OldestFirst);
+        assert!(query.cursor.is_none());
+        assert!(query.limit.is_none());
+    }
+}
@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        constants::THUMBNAIL_MAX_SIZE, database::DatabaseManager, thumbnail::process_image,
+        types::ContentType,
+    };
+    use base64::{engine::general_purpose, Engine};
+    use image::{ImageFormat, Rgba, RgbaImage};
+    use serial_test::serial;
+    use std::io::Cursor;
+
+    fn make_image(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let img = RgbaImage::from_pixel(width, height, Rgba([200, 30, 30, 255]));
+        let mut buffer = Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgba8(img)
+            .write_to(&mut buffer, format)
+            .unwrap();
+        buffer.into_inner()
+    }
+
+    fn make_png(width: u32, height: u32) -> Vec<u8> {
+        make_image(width, height, ImageFormat::Png)
+    }
+
+    #[test]
+    fn test_process_image_small_png() {
+        let png = make_png(20, 10);
+        let processed = process_image(&png).unwrap();
+
+        assert_eq!(processed.width, 20);
+        assert_eq!(processed.height, 10);
+        assert_eq!(processed.png, png);
+        assert_eq!(processed.thumbnail, png);
+    }
+
+    #[test]
+    fn test_process_image_generates_thumbnail() {
+        let png = make_png(800, 400);
+        let processed = process_image(&png).unwrap();
+
+        let thumbnail = image::load_from_memory(&processed.thumbnail).unwrap();
+        assert_eq!(thumbnail.width(), THUMBNAIL_MAX_SIZE);
+        assert_eq!(thumbnail.height(), THUMBNAIL_MAX_SIZE / 2);
+        assert!(processed.thumbnail.len() < processed.png.len());
+    }
+
+    #[test]
+    fn test_process_image_converts_to_png() {
+        let bmp = make_image(4, 4, ImageFormat::Bmp);
+        let processed = process_image(&bmp).unwrap();
+
+        assert_eq!(
+            image::guess_format(&processed.png).unwrap(),
+            ImageFormat::Png
+        );
+    }
+
+    #[test]
+    fn test_process_image_rejects_garbage() {
+        assert!(process_image(b"not an image").is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_add_image_item_returns_thumbnail_only() {
+        let db = DatabaseManager::new_test().unwrap();
+        let png = make_png(640, 480);
+        db.add_image_item(&png).unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        let image = history[0].content.as_image().unwrap();
+        assert_eq!(image.mime, "image/png");
+        assert_eq!(image.width, 640);
+        assert_eq!(image.height, 480);
+        let thumbnail = general_purpose::STANDARD.decode(&image.thumbnail).unwrap();
+        assert!(thumbnail.len() < png.len());
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_image_data_roundtrip() {
+        let db = DatabaseManager::new_test().unwrap();
+        let png = make_png(300, 200);
+        db.add_image_item(&png).unwrap();
+
+        let id = db.get_clipboard_history().unwrap()[0].id;
+        assert_eq!(db.get_image_data(id).unwrap(), Some(png));
+        assert_eq!(db.get_image_data(id + 1).unwrap(), None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_duplicate_image_is_replaced() {
+        let db = DatabaseManager::new_test().unwrap();
+        let png = make_png(8, 8);
+        db.add_image_item(&png).unwrap();
+        db.add_clipboard_item("between").unwrap();
+        db.add_image_item(&png).unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].content.content_type(), ContentType::Image);
+
+        let conn = db.get_connection().lock().unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM clipboard_images", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_deleting_image_item_removes_blob() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_image_item(&make_png(8, 8)).unwrap();
+
+        let id = db.get_clipboard_history().unwrap()[0].id;
+        db.delete_clipboard_item(id).unwrap();
+
+        assert_eq!(db.get_image_data(id).unwrap(), None);
+        let conn = db.get_connection().lock().unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM clipboard_images", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_add_image_item_rejects_invalid_data() {
+        let db = DatabaseManager::new_test().unwrap();
+        assert!(db.add_image_item(b"garbage").is_err());
+        assert!(db.get_clipboard_history().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        database::DatabaseManager,
+        migrations::{get_schema_version, latest_schema_version, run_migrations, MigrationError},
+        types::ClipboardContent,
+    };
+    use base64::{engine::general_purpose, Engine};
+    use rusqlite::Connection;
+    use serial_test::serial;
+    use std::io::Cursor;
+    use tempfile::tempdir;
+
+    #[test]
+    #[serial]
+    fn test_new_database_is_at_latest_version() {
+        let db = DatabaseManager::new_test().unwrap();
+
+        let conn = db.get_connection().lock().unwrap();
+        let version = get_schema_version(&conn).unwrap();
+        assert_eq!(version, latest_schema_version());
+    }
+
+    #[test]
+    #[serial]
+    fn test_migrations_are_idempotent() {
+        let mut conn = Connection::open_in_memory().unwrap();
+
+        run_migrations(&mut conn).unwrap();
+        run_migrations(&mut conn).unwrap();
+
+        assert_eq!(get_schema_version(&conn).unwrap(), latest_schema_version());
+    }
+
+    #[test]
+    #[serial]
+    fn test_legacy_unversioned_database_is_adopted() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+
+        // Database created by a release without schema versioning
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute(
+                "CREATE TABLE clipboard_history (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    content TEXT NOT NULL,
+                    content_type TEXT NOT NULL,
+                    timestamp TEXT NOT NULL,
+                    pinned BOOLEAN DEFAULT FALSE
+                )",
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                "CREATE TABLE app_config (key TEXT PRIMARY KEY, value TEXT NOT NULL)",
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO clipboard_history (content, content_type, timestamp, pinned)
+                 VALUES ('legacy item', 'text', '2024-01-01T00:00:00Z', TRUE)",
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO app_config (key, value) VALUES ('max_history_count', '10')",
+                [],
+            )
+            .unwrap();
+        }
+
+        let db = DatabaseManager::new(db_path).unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].content, ClipboardContent::text("legacy item"));
+        assert!(history[0].pinned);
+
+        // Existing settings are kept, missing ones get defaults
+        let config = db.get_config().unwrap();
+        assert_eq!(config.max_history_count, 10);
+        assert!(!config.autostart);
+
+        let conn = db.get_connection().lock().unwrap();
+        assert_eq!(get_schema_version(&conn).unwrap(), latest_schema_version());
+    }
+
+    #[test]
+    #[serial]
+    fn test_existing_html_items_are_indexed() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE clipboard_history (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    content TEXT NOT NULL,
+                    content_type TEXT NOT NULL,
+                    timestamp TEXT NOT NULL,
+                    pinned BOOLEAN DEFAULT FALSE
+                );
+                CREATE TABLE app_config (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+                INSERT INTO clipboard_history (content, content_type, timestamp)
+                VALUES ('<i>legacy</i> markup', 'html', '2024-01-01T00:00:00Z'),
+                       ('plain legacy', 'text', '2024-01-02T00:00:00Z');",
+            )
+            .unwrap();
+        }
+
+        let db = DatabaseManager::new(db_path).unwrap();
+        let results = db.search_clipboard_history("legacy", 50, false).unwrap();
+        assert_eq!(results.len(), 2);
+        let results = db.search_clipboard_history("markup", 50, false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            results[0].item.content,
+            ClipboardContent::Html { .. }
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_legacy_base64_images_are_moved_to_blobs() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+
+        let mut png = Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgba8(image::RgbaImage::new(400, 200))
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let png = png.into_inner();
+
+        {
+            let mut conn = Connection::open(&db_path).unwrap();
+            // Schema as it was before images moved out of the content column
+            conn.execute_batch(
+                "CREATE TABLE clipboard_history (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    content TEXT NOT NULL,
+                    content_type TEXT NOT NULL,
+                    timestamp TEXT NOT NULL,
+                    pinned BOOLEAN DEFAULT FALSE
+                );
+                CREATE TABLE app_config (key TEXT PRIMARY KEY, value TEXT NOT NULL);",
+            )
+            .unwrap();
+            let tx = conn.transaction().unwrap();
+            tx.execute(
+                "INSERT INTO clipboard_history (content, content_type, timestamp, pinned)
+                 VALUES (?1, 'image', '2024-01-01T00:00:00Z', TRUE)",
+                [general_purpose::STANDARD.encode(&png)],
+            )
+            .unwrap();
+            tx.execute(
+                "INSERT INTO clipboard_history (content, content_type, timestamp, pinned)
+                 VALUES ('bm90IGFuIGltYWdl', 'image', '2024-01-02T00:00:00Z', FALSE)",
+                [],
+            )
+            .unwrap();
+            tx.commit().unwrap();
+        }
+
+        let db = DatabaseManager::new(db_path).unwrap();
+        let history = db.get_clipboard_history().unwrap();
+
+        // The undecodable image is dropped, the valid one keeps its pin
+        assert_eq!(history.len(), 1);
+        assert!(history[0].pinned);
+        let image = history[0].content.as_image().unwrap();
+        assert_eq!((image.width, image.height), (400, 200));
+        assert_eq!(db.get_image_data(history[0].id).unwrap(), Some(png));
+        // Thumbnails made by the migration are 160 pixels wide, whatever the current size
+        let thumbnail = general_purpose::STANDARD.decode(&image.thumbnail).unwrap();
+        let thumbnail = image::load_from_memory(&thumbnail).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (160, 80));
+    }
+
+    #[test]
+    #[serial]
+    fn test_reopen_existing_database() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+
+        {
+            let db = DatabaseManager::new(db_path.clone()).unwrap();
+            db.add_clipboard_item("persisted").unwrap();
+        }
+
+        let db = DatabaseManager::new(db_path).unwrap();
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].content, ClipboardContent::text("persisted"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_newer_schema_is_refused() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.pragma_update(None, "user_version", latest_schema_version() + 1)
+                .unwrap();
+        }
+
+        let result = DatabaseManager::new(db_path);
+        match result {
+            Err(MigrationError::SchemaTooNew { found, supported }) => {
+                assert_eq!(found, latest_schema_version() + 1);
+                assert_eq!(supported, latest_schema_version());
+            }
+            _ => panic!("expected SchemaTooNew error"),
+        }
+    }
+}
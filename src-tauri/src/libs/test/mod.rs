@@ -1,7 +1,55 @@
 // Test modules for the libs package
 #[cfg(test)]
+mod app_blacklist_tests;
+#[cfg(test)]
+mod archive_tests;
+#[cfg(test)]
+mod backup_tests;
+#[cfg(test)]
 mod complex_tests;
 #[cfg(test)]
 mod database_tests;
 #[cfg(test)]
+mod dedup_tests;
+#[cfg(test)]
+mod encryption_tests;
+#[cfg(test)]
+mod error_tests;
+#[cfg(test)]
+mod exclusion_tests;
+#[cfg(test)]
+mod fixtures;
+#[cfg(test)]
+mod format_tests;
+#[cfg(test)]
+mod history_query_tests;
+#[cfg(test)]
+mod image_tests;
+#[cfg(test)]
+mod migration_tests;
+#[cfg(test)]
+mod monitor_tests;
+#[cfg(test)]
+mod own_write_tests;
+#[cfg(test)]
+mod pause_tests;
+#[cfg(test)]
+mod retention_tests;
+#[cfg(test)]
+mod search_tests;
+#[cfg(test)]
+mod selection_tests;
+#[cfg(test)]
+mod sensitive_tests;
+#[cfg(test)]
+mod snippet_tests;
+#[cfg(test)]
+mod source_app_tests;
+#[cfg(test)]
+mod startup_tests;
+#[cfg(test)]
+mod tag_tests;
+#[cfg(test)]
+mod template_tests;
+#[cfg(test)]
 mod types_tests;
@@ -0,0 +1,267 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        clipboard::{copy_to_clipboard_impl, ClipboardMonitor},
+        clipboard_backend::{ClipboardBackend, ClipboardFormats, MemoryClipboard, MultiClipboard},
+        database::DatabaseManager,
+        error::ClipRayError,
+        test::fixtures::make_png,
+        types::{CaptureSettings, ClipboardContent, ContentType},
+    };
+    use serial_test::serial;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    #[serial]
+    fn test_poll_captures_text_once() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        clipboard.write_text("captured".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        assert!(!monitor.poll(&clipboard, &db));
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].content, ClipboardContent::text("captured"));
+        assert_eq!(history[0].copy_count, 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_poll_ignores_empty_clipboard() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        assert!(!monitor.poll(&clipboard, &db));
+        clipboard.write_text(String::new()).unwrap();
+        assert!(!monitor.poll(&clipboard, &db));
+
+        assert!(db.get_clipboard_history().unwrap().is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_poll_recopy_counts_usage() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        for text in ["first", "second", "first"] {
+            clipboard.write_text(text.to_string()).unwrap();
+            assert!(monitor.poll(&clipboard, &db));
+        }
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].content, ClipboardContent::text("first"));
+        assert_eq!(history[0].copy_count, 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_poll_captures_image() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+        let png = make_png(6, 4);
+
+        clipboard.write_image(png.clone()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        assert!(!monitor.poll(&clipboard, &db));
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].content.content_type(), ContentType::Image);
+        assert_eq!(db.get_image_data(history[0].id).unwrap(), Some(png));
+    }
+
+    #[test]
+    #[serial]
+    fn test_poll_skips_invalid_image() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        clipboard.write_image(b"not an image".to_vec()).unwrap();
+        assert!(!monitor.poll(&clipboard, &db));
+
+        assert!(db.get_clipboard_history().unwrap().is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_copy_text_to_clipboard() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        db.add_clipboard_item("pasted").unwrap();
+        let id = db.get_clipboard_history().unwrap()[0].id;
+
+        copy_to_clipboard_impl(id, &db, &clipboard).unwrap();
+
+        assert_eq!(clipboard.read_text().unwrap(), "pasted");
+    }
+
+    #[test]
+    #[serial]
+    fn test_copy_image_to_clipboard_by_id() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let png = make_png(3, 3);
+        db.add_image_item(&png).unwrap();
+        let id = db.get_clipboard_history().unwrap()[0].id;
+
+        copy_to_clipboard_impl(id, &db, &clipboard).unwrap();
+        assert_eq!(clipboard.read_image().unwrap(), png);
+        assert!(clipboard.read_text().is_err());
+
+        let unknown_id = copy_to_clipboard_impl(999, &db, &clipboard);
+        assert_eq!(
+            unknown_id,
+            Err(ClipRayError::NotFound("Item 999 not found".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_memory_clipboard_formats_and_notifications() {
+        let clipboard = MemoryClipboard::new();
+        let changes = Arc::new(AtomicUsize::new(0));
+        let counter = changes.clone();
+        assert!(clipboard
+            .watch(Box::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }))
+            .unwrap());
+
+        assert_eq!(
+            clipboard.available_formats().unwrap(),
+            ClipboardFormats::default()
+        );
+
+        clipboard.write_text("text".to_string()).unwrap();
+        let formats = clipboard.available_formats().unwrap();
+        assert!(formats.text);
+        assert!(!formats.image);
+
+        clipboard.clear().unwrap();
+        assert!(clipboard.read_text().is_err());
+        assert_eq!(changes.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_unchanged_clipboard_is_not_read_again() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        clipboard.write_image(make_png(6, 4)).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        let reads = clipboard.read_count();
+        assert!(reads > 0);
+
+        for _ in 0..3 {
+            assert!(!monitor.poll(&clipboard, &db));
+        }
+        assert_eq!(clipboard.read_count(), reads);
+
+        // A new copy is read, even with the same content, and counted as a use
+        clipboard.write_image(make_png(6, 4)).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        assert!(clipboard.read_count() > reads);
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].copy_count, 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_copying_the_same_text_again_counts_usage() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        for _ in 0..3 {
+            clipboard.write_text("again".to_string()).unwrap();
+            assert!(monitor.poll(&clipboard, &db));
+            assert!(!monitor.poll(&clipboard, &db));
+        }
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].copy_count, 3);
+    }
+
+    #[test]
+    #[serial]
+    fn test_unchanged_clipboard_without_marker_is_compared() {
+        let db = DatabaseManager::new_test().unwrap();
+        let memory = MemoryClipboard::new();
+        // No change marker, so only a different content is a new copy
+        let clipboard = MultiClipboard(vec![&memory]);
+        let mut monitor = ClipboardMonitor::new();
+
+        memory.write_text("same".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        memory.write_text("same".to_string()).unwrap();
+        assert!(!monitor.poll(&clipboard, &db));
+        assert_eq!(db.get_clipboard_history().unwrap()[0].copy_count, 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_first_poll_after_pause_only_takes_a_baseline() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        clipboard.write_text("before".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        monitor.pause();
+        clipboard.write_text("during".to_string()).unwrap();
+        assert!(!monitor.poll(&clipboard, &db));
+
+        clipboard.write_text("after".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        let history = db.get_clipboard_history().unwrap();
+        let texts: Vec<_> = history.iter().map(|item| item.content.clone()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                ClipboardContent::text("after"),
+                ClipboardContent::text("before")
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_capture_settings() {
+        let db = DatabaseManager::new_test().unwrap();
+        let mut config = db.get_config().unwrap();
+        assert_eq!(config.capture, CaptureSettings::default());
+        assert_eq!(config.capture.poll_interval_ms, 1000);
+
+        config.capture.poll_interval_ms = 250;
+        db.update_config(&config).unwrap();
+        assert_eq!(db.get_capture_settings().unwrap().poll_interval_ms, 250);
+
+        assert!(config.capture.validate().is_ok());
+        for interval in [0, 99, 60_001] {
+            let settings = CaptureSettings {
+                poll_interval_ms: interval,
+                ..Default::default()
+            };
+            assert!(settings.validate().is_err(), "{}", interval);
+        }
+
+        // Configs saved before the setting existed get the default
+        let config: CaptureSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(config, CaptureSettings::default());
+    }
+}
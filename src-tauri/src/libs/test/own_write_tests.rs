@@ -0,0 +1,306 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        clipboard::{copy_to_clipboard_impl, ClipboardMonitor, OwnWriteClipboard, OwnWrites},
+        clipboard_backend::{
+            ChangeListener, ClipboardBackend, ClipboardFormats, MemoryClipboard, MultiClipboard,
+        },
+        database::DatabaseManager,
+        types::{ClipboardContent, ClipboardItem, ClipboardSnapshot, OwnWritePolicy, Selection},
+    };
+    use serial_test::serial;
+    use std::sync::Mutex;
+
+    fn contents(history: &[ClipboardItem]) -> Vec<ClipboardContent> {
+        history.iter().map(|item| item.content.clone()).collect()
+    }
+
+    // History with "older" below "newer", both captured by `monitor`
+    fn capture_two(
+        db: &DatabaseManager,
+        clipboard: &MemoryClipboard,
+        monitor: &mut ClipboardMonitor,
+    ) -> (ClipboardItem, ClipboardItem) {
+        for text in ["older", "newer"] {
+            clipboard.write_text(text.to_string()).unwrap();
+            assert!(monitor.poll(clipboard, db));
+        }
+        let history = db.get_clipboard_history().unwrap();
+        (history[1].clone(), history[0].clone())
+    }
+
+    fn set_policy(db: &DatabaseManager, policy: OwnWritePolicy) {
+        let mut config = db.get_config().unwrap();
+        config.capture.own_writes = policy;
+        db.update_config(&config).unwrap();
+    }
+
+    // Reports the change counter of `inner` only once told to, like the X11 and plugin
+    // listeners that count changes on another thread, and rewrites HTML on reads
+    struct LaggingClipboard<'a> {
+        inner: &'a MemoryClipboard,
+        reported: Mutex<Option<u64>>,
+    }
+
+    impl LaggingClipboard<'_> {
+        fn catch_up(&self) {
+            *self.reported.lock().unwrap() = self.inner.change_marker();
+        }
+    }
+
+    impl ClipboardBackend for LaggingClipboard<'_> {
+        fn read_text(&self) -> Result<String, String> {
+            self.inner.read_text()
+        }
+
+        fn read_image(&self) -> Result<Vec<u8>, String> {
+            self.inner.read_image()
+        }
+
+        fn read_html(&self) -> Result<String, String> {
+            Ok(format!(
+                "<meta charset=\"utf-8\">{}",
+                self.inner.read_html()?
+            ))
+        }
+
+        fn read_rtf(&self) -> Result<String, String> {
+            self.inner.read_rtf()
+        }
+
+        fn read_files(&self) -> Result<Vec<String>, String> {
+            self.inner.read_files()
+        }
+
+        fn available_formats(&self) -> Result<ClipboardFormats, String> {
+            self.inner.available_formats()
+        }
+
+        fn write(&self, snapshot: ClipboardSnapshot) -> Result<(), String> {
+            self.inner.write(snapshot)
+        }
+
+        fn clear(&self) -> Result<(), String> {
+            self.inner.clear()
+        }
+
+        fn watch(&self, listener: ChangeListener) -> Result<bool, String> {
+            self.inner.watch(listener)
+        }
+
+        fn change_marker(&self) -> Option<u64> {
+            *self.reported.lock().unwrap()
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_copied_item_moves_to_top_with_its_id() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let own_writes = OwnWrites::default();
+        let mut monitor = ClipboardMonitor::new();
+        let (older, _) = capture_two(&db, &clipboard, &mut monitor);
+        db.toggle_pin(older.id).unwrap();
+
+        let recorded = OwnWriteClipboard {
+            inner: &clipboard,
+            selection: Selection::Clipboard,
+            item_id: Some(older.id),
+            own_writes: &own_writes,
+        };
+        copy_to_clipboard_impl(older.id, &db, &recorded).unwrap();
+        assert!(monitor.poll_with(&clipboard, &db, &own_writes));
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 2);
+        let moved = &history[0];
+        assert_eq!(moved.id, older.id);
+        assert_eq!(moved.timestamp, older.timestamp);
+        assert!(moved.pinned);
+        assert_eq!(moved.copy_count, 2);
+        assert!(moved.last_used > older.last_used);
+
+        // Seen once; nothing changes on the next poll
+        assert!(!monitor.poll_with(&clipboard, &db, &own_writes));
+    }
+
+    #[test]
+    #[serial]
+    fn test_ignored_copy_leaves_history_alone() {
+        let db = DatabaseManager::new_test().unwrap();
+        set_policy(&db, OwnWritePolicy::Ignore);
+        let clipboard = MemoryClipboard::new();
+        let own_writes = OwnWrites::default();
+        let mut monitor = ClipboardMonitor::new();
+        let (older, newer) = capture_two(&db, &clipboard, &mut monitor);
+        let before = db.get_clipboard_history().unwrap();
+
+        let recorded = OwnWriteClipboard {
+            inner: &clipboard,
+            selection: Selection::Clipboard,
+            item_id: Some(older.id),
+            own_writes: &own_writes,
+        };
+        copy_to_clipboard_impl(older.id, &db, &recorded).unwrap();
+        assert!(!monitor.poll_with(&clipboard, &db, &own_writes));
+        assert_eq!(db.get_clipboard_history().unwrap(), before);
+
+        // Copying it again by hand afterwards is an ordinary copy
+        clipboard.write_text("newer".to_string()).unwrap();
+        assert!(monitor.poll_with(&clipboard, &db, &own_writes));
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history[0].id, newer.id);
+        assert_eq!(history[0].copy_count, 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_other_writes_are_captured() {
+        let db = DatabaseManager::new_test().unwrap();
+        set_policy(&db, OwnWritePolicy::Ignore);
+        let clipboard = MemoryClipboard::new();
+        let own_writes = OwnWrites::default();
+        let mut monitor = ClipboardMonitor::new();
+        let (older, _) = capture_two(&db, &clipboard, &mut monitor);
+
+        let item = OwnWriteClipboard {
+            inner: &clipboard,
+            selection: Selection::Clipboard,
+            item_id: Some(older.id),
+            own_writes: &own_writes,
+        };
+        item.write_text("older".to_string()).unwrap();
+
+        // An expanded snippet replaces the item before the monitor looks
+        let snippet = OwnWriteClipboard {
+            item_id: None,
+            ..item
+        };
+        snippet.write_text("expanded snippet".to_string()).unwrap();
+        assert!(monitor.poll_with(&clipboard, &db, &own_writes));
+
+        // So does a copy in another application
+        item.write_text("older".to_string()).unwrap();
+        clipboard.write_text("elsewhere".to_string()).unwrap();
+        assert!(monitor.poll_with(&clipboard, &db, &own_writes));
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(
+            contents(&history),
+            vec![
+                ClipboardContent::text("elsewhere"),
+                ClipboardContent::text("expanded snippet"),
+                ClipboardContent::text("newer"),
+                ClipboardContent::text("older"),
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_write_to_both_selections_is_one_use() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let primary = MemoryClipboard::new();
+        let own_writes = OwnWrites::default();
+        let mut clipboard_monitor = ClipboardMonitor::new();
+        let mut primary_monitor = ClipboardMonitor::for_selection(Selection::Primary);
+        let (older, _) = capture_two(&db, &clipboard, &mut clipboard_monitor);
+        set_policy(&db, OwnWritePolicy::Ignore);
+
+        let targets = [
+            OwnWriteClipboard {
+                inner: &clipboard,
+                selection: Selection::Clipboard,
+                item_id: Some(older.id),
+                own_writes: &own_writes,
+            },
+            OwnWriteClipboard {
+                inner: &primary,
+                selection: Selection::Primary,
+                item_id: Some(older.id),
+                own_writes: &own_writes,
+            },
+        ];
+        let both = MultiClipboard(
+            targets
+                .iter()
+                .map(|target| target as &dyn ClipboardBackend)
+                .collect(),
+        );
+        copy_to_clipboard_impl(older.id, &db, &both).unwrap();
+
+        // Each monitor recognises the write to its own selection
+        assert!(!clipboard_monitor.poll_with(&clipboard, &db, &own_writes));
+        assert!(!primary_monitor.poll_with(&primary, &db, &own_writes));
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].id, older.id);
+        assert_eq!(history[1].copy_count, 1);
+        assert_eq!(history[1].selection, Selection::Clipboard);
+    }
+
+    #[test]
+    #[serial]
+    fn test_write_counted_after_it_returned_is_recognised() {
+        let db = DatabaseManager::new_test().unwrap();
+        let memory = MemoryClipboard::new();
+        let clipboard = LaggingClipboard {
+            inner: &memory,
+            reported: Mutex::new(None),
+        };
+        let own_writes = OwnWrites::default();
+        let mut monitor = ClipboardMonitor::new();
+        for text in ["older", "newer"] {
+            memory.write_text(text.to_string()).unwrap();
+            clipboard.catch_up();
+            assert!(monitor.poll(&clipboard, &db));
+        }
+        let older = db.get_clipboard_history().unwrap()[1].clone();
+
+        let recorded = OwnWriteClipboard {
+            inner: &clipboard,
+            selection: Selection::Clipboard,
+            item_id: Some(older.id),
+            own_writes: &own_writes,
+        };
+        recorded
+            .write(ClipboardSnapshot {
+                text: Some("older".to_string()),
+                html: Some("<b>older</b>".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        // Not counted yet, so not seen yet
+        assert!(!monitor.poll_with(&clipboard, &db, &own_writes));
+
+        // Recognised by the counter, although the HTML came back rewritten
+        clipboard.catch_up();
+        assert!(monitor.poll_with(&clipboard, &db, &own_writes));
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].id, older.id);
+        assert_eq!(history[0].copy_count, 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_own_write_policy_setting() {
+        let db = DatabaseManager::new_test().unwrap();
+        assert_eq!(
+            db.get_capture_settings().unwrap().own_writes,
+            OwnWritePolicy::MoveToTop
+        );
+
+        set_policy(&db, OwnWritePolicy::Ignore);
+        let settings = db.get_capture_settings().unwrap();
+        assert_eq!(settings.own_writes, OwnWritePolicy::Ignore);
+        let json = serde_json::to_value(&settings).unwrap();
+        assert_eq!(json["own_writes"], "ignore");
+        assert_eq!(
+            serde_json::to_value(OwnWritePolicy::MoveToTop).unwrap(),
+            "move-to-top"
+        );
+    }
+}
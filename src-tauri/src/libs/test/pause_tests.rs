@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        clipboard::ClipboardMonitor,
+        clipboard_backend::{ClipboardBackend, MemoryClipboard},
+        database::DatabaseManager,
+        pause::CapturePause,
+        types::{AppConfig, ClipboardContent, PauseStatus},
+    };
+    use chrono::{Duration, Utc};
+    use serial_test::serial;
+
+    #[test]
+    fn test_pause_and_resume() {
+        let pause = CapturePause::default();
+        assert!(!pause.is_paused());
+
+        let status = pause.pause(None);
+        assert_eq!(
+            status,
+            PauseStatus {
+                paused: true,
+                until: None
+            }
+        );
+        assert!(pause.is_paused());
+
+        assert_eq!(pause.resume(), PauseStatus::default());
+        assert!(!pause.is_paused());
+    }
+
+    #[test]
+    fn test_toggle() {
+        let pause = CapturePause::default();
+
+        assert!(pause.toggle().paused);
+        assert!(!pause.toggle().paused);
+
+        // Toggling a timed pause resumes it
+        pause.pause(Some(Duration::minutes(5)));
+        assert!(!pause.toggle().paused);
+    }
+
+    #[test]
+    fn test_timed_pause_expires() {
+        let pause = CapturePause::default();
+        let status = pause.pause(Some(Duration::minutes(5)));
+        let until = status.until.unwrap();
+        assert!(until > Utc::now() + Duration::minutes(4));
+
+        assert!(!pause.expire(Utc::now()));
+        assert!(pause.is_paused());
+
+        assert!(pause.expire(until));
+        assert_eq!(pause.status(), PauseStatus::default());
+        assert!(!pause.expire(until), "expires only once");
+    }
+
+    #[test]
+    fn test_status_ignores_elapsed_pause() {
+        // A pause restored after its end time reads as resumed right away
+        let pause = CapturePause::new(PauseStatus {
+            paused: true,
+            until: Some(Utc::now() - Duration::minutes(1)),
+        });
+
+        assert!(!pause.is_paused());
+    }
+
+    #[tokio::test]
+    async fn test_changed_wakes_waiter() {
+        let pause = CapturePause::default();
+
+        // The notification is kept for a waiter that arrives later
+        pause.pause(None);
+        tokio::time::timeout(std::time::Duration::from_secs(1), pause.changed())
+            .await
+            .expect("pausing notifies");
+    }
+
+    #[test]
+    #[serial]
+    fn test_copies_while_paused_are_not_captured() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        clipboard.write_text("before".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+
+        monitor.pause();
+        clipboard.write_text("secret".to_string()).unwrap();
+        monitor.pause();
+
+        // After resuming, the clipboard still holds the secret
+        assert!(!monitor.poll(&clipboard, &db));
+        assert!(!monitor.poll(&clipboard, &db));
+
+        clipboard.write_text("after".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].content, ClipboardContent::text("after"));
+        assert_eq!(history[1].content, ClipboardContent::text("before"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_pause_status_persistence() {
+        let db = DatabaseManager::new_test().unwrap();
+        assert_eq!(db.get_pause_status().unwrap(), PauseStatus::default());
+
+        let status = CapturePause::default().pause(Some(Duration::minutes(30)));
+        db.save_pause_status(&status).unwrap();
+        assert_eq!(db.get_pause_status().unwrap(), status);
+
+        db.save_pause_status(&PauseStatus::default()).unwrap();
+        assert_eq!(db.get_pause_status().unwrap(), PauseStatus::default());
+    }
+
+    #[test]
+    #[serial]
+    fn test_pause_settings_saved_with_config() {
+        let db = DatabaseManager::new_test().unwrap();
+        let config = db.get_config().unwrap();
+        assert_eq!(config.pause_hotkey, None);
+        assert!(!config.persist_pause);
+
+        db.update_config(&AppConfig {
+            pause_hotkey: Some("Alt+Shift+P".to_string()),
+            persist_pause: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let config = db.get_config().unwrap();
+        assert_eq!(config.pause_hotkey.as_deref(), Some("Alt+Shift+P"));
+        assert!(config.persist_pause);
+
+        db.update_config(&AppConfig::default()).unwrap();
+        assert_eq!(db.get_config().unwrap().pause_hotkey, None);
+    }
+}
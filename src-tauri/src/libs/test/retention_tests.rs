@@ -0,0 +1,241 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        database::DatabaseManager,
+        test::fixtures::make_png,
+        types::{AppConfig, ClipboardContent, ContentType, RetentionPolicy},
+    };
+    use chrono::{Duration, Utc};
+    use serial_test::serial;
+    use std::collections::BTreeMap;
+
+    fn set_policy(db: &DatabaseManager, retention: RetentionPolicy) {
+        db.update_config(&AppConfig {
+            retention,
+            ..Default::default()
+        })
+        .unwrap();
+    }
+
+    // Pretend an item was last copied `days` ago
+    fn age_item(db: &DatabaseManager, content: &str, days: i64) {
+        let last_used = (Utc::now() - Duration::days(days)).to_rfc3339();
+        let conn = db.get_connection().lock().unwrap();
+        conn.execute(
+            "UPDATE clipboard_history SET last_used = ?1 WHERE content = ?2",
+            rusqlite::params![last_used, content],
+        )
+        .unwrap();
+    }
+
+    fn history_texts(db: &DatabaseManager) -> Vec<String> {
+        db.get_clipboard_history()
+            .unwrap()
+            .into_iter()
+            .map(|item| match item.content {
+                ClipboardContent::Text { content } => content,
+                other => format!("{:?}", other.content_type()),
+            })
+            .collect()
+    }
+
+    #[test]
+    #[serial]
+    fn test_default_policy_keeps_everything() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item("old").unwrap();
+        age_item(&db, "old", 3650);
+
+        assert_eq!(db.apply_retention(Utc::now()).unwrap(), 0);
+        assert_eq!(history_texts(&db), vec!["old"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_max_age_deletes_old_unpinned_items() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item("old").unwrap();
+        db.add_clipboard_item("old pinned").unwrap();
+        db.add_clipboard_item("recent").unwrap();
+        let pinned_id = db.get_clipboard_history().unwrap()[1].id;
+        db.toggle_pin(pinned_id).unwrap();
+        age_item(&db, "old", 10);
+        age_item(&db, "old pinned", 10);
+        age_item(&db, "recent", 2);
+
+        set_policy(
+            &db,
+            RetentionPolicy {
+                max_age_days: Some(7),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(db.apply_retention(Utc::now()).unwrap(), 1);
+        assert_eq!(history_texts(&db), vec!["old pinned", "recent"]);
+
+        // Time passing is enough for more items to expire
+        assert_eq!(
+            db.apply_retention(Utc::now() + Duration::days(6)).unwrap(),
+            1
+        );
+        assert_eq!(history_texts(&db), vec!["old pinned"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_per_type_limits() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_image_item(&make_png(4, 4)).unwrap();
+        db.add_image_item(&make_png(5, 5)).unwrap();
+        db.add_clipboard_item("text 1").unwrap();
+        db.add_image_item(&make_png(6, 6)).unwrap();
+        db.add_clipboard_item("text 2").unwrap();
+
+        set_policy(
+            &db,
+            RetentionPolicy {
+                max_items_per_type: BTreeMap::from([(ContentType::Image, 1)]),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(db.apply_retention(Utc::now()).unwrap(), 2);
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 3);
+        // Only the most recently used image is kept
+        let images: Vec<_> = history
+            .iter()
+            .filter_map(|item| match &item.content {
+                ClipboardContent::Image(info) => Some(info.width),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(images, vec![6]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_max_total_bytes_drops_least_recently_used() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item(&"a".repeat(100)).unwrap();
+        db.add_clipboard_item(&"b".repeat(100)).unwrap();
+        db.add_clipboard_item(&"c".repeat(100)).unwrap();
+        // Pinned items are kept, but use up part of the budget
+        db.add_clipboard_item(&"p".repeat(100)).unwrap();
+        let pinned_id = db.get_clipboard_history().unwrap()[0].id;
+        db.toggle_pin(pinned_id).unwrap();
+
+        set_policy(
+            &db,
+            RetentionPolicy {
+                max_total_bytes: Some(250),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(db.apply_retention(Utc::now()).unwrap(), 2);
+        assert_eq!(history_texts(&db), vec!["p".repeat(100), "c".repeat(100)]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_max_total_bytes_keeps_tagged_items() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item(&"t".repeat(100)).unwrap();
+        db.add_clipboard_item(&"a".repeat(100)).unwrap();
+        db.add_clipboard_item(&"b".repeat(100)).unwrap();
+        // The least recently used item is tagged, so it stays and uses up the budget
+        let tagged_id = db.get_clipboard_history().unwrap()[2].id;
+        let tag = db.create_tag("keep").unwrap();
+        db.set_item_tag(tagged_id, tag.id, true).unwrap();
+
+        set_policy(
+            &db,
+            RetentionPolicy {
+                max_total_bytes: Some(250),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(db.apply_retention(Utc::now()).unwrap(), 1);
+        assert_eq!(history_texts(&db), vec!["b".repeat(100), "t".repeat(100)]);
+
+        // The age limit still applies to tagged items
+        set_policy(
+            &db,
+            RetentionPolicy {
+                max_age_days: Some(7),
+                ..Default::default()
+            },
+        );
+        age_item(&db, &"t".repeat(100), 10);
+        assert_eq!(db.apply_retention(Utc::now()).unwrap(), 1);
+        assert_eq!(history_texts(&db), vec!["b".repeat(100)]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_max_total_bytes_counts_images_and_formats() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_image_item(&make_png(64, 64)).unwrap();
+        db.add_clipboard_item("small").unwrap();
+
+        set_policy(
+            &db,
+            RetentionPolicy {
+                max_total_bytes: Some(100),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(db.apply_retention(Utc::now()).unwrap(), 1);
+        assert_eq!(history_texts(&db), vec!["small"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_policy_is_applied_on_insert() {
+        let db = DatabaseManager::new_test().unwrap();
+        set_policy(
+            &db,
+            RetentionPolicy {
+                max_items_per_type: BTreeMap::from([(ContentType::Text, 2)]),
+                ..Default::default()
+            },
+        );
+
+        db.add_clipboard_item("1").unwrap();
+        db.add_clipboard_item("2").unwrap();
+        db.add_clipboard_item("3").unwrap();
+
+        assert_eq!(history_texts(&db), vec!["3", "2"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_policy_saved_with_config() {
+        let db = DatabaseManager::new_test().unwrap();
+        assert_eq!(
+            db.get_config().unwrap().retention,
+            RetentionPolicy::default()
+        );
+
+        let policy = RetentionPolicy {
+            max_age_days: Some(30),
+            max_total_bytes: Some(50 * 1024 * 1024),
+            max_items_per_type: BTreeMap::from([(ContentType::Image, 10), (ContentType::Files, 5)]),
+        };
+        set_policy(&db, policy.clone());
+        assert_eq!(db.get_config().unwrap().retention, policy);
+
+        // Configs saved by older versions have no retention policy
+        let json = r#"{"max_history_count":50,"hotkey":"Alt+V","theme":{"preset":"default"},"autostart":false}"#;
+        let config: AppConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.retention, RetentionPolicy::default());
+
+        let policy: RetentionPolicy =
+            serde_json::from_str(r#"{"max_items_per_type":{"image":3}}"#).unwrap();
+        assert_eq!(policy.max_items_per_type[&ContentType::Image], 3);
+    }
+}
@@ -0,0 +1,264 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        database::DatabaseManager,
+        search::{build_fts_query, file_names, html_to_text, rtf_to_text},
+        test::fixtures::make_png,
+        types::{ClipboardContent, ClipboardSnapshot},
+    };
+    use serial_test::serial;
+
+    #[test]
+    fn test_build_fts_query_prefix_terms() {
+        assert_eq!(build_fts_query("hello wor").unwrap(), "\"hello\"* \"wor\"*");
+    }
+
+    #[test]
+    fn test_build_fts_query_phrase() {
+        assert_eq!(
+            build_fts_query("git \"push origin\"").unwrap(),
+            "\"git\"* \"push origin\""
+        );
+        // Unterminated quote is still treated as a phrase
+        assert_eq!(build_fts_query("\"open phrase").unwrap(), "\"open phrase\"");
+    }
+
+    #[test]
+    fn test_build_fts_query_escapes_operators() {
+        // FTS5 syntax in user input must not be interpreted
+        assert_eq!(
+            build_fts_query("a OR b NOT c*").unwrap(),
+            "\"a\"* \"OR\"* \"b\"* \"NOT\"* \"c*\"*"
+        );
+    }
+
+    #[test]
+    fn test_build_fts_query_empty() {
+        assert_eq!(build_fts_query(""), None);
+        assert_eq!(build_fts_query("   "), None);
+        assert_eq!(build_fts_query("\"\""), None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_search_prefix_match() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item("cargo build --release").unwrap();
+        db.add_clipboard_item("npm install").unwrap();
+
+        let results = db.search_clipboard_history("carg", 50, true).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].item.content,
+            ClipboardContent::text("cargo build --release")
+        );
+        assert!(results[0].snippet.contains("<mark>cargo</mark>"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_search_phrase_match() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item("git push origin main").unwrap();
+        db.add_clipboard_item("origin of git push").unwrap();
+
+        let results = db
+            .search_clipboard_history("\"push origin\"", 50, true)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].item.content,
+            ClipboardContent::text("git push origin main")
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_search_ignores_images() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_image_item(&make_png(4, 4)).unwrap();
+
+        let results = db.search_clipboard_history("image", 50, true).unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_search_finds_html_items() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_snapshot(&ClipboardSnapshot {
+            html: Some("<p class=\"quarterly\">Revenue &amp; <b>growth</b></p>".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let results = db.search_clipboard_history("growth", 50, true).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            results[0].item.content,
+            ClipboardContent::Html { .. }
+        ));
+        assert_eq!(results[0].snippet, "Revenue & <mark>growth</mark>");
+        // Markup is not searchable
+        assert!(db
+            .search_clipboard_history("quarterly", 50, true)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_search_finds_rtf_items() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_snapshot(&ClipboardSnapshot {
+            rtf: Some(
+                "{\\rtf1\\ansi{\\fonttbl{\\f0 Helvetica;}}\\f0\\b Invoice\\b0  overdue\\par}"
+                    .to_string(),
+            ),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let results = db.search_clipboard_history("overdue", 50, true).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            results[0].item.content,
+            ClipboardContent::Rtf { .. }
+        ));
+        assert!(db
+            .search_clipboard_history("helvetica", 50, true)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_search_finds_files_by_name() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_snapshot(&ClipboardSnapshot {
+            files: Some(vec![
+                "file:///home/user/Budget%20Report.xlsx".to_string(),
+                "file:///home/user/notes.txt".to_string(),
+            ]),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let results = db.search_clipboard_history("budget", 50, true).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            results[0].item.content,
+            ClipboardContent::Files { .. }
+        ));
+        assert_eq!(
+            db.search_clipboard_history("notes", 50, true)
+                .unwrap()
+                .len(),
+            1
+        );
+        // Folders are not part of the name
+        assert!(db
+            .search_clipboard_history("home", 50, true)
+            .unwrap()
+            .is_empty());
+
+        let id = results[0].item.id;
+        db.delete_clipboard_item(id).unwrap();
+        assert!(db
+            .search_clipboard_history("budget", 50, true)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_search_text_of_formats() {
+        assert_eq!(
+            html_to_text(
+                "<style>p { color: red }</style><!-- note --><td>a</td><td>b&#233;&#x21;</td><SCRIPT>x()</SCRIPT>&unknown; &lt;"
+            ),
+            "a bé! &unknown; <"
+        );
+        assert_eq!(
+            rtf_to_text("{\\rtf1{\\*\\generator Writer;}caf\\'e9 \\u8364?5\\tab \\{x\\}}"),
+            "café €5 {x}"
+        );
+        assert_eq!(
+            file_names("file:///tmp/a%C3%A9.txt\nfile:///tmp/dir/\nC:\\Users\\b.doc"),
+            "aé.txt dir b.doc"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_search_ranks_better_matches_first() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item("rust is fast and rust is safe, rust")
+            .unwrap();
+        db.add_clipboard_item(
+            "a long sentence that mentions rust only once among many other words",
+        )
+        .unwrap();
+
+        let results = db.search_clipboard_history("rust", 50, false).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0].item.content,
+            ClipboardContent::text("rust is fast and rust is safe, rust")
+        );
+        assert!(results[0].rank <= results[1].rank);
+    }
+
+    #[test]
+    #[serial]
+    fn test_search_pinned_boost() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item("token token token").unwrap();
+        db.add_clipboard_item("token value").unwrap();
+
+        let history = db.get_clipboard_history().unwrap();
+        let weaker = history
+            .iter()
+            .find(|item| item.content == ClipboardContent::text("token value"))
+            .unwrap();
+        db.toggle_pin(weaker.id).unwrap();
+
+        let boosted = db.search_clipboard_history("token", 50, true).unwrap();
+        assert_eq!(
+            boosted[0].item.content,
+            ClipboardContent::text("token value")
+        );
+
+        let unboosted = db.search_clipboard_history("token", 50, false).unwrap();
+        assert_eq!(
+            unboosted[0].item.content,
+            ClipboardContent::text("token token token")
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_search_index_follows_deletes_and_dedup() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item("remove me").unwrap();
+        db.add_clipboard_item("remove me").unwrap();
+
+        let results = db.search_clipboard_history("remove", 50, true).unwrap();
+        assert_eq!(results.len(), 1);
+
+        db.delete_clipboard_item(results[0].item.id).unwrap();
+        let results = db.search_clipboard_history("remove", 50, true).unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_search_limit() {
+        let db = DatabaseManager::new_test().unwrap();
+        for i in 0..5 {
+            db.add_clipboard_item(&format!("limit item {}", i)).unwrap();
+        }
+
+        let results = db.search_clipboard_history("limit", 3, true).unwrap();
+        assert_eq!(results.len(), 3);
+    }
+}
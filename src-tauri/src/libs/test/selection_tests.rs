@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        clipboard::{copy_snippet_impl, wait_until_quiet, ClipboardMonitor},
+        clipboard_backend::{ClipboardBackend, MemoryClipboard, MultiClipboard},
+        database::DatabaseManager,
+        pause::CapturePause,
+        types::{CaptureSettings, HistoryQuery, Selection, SnippetDraft, WriteBack},
+    };
+    use serial_test::serial;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use tokio::sync::Notify;
+
+    #[test]
+    fn test_multi_clipboard_writes_every_backend() {
+        let clipboard = MemoryClipboard::new();
+        let primary = MemoryClipboard::new();
+        primary.write_text("selected".to_string()).unwrap();
+
+        let both = MultiClipboard(vec![&clipboard, &primary]);
+        assert!(both.read_text().is_err());
+        both.write_text("copied".to_string()).unwrap();
+        assert_eq!(clipboard.read_text().unwrap(), "copied");
+        assert_eq!(primary.read_text().unwrap(), "copied");
+
+        // Reads come from the first backend
+        let primary_first = MultiClipboard(vec![&primary, &clipboard]);
+        primary.write_text("selected".to_string()).unwrap();
+        assert_eq!(primary_first.read_text().unwrap(), "selected");
+
+        both.clear().unwrap();
+        assert!(clipboard.read_text().is_err());
+        assert!(primary.read_text().is_err());
+
+        assert!(MultiClipboard(Vec::new())
+            .write_text("lost".to_string())
+            .is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_primary_selection_is_recorded() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let primary = MemoryClipboard::new();
+        let mut clipboard_monitor = ClipboardMonitor::new();
+        let mut primary_monitor = ClipboardMonitor::for_selection(Selection::Primary);
+
+        clipboard.write_text("copied".to_string()).unwrap();
+        primary.write_text("selected".to_string()).unwrap();
+        assert!(clipboard_monitor.poll(&clipboard, &db));
+        assert!(primary_monitor.poll(&primary, &db));
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].selection, Selection::Primary);
+        assert_eq!(history[1].selection, Selection::Clipboard);
+
+        let query = HistoryQuery {
+            selection: Some(Selection::Primary),
+            ..Default::default()
+        };
+        let page = db.query_clipboard_history(&query).unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].id, history[0].id);
+    }
+
+    #[test]
+    #[serial]
+    fn test_recopied_item_takes_the_latest_selection() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let primary = MemoryClipboard::new();
+        let mut clipboard_monitor = ClipboardMonitor::new();
+        let mut primary_monitor = ClipboardMonitor::for_selection(Selection::Primary);
+
+        // Selecting text and then copying it is one item
+        primary.write_text("same".to_string()).unwrap();
+        assert!(primary_monitor.poll(&primary, &db));
+        clipboard.write_text("same".to_string()).unwrap();
+        assert!(clipboard_monitor.poll(&clipboard, &db));
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].selection, Selection::Clipboard);
+    }
+
+    #[test]
+    #[serial]
+    fn test_selection_settings() {
+        let db = DatabaseManager::new_test().unwrap();
+        let mut config = db.get_config().unwrap();
+        assert!(!config.capture.primary_selection);
+        assert_eq!(config.capture.selection_debounce_ms, 300);
+        assert_eq!(config.capture.write_back, WriteBack::Clipboard);
+
+        config.capture.primary_selection = true;
+        config.capture.write_back = WriteBack::Both;
+        db.update_config(&config).unwrap();
+        let settings = db.get_capture_settings().unwrap();
+        assert!(settings.primary_selection);
+        assert_eq!(settings.write_back, WriteBack::Both);
+
+        let too_slow = CaptureSettings {
+            selection_debounce_ms: 5001,
+            ..Default::default()
+        };
+        assert!(too_slow.validate().is_err());
+
+        let json = serde_json::to_value(&settings).unwrap();
+        assert_eq!(json["write_back"], "both");
+    }
+
+    #[test]
+    #[serial]
+    fn test_snippet_written_to_primary_reads_the_clipboard() {
+        let db = DatabaseManager::new_test().unwrap();
+        let snippet = db
+            .create_snippet(&SnippetDraft {
+                name: "Quote".to_string(),
+                abbreviation: None,
+                content: "> {clipboard}".to_string(),
+                folder_id: None,
+            })
+            .unwrap();
+        let clipboard = MemoryClipboard::new();
+        let primary = MemoryClipboard::new();
+        clipboard.write_text("copied".to_string()).unwrap();
+        primary.write_text("selected".to_string()).unwrap();
+
+        copy_snippet_impl(snippet.id, &HashMap::new(), &db, &clipboard, &primary).unwrap();
+        assert_eq!(primary.read_text().unwrap(), "> copied");
+        assert_eq!(clipboard.read_text().unwrap(), "copied");
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_quiet() {
+        let changed = Arc::new(Notify::new());
+        let notify = changed.clone();
+        let selecting = tokio::spawn(async move {
+            for _ in 0..5 {
+                notify.notify_one();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        });
+
+        let start = Instant::now();
+        wait_until_quiet(&changed, Duration::from_millis(100)).await;
+        // Waited for the last change, then for the quiet time
+        assert!(start.elapsed() >= Duration::from_millis(180));
+        assert!(selecting.is_finished());
+    }
+
+    #[tokio::test]
+    async fn test_pause_wakes_every_subscriber() {
+        let pause = CapturePause::default();
+        let first = pause.subscribe();
+        let second = pause.subscribe();
+
+        pause.pause(None);
+        for subscriber in [first, second] {
+            tokio::time::timeout(Duration::from_secs(1), subscriber.notified())
+                .await
+                .expect("pausing notifies");
+        }
+        tokio::time::timeout(Duration::from_secs(1), pause.changed())
+            .await
+            .expect("pausing notifies");
+    }
+}
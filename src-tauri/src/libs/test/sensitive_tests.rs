@@ -0,0 +1,261 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        clipboard::ClipboardMonitor,
+        clipboard_backend::{ClipboardBackend, MemoryClipboard},
+        database::DatabaseManager,
+        janitor::expire_items,
+        test::fixtures::make_png,
+        types::{AppConfig, ClipboardContent, SecretKind, Selection, SensitiveRules},
+    };
+    use chrono::{DateTime, Duration, Utc};
+    use serial_test::serial;
+
+    fn expiry_of(db: &DatabaseManager, content: &str) -> Option<DateTime<Utc>> {
+        db.get_clipboard_history()
+            .unwrap()
+            .into_iter()
+            .find(|item| item.content == ClipboardContent::text(content))
+            .expect("item is in the history")
+            .expires_at
+            .map(|at| DateTime::parse_from_rfc3339(&at).unwrap().to_utc())
+    }
+
+    #[test]
+    #[serial]
+    fn test_one_time_codes_expire() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        clipboard.write_text("482 913".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        clipboard
+            .write_text("call me at 0312345678".to_string())
+            .unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+
+        let expires_at = expiry_of(&db, "482 913").expect("codes are sensitive");
+        let expected = Utc::now() + Duration::seconds(120);
+        assert!((expected - expires_at).num_seconds().abs() < 5);
+        assert_eq!(expiry_of(&db, "call me at 0312345678"), None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_custom_sensitive_patterns() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.update_config(&AppConfig {
+            sensitive: SensitiveRules {
+                patterns: vec!["^tmp-pass:".to_string()],
+                detectors: Vec::new(),
+                expire_after_secs: 30,
+            },
+            ..Default::default()
+        })
+        .unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        clipboard
+            .write_text("tmp-pass: hunter2".to_string())
+            .unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        clipboard.write_text("123456".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+
+        let expires_at = expiry_of(&db, "tmp-pass: hunter2").unwrap();
+        assert!(expires_at <= Utc::now() + Duration::seconds(30));
+        assert_eq!(
+            expiry_of(&db, "123456"),
+            None,
+            "disabled detectors do not apply"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_take_expired_items() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item("expired").unwrap();
+        db.add_clipboard_item("pinned").unwrap();
+        db.add_clipboard_item("later").unwrap();
+        db.add_clipboard_item("kept").unwrap();
+        let history = db.get_clipboard_history().unwrap();
+        let id_of = |content: &str| {
+            history
+                .iter()
+                .find(|item| item.content == ClipboardContent::text(content))
+                .unwrap()
+                .id
+        };
+
+        let now = Utc::now();
+        db.set_item_expiry(id_of("expired"), Some(now - Duration::seconds(1)))
+            .unwrap();
+        db.set_item_expiry(id_of("pinned"), Some(now - Duration::seconds(1)))
+            .unwrap();
+        db.toggle_pin(id_of("pinned")).unwrap();
+        db.set_item_expiry(id_of("later"), Some(now + Duration::minutes(1)))
+            .unwrap();
+
+        let expired = db.take_expired_items(now).unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].text.as_deref(), Some("expired"));
+        assert_eq!(db.get_clipboard_history().unwrap().len(), 3);
+
+        // Pinned items never expire
+        let expired = db.take_expired_items(now + Duration::minutes(2)).unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].text.as_deref(), Some("later"));
+        assert!(db.take_expired_items(now).unwrap().is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_mark_and_unmark_sensitive() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item("temporary password").unwrap();
+        let id = db.get_clipboard_history().unwrap()[0].id;
+
+        let at = Utc::now() + Duration::minutes(1);
+        assert!(db.set_item_expiry(id, Some(at)).unwrap());
+        assert_eq!(
+            expiry_of(&db, "temporary password").unwrap().timestamp(),
+            at.timestamp()
+        );
+
+        assert!(db.set_item_expiry(id, None).unwrap());
+        assert_eq!(expiry_of(&db, "temporary password"), None);
+
+        assert!(!db.set_item_expiry(id + 100, None).unwrap());
+    }
+
+    #[test]
+    #[serial]
+    fn test_copying_again_keeps_expiry() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        clipboard.write_text("secret".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        let id = db.get_clipboard_history().unwrap()[0].id;
+        let at = Utc::now() + Duration::minutes(1);
+        db.set_item_expiry(id, Some(at)).unwrap();
+
+        clipboard.write_text("other".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        clipboard.write_text("secret".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+
+        assert_eq!(
+            expiry_of(&db, "secret").unwrap().timestamp(),
+            at.timestamp()
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_expired_item_is_cleared_from_clipboard() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        clipboard.write_text("123456".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+
+        let later = Utc::now() + Duration::minutes(5);
+        assert_eq!(expire_items(&db, &[&clipboard], Utc::now()).unwrap(), 0);
+        assert_eq!(expire_items(&db, &[&clipboard], later).unwrap(), 1);
+        assert!(db.get_clipboard_history().unwrap().is_empty());
+        assert!(clipboard.read_text().is_err(), "clipboard was emptied");
+
+        // The emptied clipboard is not recorded
+        assert!(!monitor.poll(&clipboard, &db));
+    }
+
+    #[test]
+    #[serial]
+    fn test_expired_item_is_cleared_from_primary_selection() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let primary = MemoryClipboard::new();
+        let mut primary_monitor = ClipboardMonitor::for_selection(Selection::Primary);
+
+        clipboard.write_text("unrelated".to_string()).unwrap();
+        primary.write_text("123456".to_string()).unwrap();
+        assert!(primary_monitor.poll(&primary, &db));
+
+        let later = Utc::now() + Duration::minutes(5);
+        assert_eq!(
+            expire_items(&db, &[&clipboard, &primary], later).unwrap(),
+            1
+        );
+        assert!(
+            primary.read_text().is_err(),
+            "primary selection was emptied"
+        );
+        assert_eq!(clipboard.read_text().unwrap(), "unrelated");
+    }
+
+    #[test]
+    #[serial]
+    fn test_newer_clipboard_contents_are_left_alone() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        clipboard.write_text("123456".to_string()).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        clipboard.write_text("something else".to_string()).unwrap();
+
+        let later = Utc::now() + Duration::minutes(5);
+        assert_eq!(expire_items(&db, &[&clipboard], later).unwrap(), 1);
+        assert_eq!(clipboard.read_text().unwrap(), "something else");
+    }
+
+    #[test]
+    #[serial]
+    fn test_expired_image_is_cleared_from_clipboard() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        clipboard.write_image(make_png(8, 8)).unwrap();
+        assert!(monitor.poll(&clipboard, &db));
+        let id = db.get_clipboard_history().unwrap()[0].id;
+        db.set_item_expiry(id, Some(Utc::now())).unwrap();
+
+        let later = Utc::now() + Duration::seconds(1);
+        assert_eq!(expire_items(&db, &[&clipboard], later).unwrap(), 1);
+        assert!(clipboard.read_image().is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_rules_saved_with_config() {
+        let db = DatabaseManager::new_test().unwrap();
+        let defaults = db.get_config().unwrap().sensitive;
+        assert_eq!(defaults, SensitiveRules::default());
+        assert_eq!(defaults.detectors, vec![SecretKind::OneTimeCode]);
+
+        let rules = SensitiveRules {
+            patterns: vec!["otp".to_string()],
+            detectors: vec![SecretKind::Jwt],
+            expire_after_secs: 600,
+        };
+        db.update_config(&AppConfig {
+            sensitive: rules.clone(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(db.get_config().unwrap().sensitive, rules);
+
+        let config: AppConfig = serde_json::from_str(
+            r#"{"max_history_count":50,"hotkey":"Alt+V","theme":{"preset":"default"},"autostart":false}"#,
+        )
+        .unwrap();
+        assert_eq!(config.sensitive, SensitiveRules::default());
+    }
+}
@@ -0,0 +1,303 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        clipboard::{copy_snippet_impl, snippet_fields},
+        clipboard_backend::{ClipboardBackend, MemoryClipboard},
+        database::DatabaseManager,
+        test::fixtures::make_png,
+        types::{AppConfig, ContentType, RetentionPolicy, SnippetDraft},
+    };
+    use chrono::{Duration, Utc};
+    use serial_test::serial;
+    use std::collections::{BTreeMap, HashMap};
+
+    fn draft(name: &str, content: &str) -> SnippetDraft {
+        SnippetDraft {
+            name: name.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn snippet_names(db: &DatabaseManager) -> Vec<String> {
+        db.list_snippets()
+            .unwrap()
+            .into_iter()
+            .map(|snippet| snippet.name)
+            .collect()
+    }
+
+    #[test]
+    #[serial]
+    fn test_create_and_list_snippets() {
+        let db = DatabaseManager::new_test().unwrap();
+        let folder = db.create_snippet_folder("Email").unwrap();
+
+        let signature = db
+            .create_snippet(&SnippetDraft {
+                name: " Signature ".to_string(),
+                abbreviation: Some(" sig ".to_string()),
+                content: "Best regards,\nAlex".to_string(),
+                folder_id: Some(folder.id),
+            })
+            .unwrap();
+        assert_eq!(signature.name, "Signature");
+        assert_eq!(signature.abbreviation.as_deref(), Some("sig"));
+        assert_eq!(signature.folder_id, Some(folder.id));
+        assert_eq!(signature.created_at, signature.updated_at);
+
+        let address = db.create_snippet(&draft("address", "1 Main St")).unwrap();
+        assert_eq!(address.abbreviation, None);
+        db.create_snippet(&SnippetDraft {
+            abbreviation: Some("  ".to_string()),
+            ..draft("Bank details", "IBAN")
+        })
+        .unwrap();
+
+        // Snippets outside a folder come first, then by name
+        assert_eq!(
+            snippet_names(&db),
+            vec!["address", "Bank details", "Signature"]
+        );
+        assert_eq!(db.get_snippet(signature.id).unwrap(), Some(signature));
+    }
+
+    #[test]
+    #[serial]
+    fn test_snippets_are_validated() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.create_snippet(&SnippetDraft {
+            abbreviation: Some("sig".to_string()),
+            ..draft("Signature", "Regards")
+        })
+        .unwrap();
+
+        assert!(db.create_snippet(&draft(" ", "content")).is_err());
+        assert!(db.create_snippet(&draft("Empty", "")).is_err());
+        let spaced = SnippetDraft {
+            abbreviation: Some("my sig".to_string()),
+            ..draft("Other", "text")
+        };
+        assert!(db.create_snippet(&spaced).is_err());
+        let duplicate = SnippetDraft {
+            abbreviation: Some("SIG".to_string()),
+            ..draft("Other", "text")
+        };
+        let error = db.create_snippet(&duplicate).unwrap_err().to_string();
+        assert!(error.contains("already uses"), "{}", error);
+        let missing_folder = SnippetDraft {
+            folder_id: Some(42),
+            ..draft("Other", "text")
+        };
+        assert!(db.create_snippet(&missing_folder).is_err());
+        let error = db
+            .create_snippet(&draft("Dated", "Today is {date:%Q}"))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Invalid placeholder"), "{}", error);
+
+        assert_eq!(db.list_snippets().unwrap().len(), 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_update_and_delete_snippets() {
+        let db = DatabaseManager::new_test().unwrap();
+        let snippet = db.create_snippet(&draft("Greeting", "Hello")).unwrap();
+
+        let updated = db
+            .update_snippet(
+                snippet.id,
+                &SnippetDraft {
+                    abbreviation: Some("hi".to_string()),
+                    ..draft("Greeting", "Hello there")
+                },
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.content, "Hello there");
+        assert_eq!(updated.abbreviation.as_deref(), Some("hi"));
+        assert_eq!(updated.created_at, snippet.created_at);
+        assert!(updated.updated_at >= snippet.updated_at);
+        assert_eq!(
+            db.update_snippet(snippet.id + 1, &draft("x", "y")).unwrap(),
+            None
+        );
+
+        assert!(db.delete_snippet(snippet.id).unwrap());
+        assert!(!db.delete_snippet(snippet.id).unwrap());
+        assert!(db.list_snippets().unwrap().is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_create_snippet_from_item() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item(
+            "\n  SELECT * FROM orders WHERE shipped_at IS NULL ORDER BY id\nLIMIT 10",
+        )
+        .unwrap();
+        db.add_image_item(&make_png(4, 4)).unwrap();
+        let history = db.get_clipboard_history().unwrap();
+        let image_id = history[0].id;
+        let text_id = history[1].id;
+
+        let snippet = db.create_snippet_from_item(text_id, None).unwrap().unwrap();
+        assert_eq!(snippet.name, "SELECT * FROM orders WHERE shipped_at IS…");
+        assert!(snippet.content.ends_with("LIMIT 10"));
+
+        let named = db
+            .create_snippet_from_item(text_id, Some("Unshipped orders"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(named.name, "Unshipped orders");
+
+        assert!(db.create_snippet_from_item(image_id, None).is_err());
+        assert_eq!(
+            db.create_snippet_from_item(text_id + 100, None).unwrap(),
+            None
+        );
+
+        // The snippet does not depend on the history item
+        db.delete_clipboard_item(text_id).unwrap();
+        assert_eq!(db.list_snippets().unwrap().len(), 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_snippets_are_not_touched_by_retention() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.update_config(&AppConfig {
+            max_history_count: 1,
+            retention: RetentionPolicy {
+                max_age_days: Some(1),
+                max_total_bytes: Some(1),
+                max_items_per_type: BTreeMap::from([(ContentType::Text, 1)]),
+            },
+            ..Default::default()
+        })
+        .unwrap();
+        db.create_snippet(&draft("Kept", "same text as history"))
+            .unwrap();
+
+        db.add_clipboard_item("same text as history").unwrap();
+        db.add_clipboard_item("newer").unwrap();
+        db.apply_retention(Utc::now() + Duration::days(30)).unwrap();
+
+        assert!(db.get_clipboard_history().unwrap().is_empty());
+        assert_eq!(snippet_names(&db), vec!["Kept"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_snippet_folders() {
+        let db = DatabaseManager::new_test().unwrap();
+        let work = db.create_snippet_folder("Work").unwrap();
+        let home = db.create_snippet_folder("home").unwrap();
+        assert_eq!(
+            db.list_snippet_folders().unwrap(),
+            vec![home.clone(), work.clone()]
+        );
+
+        assert!(db.create_snippet_folder("work").is_err());
+        assert!(db.rename_snippet_folder(home.id, "WORK").is_err());
+        assert!(db.rename_snippet_folder(home.id, "Personal").unwrap());
+        assert!(!db.rename_snippet_folder(home.id + 100, "Other").unwrap());
+
+        let snippet = db
+            .create_snippet(&SnippetDraft {
+                folder_id: Some(work.id),
+                ..draft("Standup", "Yesterday / Today / Blockers")
+            })
+            .unwrap();
+        assert!(db.delete_snippet_folder(work.id).unwrap());
+        assert!(!db.delete_snippet_folder(work.id).unwrap());
+
+        // Snippets of a deleted folder are kept
+        let snippet = db.get_snippet(snippet.id).unwrap().unwrap();
+        assert_eq!(snippet.folder_id, None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_search_snippets() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.create_snippet(&draft("Signature", "Best regards from the team"))
+            .unwrap();
+        db.create_snippet(&SnippetDraft {
+            abbreviation: Some("addr".to_string()),
+            ..draft("Office", "1 Main Street")
+        })
+        .unwrap();
+        db.create_snippet(&draft("addresses", "addr book export"))
+            .unwrap();
+
+        let results = db.search_snippets("regards", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].snippet.name, "Signature");
+        assert!(results[0].excerpt.contains("<mark>regards</mark>"));
+
+        // Names are searched too
+        assert_eq!(db.search_snippets("signat", 10).unwrap().len(), 1);
+
+        // An exact abbreviation match comes first
+        let results = db.search_snippets("ADDR", 10).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].snippet.name, "Office");
+        assert_eq!(db.search_snippets("ADDR", 1).unwrap().len(), 1);
+
+        assert!(db.search_snippets("   ", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_copy_snippet() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let snippet = db.create_snippet(&draft("Greeting", "Hello!")).unwrap();
+
+        let no_fields = HashMap::new();
+        copy_snippet_impl(snippet.id, &no_fields, &db, &clipboard, &clipboard).unwrap();
+        assert_eq!(clipboard.read_text().unwrap(), "Hello!");
+
+        assert!(
+            copy_snippet_impl(snippet.id + 1, &no_fields, &db, &clipboard, &clipboard).is_err()
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_copy_snippet_expands_placeholders() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let snippet = db
+            .create_snippet(&draft(
+                "Reply",
+                "Hi {field:Name}, about \"{clipboard}\": {field:Answer} ({date:%Y})",
+            ))
+            .unwrap();
+        assert_eq!(
+            snippet_fields(snippet.id, &db).unwrap(),
+            vec!["Name", "Answer"]
+        );
+
+        clipboard.write_text("the report".to_string()).unwrap();
+        let fields = HashMap::from([
+            ("Name".to_string(), "Sam".to_string()),
+            ("Answer".to_string(), "done".to_string()),
+        ]);
+        copy_snippet_impl(snippet.id, &fields, &db, &clipboard, &clipboard).unwrap();
+        let year = chrono::Local::now().format("%Y").to_string();
+        assert_eq!(
+            clipboard.read_text().unwrap(),
+            format!("Hi Sam, about \"the report\": done ({})", year)
+        );
+
+        // A missing field leaves the clipboard alone
+        let error = copy_snippet_impl(snippet.id, &HashMap::new(), &db, &clipboard, &clipboard)
+            .unwrap_err();
+        assert_eq!(error.code(), "invalid-input");
+        assert!(error.to_string().contains("Name"), "{}", error);
+        assert!(clipboard.read_text().unwrap().starts_with("Hi Sam"));
+    }
+}
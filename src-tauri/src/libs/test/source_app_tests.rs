@@ -0,0 +1,204 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        clipboard::ClipboardMonitor,
+        clipboard_backend::{ClipboardBackend, MemoryClipboard},
+        database::DatabaseManager,
+        exclusion::{AppMatcher, ExclusionFilter, ExclusionReason},
+        types::{AppConfig, ClipboardContent, ExclusionRules, HistoryQuery, SourceApp},
+    };
+    use serial_test::serial;
+
+    fn app(process: &str, class: &str) -> SourceApp {
+        SourceApp {
+            process: Some(process.to_string()),
+            class: Some(class.to_string()),
+            title: Some(format!("{} window", class)),
+        }
+    }
+
+    fn copy_in(
+        source_app: Option<SourceApp>,
+        text: &str,
+        clipboard: &MemoryClipboard,
+        monitor: &mut ClipboardMonitor,
+        db: &DatabaseManager,
+    ) -> bool {
+        clipboard.set_source_app(source_app);
+        clipboard.write_text(text.to_string()).unwrap();
+        monitor.poll(clipboard, db)
+    }
+
+    #[test]
+    fn test_source_app_names() {
+        let named = |source_app: &SourceApp, name: &str| {
+            AppMatcher::parse(name).unwrap().matches(source_app)
+        };
+        let firefox = app("firefox", "Firefox");
+        assert!(named(&firefox, "FIREFOX"));
+        assert!(!named(&firefox, "fire"));
+
+        // Windows without a known process are named by their class
+        let class_only = SourceApp {
+            class: Some("XTerm".to_string()),
+            ..Default::default()
+        };
+        assert!(named(&class_only, "xterm"));
+        assert!(!named(&SourceApp::default(), "xterm"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_source_app_is_recorded() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        assert!(copy_in(
+            Some(app("firefox", "Firefox")),
+            "from the browser",
+            &clipboard,
+            &mut monitor,
+            &db
+        ));
+        assert!(copy_in(None, "from nowhere", &clipboard, &mut monitor, &db));
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history[0].source_app, None);
+        assert_eq!(history[1].source_app, Some(app("firefox", "Firefox")));
+
+        let json = serde_json::to_value(&history[1]).unwrap();
+        assert_eq!(json["source_app"]["process"], "firefox");
+        assert_eq!(json["source_app"]["title"], "Firefox window");
+    }
+
+    #[test]
+    #[serial]
+    fn test_recopied_item_takes_the_latest_source_app() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+        let editor = app("code", "Code");
+
+        copy_in(
+            Some(app("firefox", "Firefox")),
+            "shared",
+            &clipboard,
+            &mut monitor,
+            &db,
+        );
+        copy_in(None, "other", &clipboard, &mut monitor, &db);
+        copy_in(
+            Some(editor.clone()),
+            "shared",
+            &clipboard,
+            &mut monitor,
+            &db,
+        );
+        copy_in(None, "other", &clipboard, &mut monitor, &db);
+        // Not knowing the source of a later copy keeps the known one
+        copy_in(None, "shared", &clipboard, &mut monitor, &db);
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].content, ClipboardContent::text("shared"));
+        assert_eq!(history[0].copy_count, 3);
+        assert_eq!(history[0].source_app, Some(editor));
+    }
+
+    #[test]
+    #[serial]
+    fn test_query_by_source_app() {
+        let db = DatabaseManager::new_test().unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+        copy_in(
+            Some(app("firefox", "Firefox")),
+            "a",
+            &clipboard,
+            &mut monitor,
+            &db,
+        );
+        copy_in(
+            Some(app("code", "Code")),
+            "b",
+            &clipboard,
+            &mut monitor,
+            &db,
+        );
+        copy_in(None, "c", &clipboard, &mut monitor, &db);
+
+        let texts = |source_app: &str| -> Vec<ClipboardContent> {
+            let query = HistoryQuery {
+                source_app: Some(source_app.to_string()),
+                ..Default::default()
+            };
+            let page = db.query_clipboard_history(&query).unwrap();
+            page.items.into_iter().map(|item| item.content).collect()
+        };
+        assert_eq!(texts("firefox"), vec![ClipboardContent::text("a")]);
+        // By window class too, ignoring case
+        assert_eq!(texts("CODE"), vec![ClipboardContent::text("b")]);
+        assert!(texts("fire").is_empty());
+    }
+
+    #[test]
+    fn test_check_app() {
+        let filter = ExclusionFilter::new(&ExclusionRules {
+            apps: vec![
+                "  ".to_string(),
+                "KeePassXC".to_string(),
+                "Remmina".to_string(),
+            ],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let keepass = app("keepassxc", "KeePassXC");
+        assert_eq!(
+            filter.check_app(Some(&keepass)),
+            Some(ExclusionReason::App(1))
+        );
+        let remmina = SourceApp {
+            class: Some("org.remmina.Remmina".to_string()),
+            process: Some("remmina".to_string()),
+            title: None,
+        };
+        assert_eq!(
+            filter.check_app(Some(&remmina)),
+            Some(ExclusionReason::App(2))
+        );
+        assert_eq!(filter.check_app(Some(&app("firefox", "Firefox"))), None);
+        assert_eq!(filter.check_app(None), None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_copies_from_excluded_apps_are_not_recorded() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.update_config(&AppConfig {
+            exclusion_rules: ExclusionRules {
+                apps: vec!["keepassxc".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap();
+        let clipboard = MemoryClipboard::new();
+        let mut monitor = ClipboardMonitor::new();
+
+        let keepass = Some(app("keepassxc", "KeePassXC"));
+        assert!(!copy_in(keepass, "hunter2", &clipboard, &mut monitor, &db));
+        assert!(copy_in(
+            Some(app("firefox", "Firefox")),
+            "kept",
+            &clipboard,
+            &mut monitor,
+            &db
+        ));
+
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].content, ClipboardContent::text("kept"));
+    }
+}
@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        backup::{move_aside, open_or_recover},
+        constants::STARTUP_LOG_FILE_NAME,
+        database::DatabaseManager,
+        encryption::{change_encryption, open_database, KeySource},
+        migrations::MigrationError,
+        setup::{log_startup_failure, reset_config, StartupError, StartupFailureState},
+        types::{AppConfig, PauseStatus, StartupAction, StartupErrorCode},
+    };
+    use chrono::{DateTime, Utc};
+    use serial_test::serial;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_error_codes_and_actions() {
+        let data_dir = StartupError::DataDir("Failed to create /data: denied".to_string());
+        assert_eq!(data_dir.code(), StartupErrorCode::DataDir);
+        assert_eq!(data_dir.actions(), vec![StartupAction::Retry]);
+        assert_eq!(data_dir.to_string(), "Failed to create /data: denied");
+
+        let damaged = StartupError::Database(MigrationError::Corrupt("bad page".to_string()));
+        assert_eq!(damaged.code(), StartupErrorCode::Database);
+        assert_eq!(
+            damaged.actions(),
+            vec![StartupAction::Retry, StartupAction::MoveDatabaseAside]
+        );
+
+        let too_new = StartupError::Database(MigrationError::SchemaTooNew {
+            found: 99,
+            supported: 9,
+        });
+        assert_eq!(too_new.code(), StartupErrorCode::DatabaseTooNew);
+
+        let config = StartupError::Config(rusqlite::Error::QueryReturnedNoRows.into());
+        assert_eq!(config.code(), StartupErrorCode::Config);
+        assert!(config.actions().contains(&StartupAction::ResetConfig));
+        assert!(config.to_string().starts_with("Failed to load settings"));
+    }
+
+    #[test]
+    fn test_failure_is_serialized_for_the_window() {
+        let error = StartupError::Database(MigrationError::SchemaTooNew {
+            found: 99,
+            supported: 9,
+        });
+        let failure = error.to_failure(Some(Path::new("/data/clipray")));
+        assert_eq!(failure.message, error.to_string());
+        assert_eq!(failure.data_dir.as_deref(), Some("/data/clipray"));
+
+        let json = serde_json::to_value(&failure).unwrap();
+        assert_eq!(json["code"], "database-too-new");
+        assert_eq!(
+            json["actions"],
+            serde_json::json!(["retry", "move-database-aside"])
+        );
+
+        let unknown_dir = StartupError::DataDir("No home directory".to_string()).to_failure(None);
+        assert_eq!(unknown_dir.data_dir, None);
+    }
+
+    #[test]
+    fn test_startup_log_is_appended() {
+        let dir = tempdir().unwrap();
+        let data_dir = dir.path().join("not-created-yet");
+        log_startup_failure(&data_dir, &StartupError::DataDir("first".to_string())).unwrap();
+        log_startup_failure(
+            &data_dir,
+            &StartupError::Database(MigrationError::Corrupt("second".to_string())),
+        )
+        .unwrap();
+
+        let log = fs::read_to_string(data_dir.join(STARTUP_LOG_FILE_NAME)).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" first"), "{}", lines[0]);
+        assert!(lines[1].ends_with("The database is damaged: second"));
+        let (time, _) = lines[0].split_once(' ').unwrap();
+        assert!(DateTime::parse_from_rfc3339(time).is_ok(), "{}", time);
+    }
+
+    #[test]
+    #[serial]
+    fn test_reset_config() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.add_clipboard_item("kept").unwrap();
+        db.update_config(&AppConfig {
+            max_history_count: 3,
+            hotkey: "Alt+V".to_string(),
+            persist_pause: true,
+            ..Default::default()
+        })
+        .unwrap();
+        db.save_pause_status(&PauseStatus {
+            paused: true,
+            until: None,
+        })
+        .unwrap();
+
+        db.reset_config().unwrap();
+        let config = db.get_config().unwrap();
+        let defaults = AppConfig::default();
+        assert_eq!(config.max_history_count, defaults.max_history_count);
+        assert_eq!(config.hotkey, defaults.hotkey);
+        assert!(!config.persist_pause);
+        assert_eq!(db.get_pause_status().unwrap(), PauseStatus::default());
+        assert_eq!(db.get_clipboard_history().unwrap().len(), 1);
+
+        // Settings can still be changed afterwards
+        db.update_config(&AppConfig {
+            hotkey: "Alt+C".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(db.get_config().unwrap().hotkey, "Alt+C");
+    }
+
+    #[test]
+    #[serial]
+    fn test_reset_config_waits_for_the_passphrase() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        let custom = AppConfig {
+            hotkey: "Alt+V".to_string(),
+            ..Default::default()
+        };
+
+        let db = open_database(&db_path, None).unwrap();
+        db.update_config(&custom).unwrap();
+        drop(db);
+        assert!(reset_config(&db_path).unwrap());
+        let db = open_database(&db_path, None).unwrap();
+        assert_eq!(db.get_config().unwrap().hotkey, AppConfig::default().hotkey);
+
+        // An encrypted database is left as it is until it is unlocked
+        db.update_config(&custom).unwrap();
+        change_encryption(&db, KeySource::Passphrase("secret".to_string())).unwrap();
+        drop(db);
+        assert!(!reset_config(&db_path).unwrap());
+        let db = open_database(&db_path, Some("secret")).unwrap();
+        assert_eq!(db.get_config().unwrap().hotkey, "Alt+V");
+
+        let startup = StartupFailureState::default();
+        assert!(!startup.take_config_reset());
+        startup.defer_config_reset();
+        assert!(startup.take_config_reset());
+        assert!(!startup.take_config_reset(), "the reset is done once");
+    }
+
+    #[test]
+    #[serial]
+    fn test_moved_database_starts_fresh() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("clipray.db");
+        open_database(&db_path, None)
+            .unwrap()
+            .add_clipboard_item("old")
+            .unwrap();
+
+        let now = DateTime::parse_from_rfc3339("2024-05-01T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let moved = move_aside(&db_path, "moved", now).unwrap();
+        assert_eq!(moved, dir.path().join("clipray.db.moved-20240501-080000"));
+        assert!(!db_path.exists());
+
+        let (db, notice) = open_or_recover(&db_path, None).unwrap();
+        assert_eq!(notice, None);
+        assert!(db.get_clipboard_history().unwrap().is_empty());
+
+        // The moved file is still a working database
+        let old = open_database(&moved, None).unwrap();
+        assert_eq!(old.get_clipboard_history().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::{
+        database::DatabaseManager,
+        types::{AppConfig, ClipboardContent, ContentType, HistoryQuery, RetentionPolicy},
+    };
+    use serial_test::serial;
+    use std::collections::BTreeMap;
+
+    fn id_of(db: &DatabaseManager, content: &str) -> i64 {
+        db.get_clipboard_history()
+            .unwrap()
+            .into_iter()
+            .find(|item| item.content == ClipboardContent::text(content))
+            .expect("item is in the history")
+            .id
+    }
+
+    fn history_texts(db: &DatabaseManager) -> Vec<String> {
+        db.get_clipboard_history()
+            .unwrap()
+            .into_iter()
+            .filter_map(|item| item.content.as_text().map(String::from))
+            .collect()
+    }
+
+    #[test]
+    #[serial]
+    fn test_create_and_list_tags() {
+        let db = DatabaseManager::new_test().unwrap();
+        assert!(db.list_tags().unwrap().is_empty());
+
+        let work = db.create_tag("  work ").unwrap();
+        let code = db.create_tag("code").unwrap();
+        assert_eq!(work.name, "work");
+
+        let tags = db.list_tags().unwrap();
+        assert_eq!(tags, vec![code, work]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_tag_names_are_validated() {
+        let db = DatabaseManager::new_test().unwrap();
+        let work = db.create_tag("work").unwrap();
+        let code = db.create_tag("code").unwrap();
+
+        let error = db.create_tag("Work").unwrap_err().to_string();
+        assert!(error.contains("already exists"), "{}", error);
+        assert!(db.create_tag("   ").is_err());
+        assert!(db.rename_tag(code.id, "WORK").is_err());
+        assert!(db.rename_tag(work.id, "").is_err());
+        assert_eq!(db.list_tags().unwrap().len(), 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_rename_and_delete_tags() {
+        let db = DatabaseManager::new_test().unwrap();
+        let tag = db.create_tag("work").unwrap();
+        db.add_clipboard_item("meeting notes").unwrap();
+        let item_id = id_of(&db, "meeting notes");
+        db.set_item_tag(item_id, tag.id, true).unwrap();
+
+        assert!(db.rename_tag(tag.id, "Work").unwrap());
+        assert_eq!(db.list_tags().unwrap()[0].name, "Work");
+        assert!(!db.rename_tag(tag.id + 1, "other").unwrap());
+
+        assert!(db.delete_tag(tag.id).unwrap());
+        assert!(!db.delete_tag(tag.id).unwrap());
+        assert!(db.list_tags().unwrap().is_empty());
+        // The item stays, without the tag
+        let history = db.get_clipboard_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert!(history[0].tag_ids.is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_assign_and_remove_tags() {
+        let db = DatabaseManager::new_test().unwrap();
+        let work = db.create_tag("work").unwrap();
+        let code = db.create_tag("code").unwrap();
+        db.add_clipboard_item("snippet").unwrap();
+        let item_id = id_of(&db, "snippet");
+
+        assert!(db.set_item_tag(item_id, work.id, true).unwrap());
+        assert!(db.set_item_tag(item_id, code.id, true).unwrap());
+        // Assigning twice is harmless
+        assert!(db.set_item_tag(item_id, code.id, true).unwrap());
+        let item = &db.get_clipboard_history().unwrap()[0];
+        assert_eq!(item.tag_ids, vec![work.id, code.id]);
+
+        assert!(db.set_item_tag(item_id, work.id, false).unwrap());
+        let item = &db.get_clipboard_history().unwrap()[0];
+        assert_eq!(item.tag_ids, vec![code.id]);
+
+        assert!(!db.set_item_tag(item_id + 100, code.id, true).unwrap());
+        assert!(!db.set_item_tag(item_id, code.id + 100, true).unwrap());
+    }
+
+    #[test]
+    #[serial]
+    fn test_filter_history_by_tag() {
+        let db = DatabaseManager::new_test().unwrap();
+        let work = db.create_tag("work").unwrap();
+        let home = db.create_tag("home").unwrap();
+        db.add_clipboard_item("report").unwrap();
+        db.add_clipboard_item("groceries").unwrap();
+        db.add_clipboard_item("untagged").unwrap();
+        db.set_item_tag(id_of(&db, "report"), work.id, true)
+            .unwrap();
+        db.set_item_tag(id_of(&db, "groceries"), home.id, true)
+            .unwrap();
+
+        let page = db
+            .query_clipboard_history(&HistoryQuery {
+                tag_id: Some(work.id),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].content, ClipboardContent::text("report"));
+
+        let page = db
+            .query_clipboard_history(&HistoryQuery {
+                tag_id: Some(home.id),
+                content_type: Some(ContentType::Image),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(page.items.is_empty(), "filters are combined");
+    }
+
+    #[test]
+    #[serial]
+    fn test_deleting_an_item_removes_its_tags() {
+        let db = DatabaseManager::new_test().unwrap();
+        let tag = db.create_tag("work").unwrap();
+        db.add_clipboard_item("report").unwrap();
+        let item_id = id_of(&db, "report");
+        db.set_item_tag(item_id, tag.id, true).unwrap();
+
+        db.delete_clipboard_item(item_id).unwrap();
+
+        let conn = db.get_connection().lock().unwrap();
+        let links: i64 = conn
+            .query_row("SELECT COUNT(*) FROM clipboard_item_tags", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(links, 0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_tagged_items_are_not_trimmed() {
+        let db = DatabaseManager::new_test().unwrap();
+        db.update_config(&AppConfig {
+            max_history_count: 2,
+            retention: RetentionPolicy {
+                max_items_per_type: BTreeMap::from([(ContentType::Text, 2)]),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap();
+        let tag = db.create_tag("keep").unwrap();
+
+        db.add_clipboard_item("tagged").unwrap();
+        let tagged_id = id_of(&db, "tagged");
+        db.set_item_tag(tagged_id, tag.id, true).unwrap();
+        db.add_clipboard_item("1").unwrap();
+        db.add_clipboard_item("2").unwrap();
+        db.add_clipboard_item("3").unwrap();
+
+        let page = db
+            .query_clipboard_history(&HistoryQuery::default())
+            .unwrap();
+        let texts: Vec<_> = page
+            .items
+            .iter()
+            .filter_map(|item| item.content.as_text())
+            .collect();
+        assert_eq!(texts, vec!["3", "2", "tagged"]);
+
+        // Once untagged, the item is trimmed like any other
+        db.set_item_tag(tagged_id, tag.id, false).unwrap();
+        db.apply_retention(chrono::Utc::now()).unwrap();
+        assert_eq!(history_texts(&db), vec!["3", "2"]);
+    }
+}
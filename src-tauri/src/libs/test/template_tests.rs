@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod tests {
+    use crate::libs::template::{Segment, Template, TemplateContext, TemplateError};
+    use chrono::{DateTime, FixedOffset};
+    use std::collections::HashMap;
+
+    fn fixed_now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2024-03-05T14:07:09+01:00").unwrap()
+    }
+
+    fn expand(source: &str, clipboard: Option<&str>, fields: &[(&str, &str)]) -> String {
+        let fields: HashMap<String, String> = fields
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Template::parse(source)
+            .unwrap()
+            .expand(&TemplateContext {
+                now: fixed_now(),
+                clipboard,
+                fields: &fields,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_segments() {
+        let template =
+            Template::parse("Hi {field:Name}, it is {date:%H:%M}. {uuid}{clipboard}").unwrap();
+        assert_eq!(
+            template.segments(),
+            &[
+                Segment::Text("Hi ".to_string()),
+                Segment::Field("Name".to_string()),
+                Segment::Text(", it is ".to_string()),
+                Segment::Date("%H:%M".to_string()),
+                Segment::Text(". ".to_string()),
+                Segment::Uuid,
+                Segment::Clipboard,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plain_text_is_unchanged() {
+        let code = "fn main() {\n    let x = {1};\n    println!(\"{}\", x);\n}";
+        let template = Template::parse(code).unwrap();
+        assert_eq!(template.segments(), &[Segment::Text(code.to_string())]);
+        assert_eq!(expand(code, None, &[]), code);
+
+        // Unknown names and unclosed braces are text too
+        assert_eq!(expand("{name} {date", None, &[]), "{name} {date");
+        assert_eq!(expand("{DATE}", None, &[]), "{DATE}");
+        assert_eq!(expand("", None, &[]), "");
+    }
+
+    #[test]
+    fn test_escaped_placeholders() {
+        assert_eq!(expand("{{date}", None, &[]), "{date}");
+        assert_eq!(
+            expand("{{field:Name} is {field:Name}", None, &[("Name", "Sam")]),
+            "{field:Name} is Sam"
+        );
+        // Escapes are not validated
+        assert_eq!(expand("{{date:%Q}", None, &[]), "{date:%Q}");
+        // Doubled braces around anything else are left alone
+        assert_eq!(expand("{{x}}", None, &[]), "{{x}}");
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(expand("{date}", None, &[]), "2024-03-05");
+        assert_eq!(
+            expand("{date:%d/%m/%Y %H:%M:%S}", None, &[]),
+            "05/03/2024 14:07:09"
+        );
+        assert_eq!(expand("{date:%A}", None, &[]), "Tuesday");
+        assert_eq!(expand("{date:%z}", None, &[]), "+0100");
+    }
+
+    #[test]
+    fn test_invalid_date_formats() {
+        assert_eq!(
+            Template::parse("{date:%Q}"),
+            Err(TemplateError::InvalidDateFormat("%Q".to_string()))
+        );
+        assert_eq!(
+            Template::parse("{date:}"),
+            Err(TemplateError::InvalidDateFormat(String::new()))
+        );
+        let error = Template::parse("{date:%}").unwrap_err().to_string();
+        assert!(error.contains("Invalid date format"), "{}", error);
+    }
+
+    #[test]
+    fn test_uuid() {
+        let first = expand("{uuid}", None, &[]);
+        let parts: Vec<&str> = first.split('-').collect();
+        assert_eq!(
+            parts.iter().map(|part| part.len()).collect::<Vec<_>>(),
+            vec![8, 4, 4, 4, 12]
+        );
+        assert!(first.chars().all(|c| c == '-' || c.is_ascii_hexdigit()));
+        assert!(parts[2].starts_with('4'), "version 4: {}", first);
+        assert!(matches!(&parts[3][..1], "8" | "9" | "a" | "b"), "{}", first);
+
+        // Every placeholder gets a new one
+        let two = expand("{uuid} {uuid}", None, &[]);
+        let (a, b) = two.split_once(' ').unwrap();
+        assert_ne!(a, b);
+        assert_ne!(first, expand("{uuid}", None, &[]));
+    }
+
+    #[test]
+    fn test_clipboard() {
+        assert_eq!(
+            expand("> {clipboard}", Some("quoted {date}"), &[]),
+            "> quoted {date}",
+            "clipboard text is not expanded again"
+        );
+        assert_eq!(expand("> {clipboard}", None, &[]), "> ");
+    }
+
+    #[test]
+    fn test_fields() {
+        let source =
+            "Dear {field:Name},\nyour order {field: Order } ships today. Thanks {field:Name}!";
+        let template = Template::parse(source).unwrap();
+        assert_eq!(template.fields(), vec!["Name", "Order"]);
+
+        assert_eq!(
+            expand(
+                source,
+                None,
+                &[("Name", "Ada"), ("Order", "#42"), ("Unused", "x")]
+            ),
+            "Dear Ada,\nyour order #42 ships today. Thanks Ada!"
+        );
+        assert!(Template::parse("{date} {uuid}")
+            .unwrap()
+            .fields()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_missing_and_empty_fields() {
+        let template = Template::parse("Hi {field:Name}").unwrap();
+        let result = template.expand(&TemplateContext {
+            now: fixed_now(),
+            clipboard: None,
+            fields: &HashMap::new(),
+        });
+        assert_eq!(result, Err(TemplateError::MissingField("Name".to_string())));
+
+        assert_eq!(
+            Template::parse("{field: }"),
+            Err(TemplateError::EmptyFieldName)
+        );
+        // Without a name it is not a field at all
+        assert_eq!(expand("{field}", None, &[]), "{field}");
+    }
+
+    #[test]
+    fn test_placeholders_do_not_span_lines() {
+        assert_eq!(expand("{date\n}", None, &[]), "{date\n}");
+        assert_eq!(
+            expand("{date}\n{{uuid}\n", None, &[]),
+            "2024-03-05\n{uuid}\n"
+        );
+    }
+}
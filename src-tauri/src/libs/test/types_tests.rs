@@ -63,6 +63,7 @@ mod tests {
                 preset: ThemePreset::MidnightBlue,
             },
             autostart: false,
+            ..Default::default()
         };
 
         assert_eq!(app_config.max_history_count, 25);
@@ -74,15 +75,23 @@ mod tests {
     fn test_clipboard_item_creation() {
         let clipboard_item = ClipboardItem {
             id: 1,
-            content: "test content".to_string(),
-            content_type: "text".to_string(),
+            content: ClipboardContent::text("test content"),
             timestamp: "2024-01-01T00:00:00Z".to_string(),
             pinned: false,
+            last_used: "2024-01-01T00:00:00Z".to_string(),
+            copy_count: 1,
+            expires_at: None,
+            tag_ids: Vec::new(),
+            selection: Selection::Clipboard,
+            source_app: None,
         };
 
         assert_eq!(clipboard_item.id, 1);
-        assert_eq!(clipboard_item.content, "test content");
-        assert_eq!(clipboard_item.content_type, "text");
+        assert_eq!(
+            clipboard_item.content,
+            ClipboardContent::text("test content")
+        );
+        assert_eq!(clipboard_item.content.content_type(), ContentType::Text);
         assert_eq!(clipboard_item.timestamp, "2024-01-01T00:00:00Z");
         assert!(!clipboard_item.pinned);
     }
@@ -91,10 +100,15 @@ mod tests {
     fn test_clipboard_item_serialization() {
         let clipboard_item = ClipboardItem {
             id: 1,
-            content: "test content".to_string(),
-            content_type: "text".to_string(),
+            content: ClipboardContent::text("test content"),
             timestamp: "2024-01-01T00:00:00Z".to_string(),
             pinned: true,
+            last_used: "2024-01-01T00:00:00Z".to_string(),
+            copy_count: 1,
+            expires_at: None,
+            tag_ids: Vec::new(),
+            selection: Selection::Clipboard,
+            source_app: None,
         };
 
         // Test JSON serialization
@@ -103,7 +117,6 @@ mod tests {
 
         assert_eq!(clipboard_item.id, deserialized.id);
         assert_eq!(clipboard_item.content, deserialized.content);
-        assert_eq!(clipboard_item.content_type, deserialized.content_type);
         assert_eq!(clipboard_item.timestamp, deserialized.timestamp);
         assert_eq!(clipboard_item.pinned, deserialized.pinned);
     }
@@ -130,6 +143,7 @@ mod tests {
                 preset: ThemePreset::DeepPurple,
             },
             autostart: false,
+            ..Default::default()
         };
 
         // Test JSON serialization
@@ -141,4 +155,69 @@ mod tests {
         assert_eq!(app_config.theme.preset, deserialized.theme.preset);
         assert_eq!(app_config.autostart, deserialized.autostart);
     }
+
+    #[test]
+    fn test_clipboard_content_wire_format() {
+        let item = ClipboardItem {
+            id: 7,
+            content: ClipboardContent::text("hello"),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            pinned: false,
+            last_used: "2024-01-01T00:00:00Z".to_string(),
+            copy_count: 1,
+            expires_at: None,
+            tag_ids: Vec::new(),
+            selection: Selection::Clipboard,
+            source_app: None,
+        };
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(json["content_type"], "text");
+        assert_eq!(json["content"], "hello");
+
+        let files = ClipboardContent::Files {
+            files: vec!["file:///tmp/a.txt".to_string()],
+        };
+        let json = serde_json::to_value(&files).unwrap();
+        assert_eq!(json["content_type"], "files");
+        assert_eq!(json["files"][0], "file:///tmp/a.txt");
+
+        let image = ClipboardContent::Image(ImageInfo {
+            width: 2,
+            height: 1,
+            mime: "image/png".to_string(),
+            thumbnail: "AA==".to_string(),
+        });
+        let roundtrip: ClipboardContent =
+            serde_json::from_value(serde_json::to_value(&image).unwrap()).unwrap();
+        assert_eq!(roundtrip, image);
+    }
+
+    #[test]
+    fn test_unknown_content_type_is_rejected() {
+        let result = serde_json::from_str::<ClipboardContent>(
+            r#"{"content_type":"video","content":"clip.mp4"}"#,
+        );
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("unknown variant `video`"), "{}", error);
+
+        let query = serde_json::from_str::<HistoryQuery>(r#"{"content_type":"video"}"#);
+        assert!(query.is_err());
+    }
+
+    #[test]
+    fn test_content_type_str_roundtrip() {
+        for content_type in [
+            ContentType::Text,
+            ContentType::Html,
+            ContentType::Rtf,
+            ContentType::Image,
+            ContentType::Files,
+        ] {
+            assert_eq!(
+                ContentType::from_str(content_type.as_str()),
+                Some(content_type)
+            );
+        }
+        assert_eq!(ContentType::from_str("video"), None);
+    }
 }
@@ -0,0 +1,46 @@
+use image::ImageFormat;
+use std::io::Cursor;
+
+use crate::libs::constants::THUMBNAIL_MAX_SIZE;
+
+/// Image ready for storage: the original PNG plus a small preview
+pub struct ProcessedImage {
+    pub png: Vec<u8>,
+    pub thumbnail: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Decode image bytes, re-encoding to PNG if needed, and generate a thumbnail
+/// that fits within THUMBNAIL_MAX_SIZE while keeping the aspect ratio.
+pub fn process_image(bytes: &[u8]) -> Result<ProcessedImage, String> {
+    let format = image::guess_format(bytes).map_err(|e| e.to_string())?;
+    let img = image::load_from_memory_with_format(bytes, format).map_err(|e| e.to_string())?;
+
+    let png = if format == ImageFormat::Png {
+        bytes.to_vec()
+    } else {
+        encode_png(&img)?
+    };
+
+    // Small images are used as their own thumbnail
+    let thumbnail = if img.width() <= THUMBNAIL_MAX_SIZE && img.height() <= THUMBNAIL_MAX_SIZE {
+        png.clone()
+    } else {
+        encode_png(&img.thumbnail(THUMBNAIL_MAX_SIZE, THUMBNAIL_MAX_SIZE))?
+    };
+
+    Ok(ProcessedImage {
+        png,
+        thumbnail,
+        width: img.width(),
+        height: img.height(),
+    })
+}
+
+fn encode_png(img: &image::DynamicImage) -> Result<Vec<u8>, String> {
+    let mut buffer = Cursor::new(Vec::new());
+    img.write_to(&mut buffer, ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(buffer.into_inner())
+}
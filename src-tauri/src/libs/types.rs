@@ -1,12 +1,317 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::libs::constants::{
+    DEFAULT_HOTKEY, DEFAULT_MAX_HISTORY_COUNT, DEFAULT_POLL_INTERVAL_MS,
+    DEFAULT_SELECTION_DEBOUNCE_MS, DEFAULT_SENSITIVE_EXPIRY_SECS, MAX_POLL_INTERVAL_MS,
+    MAX_SELECTION_DEBOUNCE_MS, MIN_POLL_INTERVAL_MS,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ClipboardItem {
     pub id: i64,
-    pub content: String,
-    pub content_type: String, // "text" | "image"
-    pub timestamp: String,
+    #[serde(flatten)]
+    pub content: ClipboardContent,
+    pub timestamp: String, // first captured
     pub pinned: bool,
+    pub last_used: String, // last captured; history is ordered by this
+    pub copy_count: u32,
+    /// Sensitive items are deleted at this time
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// Ids of the tags assigned to the item
+    #[serde(default)]
+    pub tag_ids: Vec<i64>,
+    /// Selection the item was last captured from
+    #[serde(default)]
+    pub selection: Selection,
+    /// Application that was active when the item was last captured, if known
+    #[serde(default)]
+    pub source_app: Option<SourceApp>,
+}
+
+/// Application a copy came from, as far as the platform tells.
+/// Stored in the `source_process`, `source_class` and `source_title` columns.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct SourceApp {
+    /// Executable name, e.g. `firefox` or `KeePassXC.exe`
+    pub process: Option<String>,
+    /// Window class: the class part of X11 `WM_CLASS`, or the Win32 class name
+    pub class: Option<String>,
+    /// Window title at the time of the copy
+    pub title: Option<String>,
+}
+
+/// User-defined label for organising history items
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+}
+
+/// What an item holds, tagged by `content_type` on the wire.
+/// Text-like variants keep the value in `content`; images only carry a preview.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "content_type", rename_all = "lowercase")]
+pub enum ClipboardContent {
+    Text { content: String },
+    Html { content: String },
+    Rtf { content: String },
+    Image(ImageInfo),
+    Files { files: Vec<String> }, // file URIs
+}
+
+impl ClipboardContent {
+    pub fn text(content: impl Into<String>) -> Self {
+        ClipboardContent::Text {
+            content: content.into(),
+        }
+    }
+
+    /// Value of text-like content; `None` for images and file lists
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            ClipboardContent::Text { content }
+            | ClipboardContent::Html { content }
+            | ClipboardContent::Rtf { content } => Some(content),
+            ClipboardContent::Image(_) | ClipboardContent::Files { .. } => None,
+        }
+    }
+
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn as_image(&self) -> Option<&ImageInfo> {
+        match self {
+            ClipboardContent::Image(image) => Some(image),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> ContentType {
+        match self {
+            ClipboardContent::Text { .. } => ContentType::Text,
+            ClipboardContent::Html { .. } => ContentType::Html,
+            ClipboardContent::Rtf { .. } => ContentType::Rtf,
+            ClipboardContent::Image(_) => ContentType::Image,
+            ClipboardContent::Files { .. } => ContentType::Files,
+        }
+    }
+}
+
+/// Kind of a clipboard item, stored in the `content_type` column
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentType {
+    Text,
+    Html,
+    Rtf,
+    Image,
+    Files,
+}
+
+impl ContentType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentType::Text => "text",
+            ContentType::Html => "html",
+            ContentType::Rtf => "rtf",
+            ContentType::Image => "image",
+            ContentType::Files => "files",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "text" => Some(ContentType::Text),
+            "html" => Some(ContentType::Html),
+            "rtf" => Some(ContentType::Rtf),
+            "image" => Some(ContentType::Image),
+            "files" => Some(ContentType::Files),
+            _ => None,
+        }
+    }
+}
+
+/// X11 and Wayland have a PRIMARY selection, set by selecting text, next to the
+/// clipboard. Stored in the `selection` column.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Selection {
+    #[default]
+    Clipboard,
+    Primary,
+}
+
+impl Selection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Selection::Clipboard => "clipboard",
+            Selection::Primary => "primary",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "clipboard" => Some(Selection::Clipboard),
+            "primary" => Some(Selection::Primary),
+            _ => None,
+        }
+    }
+}
+
+/// Where copied items are written
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WriteBack {
+    #[default]
+    Clipboard,
+    Primary,
+    Both,
+}
+
+/// What happens when the monitor sees an item that Clipray itself copied to the clipboard
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum OwnWritePolicy {
+    /// Leave the history as it is
+    Ignore,
+    /// Move the item to the top of the history, keeping its id, pin, tags and expiry
+    #[default]
+    MoveToTop,
+}
+
+/// How a copy was captured, stored with the item next to its content
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CaptureDetails {
+    pub selection: Selection,
+    pub source_app: Option<SourceApp>,
+    /// Sensitive content is deleted at this time
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Every representation the clipboard offered for a single copy
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ClipboardSnapshot {
+    pub text: Option<String>,
+    pub html: Option<String>,
+    pub rtf: Option<String>,
+    pub image: Option<Vec<u8>>, // encoded image, PNG from the system clipboard
+    pub files: Option<Vec<String>>, // file URIs
+}
+
+impl ClipboardSnapshot {
+    pub fn is_empty(&self) -> bool {
+        self.text.is_none()
+            && self.html.is_none()
+            && self.rtf.is_none()
+            && self.image.is_none()
+            && self.files.is_none()
+    }
+}
+
+/// Preview of an image item. The full image is fetched separately by id.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    pub mime: String,
+    pub thumbnail: String, // Base64 PNG
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SearchResult {
+    pub item: ClipboardItem,
+    /// Excerpt around the matches, with matched terms wrapped in
+    /// SEARCH_HIGHLIGHT_START / SEARCH_HIGHLIGHT_END (not HTML-escaped)
+    pub snippet: String,
+    /// bm25 score after pinned boosting; lower is a better match
+    pub rank: f64,
+}
+
+/// Named reusable text, stored apart from the history and never trimmed
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Snippet {
+    pub id: i64,
+    pub name: String,
+    /// Short keyword for finding the snippet, unique ignoring case
+    pub abbreviation: Option<String>,
+    pub content: String,
+    pub folder_id: Option<i64>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Fields of a snippet when creating or editing it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct SnippetDraft {
+    pub name: String,
+    #[serde(default)]
+    pub abbreviation: Option<String>,
+    pub content: String,
+    #[serde(default)]
+    pub folder_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SnippetFolder {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SnippetSearchResult {
+    pub snippet: Snippet,
+    /// Excerpt of the content, highlighted like `SearchResult::snippet`
+    pub excerpt: String,
+    /// bm25 score; lower is a better match. Exact abbreviation matches come first regardless
+    pub rank: f64,
+}
+
+/// Results of `search_everything`, each list best match first
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CombinedSearchResults {
+    pub snippets: Vec<SnippetSearchResult>,
+    pub history: Vec<SearchResult>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortOrder {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+}
+
+/// Filters and paging for `query_clipboard_history`. All fields are optional.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct HistoryQuery {
+    pub content_type: Option<ContentType>,
+    pub pinned: Option<bool>,
+    /// Only items with this tag
+    pub tag_id: Option<i64>,
+    /// Only items last captured from this selection
+    pub selection: Option<Selection>,
+    /// Only items last copied in this application, by process name or window class
+    pub source_app: Option<String>,
+    /// Inclusive lower bound on `last_used`, RFC 3339
+    pub since: Option<String>,
+    /// Exclusive upper bound on `last_used`, RFC 3339
+    pub until: Option<String>,
+    pub sort: SortOrder,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryPage {
+    pub items: Vec<ClipboardItem>,
+    /// Pass back as `cursor` to fetch the next page; `None` on the last page
+    pub next_cursor: Option<String>,
 }
 
 // テーマプリセットのENUM
@@ -29,6 +334,7 @@ impl ThemePreset {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
             "default" => Some(ThemePreset::Default),
@@ -51,4 +357,280 @@ pub struct AppConfig {
     pub hotkey: String,
     pub theme: ThemeConfig,
     pub autostart: bool,
+    #[serde(default)]
+    pub exclusion_rules: ExclusionRules,
+    /// Global shortcut toggling capture pause; `None` for no shortcut
+    #[serde(default)]
+    pub pause_hotkey: Option<String>,
+    /// Keep capture paused across restarts
+    #[serde(default)]
+    pub persist_pause: bool,
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    #[serde(default)]
+    pub sensitive: SensitiveRules,
+    #[serde(default)]
+    pub capture: CaptureSettings,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            max_history_count: DEFAULT_MAX_HISTORY_COUNT,
+            hotkey: DEFAULT_HOTKEY.to_string(),
+            theme: ThemeConfig {
+                preset: ThemePreset::Default,
+            },
+            autostart: false,
+            exclusion_rules: ExclusionRules::default(),
+            pause_hotkey: None,
+            persist_pause: false,
+            retention: RetentionPolicy::default(),
+            sensitive: SensitiveRules::default(),
+            capture: CaptureSettings::default(),
+        }
+    }
+}
+
+/// Kinds of secrets recognised without user-defined patterns
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecretKind {
+    CreditCard,
+    Jwt,
+    AwsKey,
+    PrivateKey,
+    /// Short numeric codes such as 2FA and SMS verification codes
+    OneTimeCode,
+}
+
+/// Content matching these rules is never written to the history
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ExclusionRules {
+    /// Regular expressions matched against text, HTML and RTF
+    pub patterns: Vec<String>,
+    pub detectors: Vec<SecretKind>,
+    /// Skip text longer than this many characters; `None` for no limit
+    pub max_length: Option<usize>,
+    /// Skip copies that password managers mark as concealed or transient
+    pub respect_concealed_hint: bool,
+    /// Skip copies made in these applications, by process name or window class
+    pub apps: Vec<String>,
+}
+
+impl Default for ExclusionRules {
+    fn default() -> Self {
+        Self {
+            patterns: Vec::new(),
+            detectors: vec![
+                SecretKind::CreditCard,
+                SecretKind::Jwt,
+                SecretKind::AwsKey,
+                SecretKind::PrivateKey,
+            ],
+            max_length: None,
+            respect_concealed_hint: true,
+            apps: Vec::new(),
+        }
+    }
+}
+
+/// Content matching these rules is stored, but deleted again after `expire_after_secs`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SensitiveRules {
+    /// Regular expressions matched against text, HTML and RTF
+    pub patterns: Vec<String>,
+    pub detectors: Vec<SecretKind>,
+    pub expire_after_secs: u32,
+}
+
+impl Default for SensitiveRules {
+    fn default() -> Self {
+        Self {
+            patterns: Vec::new(),
+            detectors: vec![SecretKind::OneTimeCode],
+            expire_after_secs: DEFAULT_SENSITIVE_EXPIRY_SECS,
+        }
+    }
+}
+
+impl SensitiveRules {
+    /// The same matching as exclusion rules, without the length and concealed checks
+    pub fn matching_rules(&self) -> ExclusionRules {
+        ExclusionRules {
+            patterns: self.patterns.clone(),
+            detectors: self.detectors.clone(),
+            max_length: None,
+            respect_concealed_hint: false,
+            apps: Vec::new(),
+        }
+    }
+}
+
+/// How clipboard changes are picked up
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct CaptureSettings {
+    /// How often the clipboard is checked when the platform cannot notify about changes
+    pub poll_interval_ms: u64,
+    /// Also record text selected with the mouse, on X11 and Wayland
+    pub primary_selection: bool,
+    /// The primary selection is read once it has not changed for this long, so a
+    /// selection still being dragged is not recorded at every step
+    pub selection_debounce_ms: u64,
+    pub write_back: WriteBack,
+    /// Copying an item from the history is not captured as a new copy
+    pub own_writes: OwnWritePolicy,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            poll_interval_ms: DEFAULT_POLL_INTERVAL_MS,
+            primary_selection: false,
+            selection_debounce_ms: DEFAULT_SELECTION_DEBOUNCE_MS,
+            write_back: WriteBack::Clipboard,
+            own_writes: OwnWritePolicy::MoveToTop,
+        }
+    }
+}
+
+impl CaptureSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_POLL_INTERVAL_MS..=MAX_POLL_INTERVAL_MS).contains(&self.poll_interval_ms) {
+            return Err(format!(
+                "The polling interval must be between {} and {} ms",
+                MIN_POLL_INTERVAL_MS, MAX_POLL_INTERVAL_MS
+            ));
+        }
+        if self.selection_debounce_ms > MAX_SELECTION_DEBOUNCE_MS {
+            return Err(format!(
+                "The selection delay must be at most {} ms",
+                MAX_SELECTION_DEBOUNCE_MS
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Limits on what the history keeps, on top of `max_history_count`.
+/// Pinned items are never deleted, and tagged items only by `max_age_days`; the size of
+/// both counts towards `max_total_bytes`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Delete unpinned items not copied for this many days
+    pub max_age_days: Option<u32>,
+    /// Delete the least recently used items, neither pinned nor tagged, while the history
+    /// is larger than this
+    pub max_total_bytes: Option<u64>,
+    /// Maximum number of items per content type, not counting pinned and tagged ones
+    pub max_items_per_type: BTreeMap<ContentType, u32>,
+}
+
+/// Whether clipboard capture is paused
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct PauseStatus {
+    pub paused: bool,
+    /// When capture resumes on its own; `None` pauses until resumed
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// How the history database is encrypted at rest
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum EncryptionMode {
+    #[default]
+    None,
+    /// Key derived from a passphrase entered at startup
+    Passphrase,
+    /// Random key stored in a file next to the database
+    KeyFile,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EncryptionStatus {
+    /// The database is encrypted with a passphrase that has not been entered yet
+    pub locked: bool,
+    pub mode: EncryptionMode,
+}
+
+/// How imported items are combined with the current history
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Add to the history; items already in it are merged
+    #[default]
+    Merge,
+    /// Delete the history first, and use the archived settings
+    Replace,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct ImportSummary {
+    /// Items added to the history
+    pub imported: usize,
+    /// Items that were already in the history
+    pub merged: usize,
+    /// Items deleted afterwards by the history limit or retention policy
+    pub trimmed: usize,
+    /// Snippets added
+    pub snippets: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExportSummary {
+    pub path: String,
+    pub items: usize,
+}
+
+/// Shown once after a damaged database was replaced at startup
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RecoveryNotice {
+    /// What was wrong with the database
+    pub reason: String,
+    /// When the restored backup was taken; `None` when no backup was usable and the
+    /// history was started over
+    pub backup_time: Option<String>,
+    /// Where the damaged database was moved
+    pub damaged_copy: String,
+}
+
+/// What kept the application from starting
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum StartupErrorCode {
+    /// The data directory could not be found or created
+    DataDir,
+    /// The database could not be opened
+    Database,
+    /// The database was written by a newer version of the application
+    DatabaseTooNew,
+    /// The settings could not be loaded
+    Config,
+}
+
+/// What the user can do about a failed startup
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum StartupAction {
+    /// Try to start again
+    Retry,
+    /// Put every setting back to its default, then start again
+    ResetConfig,
+    /// Rename the database out of the way and start with a new one
+    MoveDatabaseAside,
+}
+
+/// Startup failure shown in the window instead of the history
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StartupFailure {
+    pub code: StartupErrorCode,
+    pub message: String,
+    /// Where the database and the startup log are, when it is known.
+    /// The window offers to open it with `open_data_folder`.
+    pub data_dir: Option<String>,
+    pub actions: Vec<StartupAction>,
 }
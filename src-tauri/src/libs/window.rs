@@ -1,5 +1,7 @@
 use tauri::{PhysicalPosition, PhysicalSize, WebviewWindow, Window, Wry};
 
+use crate::libs::error::ClipRayError;
+
 #[cfg(windows)]
 use windows::Win32::{
     Foundation::{POINT, RECT},
@@ -7,7 +9,7 @@ use windows::Win32::{
 };
 
 #[cfg(windows)]
-fn get_window_at_cursor() -> Result<Option<(i32, i32, i32, i32)>, String> {
+fn get_window_at_cursor() -> Result<Option<(i32, i32, i32, i32)>, ClipRayError> {
     // Get the window handle at the current cursor position
     unsafe {
         let mut point = POINT::default();
@@ -29,15 +31,19 @@ fn get_window_at_cursor() -> Result<Option<(i32, i32, i32, i32)>, String> {
                     Ok(None)
                 }
             }
-            Err(_) => Err("Failed to get cursor position".to_string()),
+            Err(_) => Err(ClipRayError::Window(
+                "Failed to get cursor position".to_string(),
+            )),
         }
     }
 }
 
-pub fn show_window_impl(window: &Window<Wry>) -> Result<(), String> {
+pub fn show_window_impl(window: &Window<Wry>) -> Result<(), ClipRayError> {
     // Try to position the window at the center of the window where cursor is located on supported platforms (Windows).
     // Fallback to centering if cursor position is unavailable.
 
+    // Only set on Windows
+    #[cfg_attr(not(windows), allow(unused_mut))]
     let mut positioned_at_cursor = false;
 
     #[cfg(windows)]
@@ -62,7 +68,9 @@ pub fn show_window_impl(window: &Window<Wry>) -> Result<(), String> {
 
     if !positioned_at_cursor {
         // Center the window on the current monitor as a fallback
-        let monitor = window.current_monitor().map_err(|e| e.to_string())?;
+        let monitor = window
+            .current_monitor()
+            .map_err(|e| ClipRayError::Window(e.to_string()))?;
         if let Some(monitor) = monitor {
             let screen_size = monitor.size();
             // Use configured size as a conservative default if outer_size is not available
@@ -73,15 +81,21 @@ pub fn show_window_impl(window: &Window<Wry>) -> Result<(), String> {
         }
     }
 
-    window.show().map_err(|e| e.to_string())?;
-    window.set_focus().map_err(|e| e.to_string())?;
+    window
+        .show()
+        .map_err(|e| ClipRayError::Window(e.to_string()))?;
+    window
+        .set_focus()
+        .map_err(|e| ClipRayError::Window(e.to_string()))?;
     Ok(())
 }
 
-pub fn show_webview_window_impl(window: &WebviewWindow) -> Result<(), String> {
+pub fn show_webview_window_impl(window: &WebviewWindow) -> Result<(), ClipRayError> {
     // Try to position the window at the center of the window where cursor is located on supported platforms (Windows).
     // Fallback to centering if cursor position is unavailable.
 
+    // Only set on Windows
+    #[cfg_attr(not(windows), allow(unused_mut))]
     let mut positioned_at_cursor = false;
 
     #[cfg(windows)]
@@ -106,7 +120,9 @@ pub fn show_webview_window_impl(window: &WebviewWindow) -> Result<(), String> {
 
     if !positioned_at_cursor {
         // Center the window on the current monitor as a fallback
-        let monitor = window.current_monitor().map_err(|e| e.to_string())?;
+        let monitor = window
+            .current_monitor()
+            .map_err(|e| ClipRayError::Window(e.to_string()))?;
         if let Some(monitor) = monitor {
             let screen_size = monitor.size();
             let window_size = window.outer_size().unwrap_or(PhysicalSize::new(400, 500));
@@ -116,12 +132,18 @@ pub fn show_webview_window_impl(window: &WebviewWindow) -> Result<(), String> {
         }
     }
 
-    window.show().map_err(|e| e.to_string())?;
-    window.set_focus().map_err(|e| e.to_string())?;
+    window
+        .show()
+        .map_err(|e| ClipRayError::Window(e.to_string()))?;
+    window
+        .set_focus()
+        .map_err(|e| ClipRayError::Window(e.to_string()))?;
     Ok(())
 }
 
-pub fn hide_window_impl(window: &Window<Wry>) -> Result<(), String> {
-    window.hide().map_err(|e| e.to_string())?;
+pub fn hide_window_impl(window: &Window<Wry>) -> Result<(), ClipRayError> {
+    window
+        .hide()
+        .map_err(|e| ClipRayError::Window(e.to_string()))?;
     Ok(())
 }
@@ -6,10 +6,13 @@ use tauri_plugin_autostart::MacosLauncher;
 
 pub mod libs;
 
-use libs::clipboard::monitor_clipboard;
+use libs::backup::{run_backups, RecoveryState};
+use libs::clipboard::{monitor_clipboard, monitor_primary_selection, OwnWrites};
 use libs::commands::*;
-use libs::config::register_hotkey;
-use libs::database::DatabaseManager;
+use libs::janitor::{run_expiry, run_janitor};
+use libs::pause::CapturePause;
+use libs::selection::{open_primary_selection, PrimarySelection};
+use libs::setup::{open_history, report_startup_failure, StartupFailureState};
 
 #[cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 fn main() {
@@ -19,51 +22,104 @@ fn main() {
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_clipboard::init())
+        .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_global_shortcut::Builder::default().build())
         .plugin(tauri_plugin_window_state::Builder::default().build())
         // MacosLauncher::LaunchAgent is the macOS launch method; ignored on Windows/Linux
         .plugin(tauri_plugin_autostart::init(MacosLauncher::LaunchAgent, None))
         .setup(|app| {
             let app_handle = app.handle();
-            let db_path = app_handle.path().app_data_dir().unwrap().join("clipray.db");
+            app.manage(CapturePause::default());
+            app.manage(OwnWrites::default());
+            app.manage(RecoveryState::default());
+            app.manage(StartupFailureState::default());
 
-            // Create database directory if it doesn't exist
-            if let Some(db_dir) = db_path.parent() {
-                std::fs::create_dir_all(db_dir).unwrap();
+            // A failed start is shown in the window with ways to recover, instead of exiting
+            if let Err(e) = open_history(app_handle) {
+                report_startup_failure(app_handle, &e);
             }
 
-            let db = DatabaseManager::new(db_path).unwrap();
+            // Start clipboard monitoring
+            let handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                monitor_clipboard(handle).await;
+            });
 
-            // Load configuration and setup hotkey
-            let config = db.get_config().unwrap();
+            // Also capture the primary selection where the system has one
+            match open_primary_selection() {
+                Ok(selection) => {
+                    app.manage(PrimarySelection(selection.clone()));
+                    let handle = app_handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        monitor_primary_selection(handle, selection).await;
+                    });
+                }
+                Err(e) => eprintln!("Primary selection unavailable: {}", e),
+            }
 
-            app.manage(db);
+            // Start enforcing the retention policy
+            let handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                run_janitor(handle).await;
+            });
 
-            // Register hotkey (application continues even if this fails)
-            if let Err(e) = register_hotkey(app_handle, &config.hotkey) {
-                eprintln!("Failed to register hotkey '{}': {}", config.hotkey, e);
-                eprintln!("The hotkey might already be in use by another application.");
-                eprintln!("You can change the hotkey in the settings.");
-            } else {
-                println!("Hotkey '{}' registered successfully!", config.hotkey);
-            }
+            // Start deleting expired sensitive items
+            let handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                run_expiry(handle).await;
+            });
 
-            // Start clipboard monitoring
+            // Start backing up the database
             let handle = app_handle.clone();
             tauri::async_runtime::spawn(async move {
-                monitor_clipboard(handle).await;
+                run_backups(handle).await;
             });
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             get_clipboard_history,
+            query_clipboard_history,
+            get_most_used_items,
+            search_clipboard_history,
             delete_clipboard_item,
             toggle_pin,
+            set_item_sensitive,
+            list_tags,
+            create_tag,
+            rename_tag,
+            delete_tag,
+            set_item_tag,
+            list_snippets,
+            create_snippet,
+            create_snippet_from_item,
+            update_snippet,
+            delete_snippet,
+            list_snippet_folders,
+            create_snippet_folder,
+            rename_snippet_folder,
+            delete_snippet_folder,
+            search_everything,
+            get_snippet_fields,
+            copy_snippet,
+            copy_snippet_and_hide,
+            get_full_image,
             copy_to_clipboard,
             copy_and_hide,
             get_config,
             update_config,
+            export_data,
+            import_data,
+            get_pause_status,
+            pause_capture,
+            resume_capture,
+            get_encryption_status,
+            unlock_database,
+            set_database_encryption,
+            take_recovery_notice,
+            get_startup_failure,
+            recover_startup,
+            open_data_folder,
             show_window,
             hide_window,
             exit_app,
@@ -8,6 +8,11 @@ import Settings from "./components/Settings";
 import ExitConfirmModal from "./components/ExitConfirmModal";
 import ClipboardList from "./components/ClipboardList";
 import Footer from "./components/Footer";
+import UnlockScreen from "./components/UnlockScreen";
+import StartupErrorScreen from "./components/StartupErrorScreen";
+import SnippetLibrary from "./components/SnippetLibrary";
+import { ClipboardItem, ListTab } from "./types";
+import { sourceAppName } from "./utils/source-app";
 import "./App.css";
 
 function App() {
@@ -15,7 +20,10 @@ function App() {
   const [showSettings, setShowSettings] = useState(false);
   const [showExitModal, setShowExitModal] = useState(false);
   const [selectedIndex, setSelectedIndex] = useState(0);
-  const [activeTab, setActiveTab] = useState<"pinned" | "history">("history"); // Add: tab state
+  const [activeTab, setActiveTab] = useState<ListTab>("history"); // Add: tab state
+  const [tagFilter, setTagFilter] = useState<number | null>(null);
+  const [taggedItems, setTaggedItems] = useState<ClipboardItem[]>([]);
+  const [appFilter, setAppFilter] = useState<string | null>(null);
   const [originalConfig, setOriginalConfig] = useState<typeof config | null>(
     null
   );
@@ -28,10 +36,40 @@ function App() {
     config,
     setConfig,
     loadClipboardHistory,
+    queryHistory,
+    tags,
+    createTag,
+    renameTag,
+    deleteTag,
+    setItemTag,
+    snippets,
+    snippetFolders,
+    saveSnippet,
+    createSnippetFromItem,
+    deleteSnippet,
+    createSnippetFolder,
+    deleteSnippetFolder,
+    searchEverything,
+    getSnippetFields,
+    copySnippetAndHide,
+    pauseStatus,
+    pauseCapture,
+    resumeCapture,
+    startupFailure,
+    recoverStartup,
+    openDataFolder,
+    encryptionStatus,
+    unlockDatabase,
+    setDatabaseEncryption,
+    recoveryNotice,
+    dismissRecoveryNotice,
+    exportData,
+    importData,
     copyToClipboard,
     copyAndHide,
     deleteItem,
     togglePin,
+    setItemSensitive,
     saveConfig,
     hideWindow,
     exitApp,
@@ -40,8 +78,32 @@ function App() {
   // Apply theme
   useTheme(config.theme);
 
+  // Tagged items are kept beyond the history limit, so they are queried separately
+  useEffect(() => {
+    if (tagFilter === null) return;
+    if (!tags.some((tag) => tag.id === tagFilter)) {
+      setTagFilter(null);
+      return;
+    }
+    queryHistory({ tag_id: tagFilter, limit: 500 }).then(setTaggedItems);
+  }, [tagFilter, tags, clipboardItems]);
+
+  const sourceItems = tagFilter === null ? clipboardItems : taggedItems;
+
+  // Applications the listed items were copied in
+  const sourceApps = [
+    ...new Set(
+      sourceItems
+        .map(sourceAppName)
+        .filter((name): name is string => name !== null)
+    ),
+  ].sort();
+
   // Search filter
-  const filteredItems = clipboardItems.filter((item) => {
+  const filteredItems = sourceItems.filter((item) => {
+    if (appFilter !== null && sourceAppName(item) !== appFilter) {
+      return false;
+    }
     if (item.content_type === "image") {
       if (
         searchQuery.toLowerCase().startsWith("image") ||
@@ -53,12 +115,16 @@ function App() {
       // Hide images from search results
       return false;
     }
-    return item.content.toLowerCase().includes(searchQuery.toLowerCase());
+    const text =
+      item.content_type === "files" ? item.files.join("\n") : item.content;
+    return text.toLowerCase().includes(searchQuery.toLowerCase());
   });
 
   // Tab filter - filter pinned/history items based on activeTab
   const tabFilteredItems = filteredItems.filter((item) => {
-    if (activeTab === "pinned") {
+    if (activeTab === "snippets") {
+      return false;
+    } else if (activeTab === "pinned") {
       return item.pinned;
     } else {
       return !item.pinned;
@@ -71,11 +137,21 @@ function App() {
     setActiveTab("history"); // Return to history tab when resetting search
   };
 
-  const handleTabChange = (tab: "pinned" | "history") => {
+  const handleTabChange = (tab: ListTab) => {
     setActiveTab(tab);
     setSelectedIndex(0); // Reset selected index when switching tabs
   };
 
+  const handleTagFilterChange = (value: string) => {
+    setTagFilter(value === "" ? null : Number(value));
+    setSelectedIndex(0);
+  };
+
+  const handleAppFilterChange = (value: string) => {
+    setAppFilter(value === "" ? null : value);
+    setSelectedIndex(0);
+  };
+
   // Keyboard navigation
   useKeyboardNavigation({
     showSettings,
@@ -159,14 +235,19 @@ function App() {
   const handleItemClick = (index: number) => {
     setSelectedIndex(index);
     const item = tabFilteredItems[index];
-    copyAndHide(item.content, item.content_type);
+    copyAndHide(item.id);
     resetSearch(); // Reset search
   };
 
+  // Settings stay open with the error when the configuration was not saved
   const handleSettingsSave = async (newConfig: typeof config) => {
-    await saveConfig(newConfig);
+    const error = await saveConfig(newConfig);
+    if (error) {
+      return error;
+    }
     setOriginalConfig(null);
     setShowSettings(false);
+    return null;
   };
 
   const handleSettingsCancel = () => {
@@ -198,6 +279,27 @@ function App() {
   // Count pinned items
   const pinnedItemsCount = clipboardItems.filter((item) => item.pinned).length;
 
+  if (startupFailure) {
+    return (
+      <div className="app">
+        <StartupErrorScreen
+          failure={startupFailure}
+          onRecover={recoverStartup}
+          onOpenDataFolder={openDataFolder}
+          onExit={exitApp}
+        />
+      </div>
+    );
+  }
+
+  if (encryptionStatus.locked) {
+    return (
+      <div className="app">
+        <UnlockScreen onUnlock={unlockDatabase} onExit={exitApp} />
+      </div>
+    );
+  }
+
   return (
     <div className="app">
       <Header
@@ -205,9 +307,29 @@ function App() {
         onSearchChange={handleSearchChange}
         onSettingsToggle={handleSettingsToggle}
         onHide={hideWindow}
+        pauseStatus={pauseStatus}
+        onPause={pauseCapture}
+        onResume={resumeCapture}
         searchInputRef={searchInputRef}
       />
 
+      {recoveryNotice && (
+        <div className="recovery-notice" role="alert">
+          <p>
+            The history database was damaged ({recoveryNotice.reason}).{" "}
+            {recoveryNotice.backup_time
+              ? `It was restored from the backup of ${new Date(
+                  recoveryNotice.backup_time
+                ).toLocaleString()}; items copied since then are missing.`
+              : "No usable backup was found, so a new history was started."}{" "}
+            The damaged file was kept at {recoveryNotice.damaged_copy}.
+          </p>
+          <button onClick={dismissRecoveryNotice} title="Dismiss">
+            ✕
+          </button>
+        </div>
+      )}
+
       {/* Tab UI */}
       <div className="tabs-container">
         <div className="tabs">
@@ -215,14 +337,53 @@ function App() {
             className={`tab ${activeTab === "history" ? "active" : ""}`}
             onClick={() => handleTabChange("history")}
           >
-            History ({clipboardItems.filter((item) => !item.pinned).length})
+            History ({sourceItems.filter((item) => !item.pinned).length})
           </button>
           <button
             className={`tab ${activeTab === "pinned" ? "active" : ""}`}
             onClick={() => handleTabChange("pinned")}
           >
-            Pinned ({pinnedItemsCount})
+            Pinned ({sourceItems.filter((item) => item.pinned).length})
           </button>
+          <button
+            className={`tab ${activeTab === "snippets" ? "active" : ""}`}
+            onClick={() => handleTabChange("snippets")}
+          >
+            Snippets ({snippets.length})
+          </button>
+          {tags.length > 0 && (
+            <select
+              className="tag-filter"
+              value={tagFilter ?? ""}
+              onChange={(e) => handleTagFilterChange(e.target.value)}
+              title="Filter by tag"
+            >
+              <option value="">All tags</option>
+              {tags.map((tag) => (
+                <option key={tag.id} value={tag.id}>
+                  {tag.name}
+                </option>
+              ))}
+            </select>
+          )}
+          {(sourceApps.length > 0 || appFilter !== null) && (
+            <select
+              className="tag-filter"
+              value={appFilter ?? ""}
+              onChange={(e) => handleAppFilterChange(e.target.value)}
+              title="Filter by application"
+            >
+              <option value="">All apps</option>
+              {appFilter !== null && !sourceApps.includes(appFilter) && (
+                <option value={appFilter}>{appFilter}</option>
+              )}
+              {sourceApps.map((app) => (
+                <option key={app} value={app}>
+                  {app}
+                </option>
+              ))}
+            </select>
+          )}
         </div>
       </div>
 
@@ -233,6 +394,14 @@ function App() {
           onSave={handleSettingsSave}
           onCancel={handleSettingsCancel}
           onExit={handleExitRequest}
+          encryptionMode={encryptionStatus.mode}
+          onEncryptionChange={setDatabaseEncryption}
+          tags={tags}
+          onCreateTag={createTag}
+          onRenameTag={renameTag}
+          onDeleteTag={deleteTag}
+          onExport={exportData}
+          onImport={importData}
         />
       )}
 
@@ -243,17 +412,37 @@ function App() {
         pinnedItemsCount={pinnedItemsCount}
       />
 
-      <ClipboardList
-        items={tabFilteredItems}
-        selectedIndex={selectedIndex}
-        onItemClick={handleItemClick}
-        onPin={togglePin}
-        onCopy={copyToClipboard}
-        onDelete={deleteItem}
-        selectedItemRef={selectedItemRef}
-        listRef={clipboardListRef}
-        activeTab={activeTab}
-      />
+      {activeTab === "snippets" ? (
+        <SnippetLibrary
+          snippets={snippets}
+          folders={snippetFolders}
+          searchQuery={searchQuery}
+          onCopy={copySnippetAndHide}
+          onGetFields={getSnippetFields}
+          onCopyItem={copyAndHide}
+          onSave={saveSnippet}
+          onDelete={deleteSnippet}
+          onCreateFolder={createSnippetFolder}
+          onDeleteFolder={deleteSnippetFolder}
+          onSearch={searchEverything}
+        />
+      ) : (
+        <ClipboardList
+          items={tabFilteredItems}
+          selectedIndex={selectedIndex}
+          onItemClick={handleItemClick}
+          onPin={togglePin}
+          onSetSensitive={setItemSensitive}
+          tags={tags}
+          onSetTag={setItemTag}
+          onSaveAsSnippet={createSnippetFromItem}
+          onCopy={copyToClipboard}
+          onDelete={deleteItem}
+          selectedItemRef={selectedItemRef}
+          listRef={clipboardListRef}
+          activeTab={activeTab}
+        />
+      )}
 
       {tabFilteredItems.length > 0 && (
         <Footer hotkey={config.hotkey} itemCount={tabFilteredItems.length} />
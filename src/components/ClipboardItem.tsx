@@ -1,15 +1,30 @@
-import React from "react";
-import { Pin, Copy, Trash2, Image, FileText } from "lucide-react";
+import React, { useState } from "react";
+import {
+  Pin,
+  Copy,
+  Trash2,
+  Image,
+  FileText,
+  Files,
+  Timer,
+  Tag as TagIcon,
+  BookmarkPlus,
+} from "lucide-react";
 import { formatDistanceToNow } from "date-fns";
 import { enUS } from "date-fns/locale";
-import { ClipboardItem as ClipboardItemData } from "../types";
+import { ClipboardItem as ClipboardItemData, Tag } from "../types";
+import { sourceAppName } from "../utils/source-app";
 
 interface ClipboardItemProps {
   item: ClipboardItemData;
   isSelected: boolean;
   onClick: () => void;
   onPin: (id: number) => void;
-  onCopy: (content: string, contentType: string) => void;
+  onSetSensitive: (id: number, sensitive: boolean) => void;
+  tags: Tag[];
+  onSetTag: (itemId: number, tagId: number, tagged: boolean) => void;
+  onSaveAsSnippet: (id: number) => void;
+  onCopy: (id: number) => void;
   onDelete: (id: number) => void;
 }
 
@@ -18,18 +33,46 @@ const ClipboardItem: React.FC<ClipboardItemProps> = ({
   isSelected,
   onClick,
   onPin,
+  onSetSensitive,
+  tags,
+  onSetTag,
+  onSaveAsSnippet,
   onCopy,
   onDelete,
 }) => {
+  const [showTagMenu, setShowTagMenu] = useState(false);
+  const itemTags = tags.filter((tag) => item.tag_ids.includes(tag.id));
+
   const truncateText = (text: string, maxLength: number = 80) => {
     if (text.length <= maxLength) return text;
     return text.substring(0, maxLength) + "...";
   };
 
+  // Show just the names of copied files
+  const getDisplayText = () => {
+    if (item.content_type === "image") return "";
+    if (item.content_type !== "files") return item.content;
+    return item.files
+      .map((uri) => decodeURIComponent(uri.split("/").pop() || uri))
+      .join(", ");
+  };
+
   const getImagePreview = (base64Data: string) => {
     return `data:image/png;base64,${base64Data}`;
   };
 
+  // Pinned items never expire
+  const expiry =
+    !item.pinned &&
+    item.expires_at &&
+    ` · expires ${formatDistanceToNow(new Date(item.expires_at), {
+      addSuffix: true,
+      locale: enUS,
+    })}`;
+
+  const sourceApp = sourceAppName(item);
+  const source = sourceApp && ` · ${sourceApp}`;
+
   return (
     <div
       className={`clipboard-item ${item.pinned ? "pinned" : ""} ${
@@ -40,6 +83,8 @@ const ClipboardItem: React.FC<ClipboardItemProps> = ({
       <div className="item-icon">
         {item.content_type === "image" ? (
           <Image size={18} />
+        ) : item.content_type === "files" ? (
+          <Files size={18} />
         ) : (
           <FileText size={18} />
         )}
@@ -48,31 +93,49 @@ const ClipboardItem: React.FC<ClipboardItemProps> = ({
         {item.content_type === "image" ? (
           <div className="image-item">
             <img
-              src={getImagePreview(item.content)}
+              src={getImagePreview(item.thumbnail)}
               alt="Clipboard image"
               className="image-preview"
             />
             <div className="image-meta">
-              <span>Image</span>
+              <span>
+                Image {item.width}×{item.height}
+              </span>
               <div className="item-meta">
-                {formatDistanceToNow(new Date(item.timestamp), {
+                {formatDistanceToNow(new Date(item.last_used), {
                   addSuffix: true,
                   locale: enUS,
                 })}
+                {item.copy_count > 1 && ` · ${item.copy_count}×`}
+                {source}
+                {expiry}
               </div>
             </div>
           </div>
         ) : (
           <div className="text-item">
-            <div className="item-text">{truncateText(item.content)}</div>
+            <div className="item-text">{truncateText(getDisplayText())}</div>
             <div className="item-meta">
-              {formatDistanceToNow(new Date(item.timestamp), {
+              {formatDistanceToNow(new Date(item.last_used), {
                 addSuffix: true,
                 locale: enUS,
               })}
+              {item.copy_count > 1 && ` · ${item.copy_count}×`}
+              {item.selection === "primary" && " · selected"}
+              {source}
+              {expiry}
             </div>
           </div>
         )}
+        {itemTags.length > 0 && (
+          <div className="item-tags">
+            {itemTags.map((tag) => (
+              <span key={tag.id} className="item-tag">
+                {tag.name}
+              </span>
+            ))}
+          </div>
+        )}
       </div>
       <div className="item-actions">
         <button
@@ -85,10 +148,68 @@ const ClipboardItem: React.FC<ClipboardItemProps> = ({
         >
           <Pin size={14} />
         </button>
+        {tags.length > 0 && (
+          <div className="tag-control">
+            <button
+              onClick={(e) => {
+                e.stopPropagation();
+                setShowTagMenu(!showTagMenu);
+              }}
+              className="icon-button"
+              title="Tags"
+            >
+              <TagIcon size={14} />
+            </button>
+            {showTagMenu && (
+              <div
+                className="pause-menu tag-menu"
+                onClick={(e) => e.stopPropagation()}
+                onMouseLeave={() => setShowTagMenu(false)}
+              >
+                {tags.map((tag) => (
+                  <label key={tag.id}>
+                    <input
+                      type="checkbox"
+                      checked={item.tag_ids.includes(tag.id)}
+                      onChange={(e) =>
+                        onSetTag(item.id, tag.id, e.target.checked)
+                      }
+                    />
+                    {tag.name}
+                  </label>
+                ))}
+              </div>
+            )}
+          </div>
+        )}
+        {item.content_type !== "image" && item.content_type !== "files" && (
+          <button
+            onClick={(e) => {
+              e.stopPropagation();
+              onSaveAsSnippet(item.id);
+            }}
+            className="icon-button"
+            title="Save as snippet"
+          >
+            <BookmarkPlus size={14} />
+          </button>
+        )}
+        {!item.pinned && (
+          <button
+            onClick={(e) => {
+              e.stopPropagation();
+              onSetSensitive(item.id, !item.expires_at);
+            }}
+            className={`icon-button ${item.expires_at ? "sensitive" : ""}`}
+            title={item.expires_at ? "Keep" : "Mark as sensitive"}
+          >
+            <Timer size={14} />
+          </button>
+        )}
         <button
           onClick={(e) => {
             e.stopPropagation();
-            onCopy(item.content, item.content_type);
+            onCopy(item.id);
           }}
           className="icon-button"
           title="Copy only"
@@ -1,5 +1,5 @@
 import React from "react";
-import { ClipboardItem as ClipboardItemType } from "../types";
+import { ClipboardItem as ClipboardItemType, ListTab, Tag } from "../types";
 import ClipboardItem from "./ClipboardItem";
 import EmptyState from "./EmptyState";
 
@@ -8,11 +8,15 @@ interface ClipboardListProps {
   selectedIndex: number;
   onItemClick: (index: number) => void;
   onPin: (id: number) => void;
-  onCopy: (content: string, contentType: string) => void;
+  onSetSensitive: (id: number, sensitive: boolean) => void;
+  tags: Tag[];
+  onSetTag: (itemId: number, tagId: number, tagged: boolean) => void;
+  onSaveAsSnippet: (id: number) => void;
+  onCopy: (id: number) => void;
   onDelete: (id: number) => void;
   selectedItemRef: React.RefObject<HTMLDivElement>;
   listRef: React.RefObject<HTMLDivElement>;
-  activeTab?: "pinned" | "history";
+  activeTab?: ListTab;
 }
 
 const ClipboardList: React.FC<ClipboardListProps> = ({
@@ -20,6 +24,10 @@ const ClipboardList: React.FC<ClipboardListProps> = ({
   selectedIndex,
   onItemClick,
   onPin,
+  onSetSensitive,
+  tags,
+  onSetTag,
+  onSaveAsSnippet,
   onCopy,
   onDelete,
   selectedItemRef,
@@ -42,6 +50,10 @@ const ClipboardList: React.FC<ClipboardListProps> = ({
             isSelected={index === selectedIndex}
             onClick={() => onItemClick(index)}
             onPin={onPin}
+            onSetSensitive={onSetSensitive}
+            tags={tags}
+            onSetTag={onSetTag}
+            onSaveAsSnippet={onSaveAsSnippet}
             onCopy={onCopy}
             onDelete={onDelete}
           />
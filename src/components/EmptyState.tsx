@@ -1,7 +1,8 @@
 import React from "react";
+import { ListTab } from "../types";
 
 interface EmptyStateProps {
-  activeTab?: "pinned" | "history";
+  activeTab?: ListTab;
 }
 
 const EmptyState: React.FC<EmptyStateProps> = ({ activeTab = "history" }) => {
@@ -1,11 +1,23 @@
-import React from "react";
-import { Search, Settings, X } from "lucide-react";
+import React, { useState } from "react";
+import { Search, Settings, X, Pause, Play } from "lucide-react";
+import { PauseStatus } from "../types";
+
+// Offered when pausing capture; `undefined` pauses until resumed
+const PAUSE_OPTIONS: { label: string; minutes?: number }[] = [
+  { label: "For 5 minutes", minutes: 5 },
+  { label: "For 15 minutes", minutes: 15 },
+  { label: "For 1 hour", minutes: 60 },
+  { label: "Until resumed" },
+];
 
 interface HeaderProps {
   searchQuery: string;
   onSearchChange: (query: string) => void;
   onSettingsToggle: () => void;
   onHide: () => void;
+  pauseStatus: PauseStatus;
+  onPause: (minutes?: number) => void;
+  onResume: () => void;
   searchInputRef: React.RefObject<HTMLInputElement>;
 }
 
@@ -14,8 +26,17 @@ const Header: React.FC<HeaderProps> = ({
   onSearchChange,
   onSettingsToggle,
   onHide,
+  pauseStatus,
+  onPause,
+  onResume,
   searchInputRef,
 }) => {
+  const [showPauseMenu, setShowPauseMenu] = useState(false);
+
+  const pauseTitle = pauseStatus.until
+    ? `Capture paused until ${new Date(pauseStatus.until).toLocaleTimeString()} (click to resume)`
+    : "Capture paused (click to resume)";
+
   return (
     <div className="header">
       <div className="search-container">
@@ -30,6 +51,40 @@ const Header: React.FC<HeaderProps> = ({
         />
       </div>
       <div className="header-actions">
+        <div className="pause-control">
+          {pauseStatus.paused ? (
+            <button
+              onClick={onResume}
+              className="icon-button paused"
+              title={pauseTitle}
+            >
+              <Play size={16} />
+            </button>
+          ) : (
+            <button
+              onClick={() => setShowPauseMenu(!showPauseMenu)}
+              className="icon-button"
+              title="Pause capture"
+            >
+              <Pause size={16} />
+            </button>
+          )}
+          {showPauseMenu && !pauseStatus.paused && (
+            <div className="pause-menu">
+              {PAUSE_OPTIONS.map((option) => (
+                <button
+                  key={option.label}
+                  onClick={() => {
+                    setShowPauseMenu(false);
+                    onPause(option.minutes);
+                  }}
+                >
+                  {option.label}
+                </button>
+              ))}
+            </div>
+          )}
+        </div>
         <button
           onClick={onSettingsToggle}
           className="icon-button"
@@ -1,18 +1,54 @@
 import React, { useState } from "react";
+import { Trash2 } from "lucide-react";
 import {
   AppConfig,
+  ContentType,
+  EncryptionMode,
+  ENCRYPTION_MODE_DISPLAY_NAMES,
+  ExclusionRules,
+  ImportMode,
+  OwnWritePolicy,
+  RetentionPolicy,
+  SecretKind,
+  SECRET_KIND_DISPLAY_NAMES,
+  SensitiveRules,
+  Tag,
   ThemeConfig,
+  WriteBack,
+  WRITE_BACK_DISPLAY_NAMES,
   THEME_ORDER,
   THEME_DISPLAY_NAMES,
   THEME_PREVIEW_COLORS,
 } from "../types";
 
+const BYTES_PER_MB = 1024 * 1024;
+
+// Content types with their own item limit in the retention settings
+const RETENTION_TYPES: [ContentType, string][] = [
+  ["text", "text items"],
+  ["image", "images"],
+  ["files", "file lists"],
+  ["html", "HTML items"],
+  ["rtf", "RTF items"],
+];
+
 interface SettingsProps {
   config: AppConfig;
   onConfigChange: (config: AppConfig) => void;
-  onSave: (config: AppConfig) => void;
+  onSave: (config: AppConfig) => Promise<string | null>;
   onCancel: () => void;
   onExit: () => void;
+  encryptionMode: EncryptionMode;
+  onEncryptionChange: (
+    mode: EncryptionMode,
+    passphrase?: string
+  ) => Promise<string | null>;
+  tags: Tag[];
+  onCreateTag: (name: string) => Promise<string | null>;
+  onRenameTag: (id: number, name: string) => Promise<string | null>;
+  onDeleteTag: (id: number) => void;
+  onExport: () => Promise<string>;
+  onImport: (path: string, mode: ImportMode) => Promise<string>;
 }
 
 const Settings: React.FC<SettingsProps> = ({
@@ -21,8 +57,79 @@ const Settings: React.FC<SettingsProps> = ({
   onSave,
   onCancel,
   onExit,
+  encryptionMode,
+  onEncryptionChange,
+  tags,
+  onCreateTag,
+  onRenameTag,
+  onDeleteTag,
+  onExport,
+  onImport,
 }) => {
   const [isThemeExpanded, setIsThemeExpanded] = useState(false);
+  const [newEncryptionMode, setNewEncryptionMode] = useState(encryptionMode);
+  const [passphrase, setPassphrase] = useState("");
+  const [confirmPassphrase, setConfirmPassphrase] = useState("");
+  const [encryptionMessage, setEncryptionMessage] = useState<string | null>(
+    null
+  );
+  const [applyingEncryption, setApplyingEncryption] = useState(false);
+  const [newTagName, setNewTagName] = useState("");
+  const [tagError, setTagError] = useState<string | null>(null);
+  const [importPath, setImportPath] = useState("");
+  const [importMode, setImportMode] = useState<ImportMode>("merge");
+  const [transferMessage, setTransferMessage] = useState<string | null>(null);
+  const [transferring, setTransferring] = useState(false);
+  const [saveError, setSaveError] = useState<string | null>(null);
+
+  const handleSave = async () => {
+    setSaveError(await onSave(config));
+  };
+
+  const handleExport = async () => {
+    setTransferring(true);
+    setTransferMessage(await onExport());
+    setTransferring(false);
+  };
+
+  const handleImport = async () => {
+    setTransferring(true);
+    setTransferMessage(await onImport(importPath.trim(), importMode));
+    setTransferring(false);
+  };
+
+  // Applied right away, since re-encrypting is not part of the saved config
+  const handleEncryptionApply = async () => {
+    if (newEncryptionMode === "passphrase" && passphrase !== confirmPassphrase) {
+      setEncryptionMessage("Passphrases do not match");
+      return;
+    }
+    setApplyingEncryption(true);
+    const error = await onEncryptionChange(
+      newEncryptionMode,
+      newEncryptionMode === "passphrase" ? passphrase : undefined
+    );
+    setApplyingEncryption(false);
+    setEncryptionMessage(error ?? "Encryption updated");
+    if (!error) {
+      setPassphrase("");
+      setConfirmPassphrase("");
+    }
+  };
+
+  // Tags are saved right away, like items being tagged
+  const handleTagCreate = async () => {
+    const error = await onCreateTag(newTagName);
+    setTagError(error);
+    if (!error) {
+      setNewTagName("");
+    }
+  };
+
+  const handleTagRename = async (tag: Tag, name: string) => {
+    if (name === tag.name) return;
+    setTagError(await onRenameTag(tag.id, name));
+  };
 
   const handleThemeChange = (preset: ThemeConfig["preset"]) => {
     onConfigChange({
@@ -34,6 +141,61 @@ const Settings: React.FC<SettingsProps> = ({
     });
   };
 
+  const handleExclusionChange = (rules: Partial<ExclusionRules>) => {
+    onConfigChange({
+      ...config,
+      exclusion_rules: {
+        ...config.exclusion_rules,
+        ...rules,
+      },
+    });
+  };
+
+  const handleRetentionChange = (policy: Partial<RetentionPolicy>) => {
+    onConfigChange({
+      ...config,
+      retention: {
+        ...config.retention,
+        ...policy,
+      },
+    });
+  };
+
+  const handleTypeLimitChange = (contentType: ContentType, value: string) => {
+    const limits = { ...config.retention.max_items_per_type };
+    const limit = parseInt(value);
+    if (limit > 0) {
+      limits[contentType] = limit;
+    } else {
+      delete limits[contentType];
+    }
+    handleRetentionChange({ max_items_per_type: limits });
+  };
+
+  const handleSensitiveChange = (rules: Partial<SensitiveRules>) => {
+    onConfigChange({
+      ...config,
+      sensitive: {
+        ...config.sensitive,
+        ...rules,
+      },
+    });
+  };
+
+  const toggleSensitiveDetector = (kind: SecretKind, enabled: boolean) => {
+    const detectors = config.sensitive.detectors.filter((k) => k !== kind);
+    handleSensitiveChange({
+      detectors: enabled ? [...detectors, kind] : detectors,
+    });
+  };
+
+  const toggleDetector = (kind: SecretKind, enabled: boolean) => {
+    const detectors = config.exclusion_rules.detectors.filter((k) => k !== kind);
+    handleExclusionChange({
+      detectors: enabled ? [...detectors, kind] : detectors,
+    });
+  };
+
   const getPreviewStyle = (preset: ThemeConfig["preset"]) => {
     const colors = THEME_PREVIEW_COLORS[preset];
 
@@ -108,6 +270,27 @@ const Settings: React.FC<SettingsProps> = ({
         />
         <small>Examples: CommandOrControl+Shift+V, Alt+V, Ctrl+Space</small>
       </div>
+      <div className="setting-item">
+        <label>Pause capture hotkey:</label>
+        <input
+          type="text"
+          value={config.pause_hotkey ?? ""}
+          onChange={(e) =>
+            onConfigChange({ ...config, pause_hotkey: e.target.value || null })
+          }
+          placeholder="None"
+        />
+      </div>
+      <div className="setting-item setting-item-inline">
+        <label>Stay paused after restart</label>
+        <input
+          type="checkbox"
+          checked={config.persist_pause}
+          onChange={(e) =>
+            onConfigChange({ ...config, persist_pause: e.target.checked })
+          }
+        />
+      </div>
       <div className="setting-item setting-item-inline">
         <label>Start on boot</label>
         <input
@@ -118,8 +301,435 @@ const Settings: React.FC<SettingsProps> = ({
           }
         />
       </div>
+      <div className="setting-item">
+        <label>Clipboard check interval (ms)</label>
+        <input
+          type="number"
+          value={config.capture.poll_interval_ms}
+          onChange={(e) =>
+            onConfigChange({
+              ...config,
+              capture: {
+                ...config.capture,
+                poll_interval_ms: parseInt(e.target.value) || 1000,
+              },
+            })
+          }
+          min="100"
+          max="60000"
+        />
+        <small>Only used where the system does not report clipboard changes</small>
+      </div>
+      <div className="setting-item setting-item-inline">
+        <label>Capture the primary selection</label>
+        <input
+          type="checkbox"
+          checked={config.capture.primary_selection}
+          onChange={(e) =>
+            onConfigChange({
+              ...config,
+              capture: {
+                ...config.capture,
+                primary_selection: e.target.checked,
+              },
+            })
+          }
+        />
+      </div>
+      {config.capture.primary_selection && (
+        <div className="setting-item">
+          <label>Wait for the selection to settle (ms)</label>
+          <input
+            type="number"
+            value={config.capture.selection_debounce_ms}
+            onChange={(e) =>
+              onConfigChange({
+                ...config,
+                capture: {
+                  ...config.capture,
+                  selection_debounce_ms: parseInt(e.target.value) || 0,
+                },
+              })
+            }
+            min="0"
+            max="5000"
+          />
+          <small>
+            Text selected with the mouse, pasted with a middle click. Linux only;
+            on Wayland it works for applications running through XWayland.
+          </small>
+        </div>
+      )}
+      <div className="setting-item">
+        <label>Copy items to</label>
+        <select
+          value={config.capture.write_back}
+          onChange={(e) =>
+            onConfigChange({
+              ...config,
+              capture: {
+                ...config.capture,
+                write_back: e.target.value as WriteBack,
+              },
+            })
+          }
+        >
+          {(Object.keys(WRITE_BACK_DISPLAY_NAMES) as WriteBack[]).map((target) => (
+            <option key={target} value={target}>
+              {WRITE_BACK_DISPLAY_NAMES[target]}
+            </option>
+          ))}
+        </select>
+      </div>
+      <div className="setting-item">
+        <label>Copying an item from the history</label>
+        <select
+          value={config.capture.own_writes}
+          onChange={(e) =>
+            onConfigChange({
+              ...config,
+              capture: {
+                ...config.capture,
+                own_writes: e.target.value as OwnWritePolicy,
+              },
+            })
+          }
+        >
+          <option value="move-to-top">Moves it to the top</option>
+          <option value="ignore">Keeps its place</option>
+        </select>
+      </div>
+      <div className="privacy-section">
+        <h4>Tags</h4>
+        {tags.map((tag) => (
+          <div key={tag.id} className="setting-item setting-item-inline">
+            <input
+              type="text"
+              defaultValue={tag.name}
+              onBlur={(e) => handleTagRename(tag, e.target.value)}
+            />
+            <button
+              onClick={() => onDeleteTag(tag.id)}
+              className="icon-button delete"
+              title="Delete tag (tagged items are kept)"
+            >
+              <Trash2 size={14} />
+            </button>
+          </div>
+        ))}
+        <div className="setting-item setting-item-inline">
+          <input
+            type="text"
+            value={newTagName}
+            onChange={(e) => setNewTagName(e.target.value)}
+            onKeyDown={(e) => {
+              if (e.key === "Enter") handleTagCreate();
+            }}
+            placeholder="New tag"
+          />
+          <button
+            onClick={handleTagCreate}
+            className="save-button"
+            disabled={newTagName.trim() === ""}
+          >
+            Add
+          </button>
+        </div>
+        {tagError && (
+          <div className="setting-item">
+            <small className="error-text">{tagError}</small>
+          </div>
+        )}
+        <small>Tagged items are kept when the history limit is reached</small>
+      </div>
+      <div className="privacy-section">
+        <h4>Retention</h4>
+        <div className="setting-item">
+          <label>Delete items not copied for (days)</label>
+          <input
+            type="number"
+            value={config.retention.max_age_days ?? ""}
+            onChange={(e) =>
+              handleRetentionChange({
+                max_age_days: parseInt(e.target.value) || null,
+              })
+            }
+            min="1"
+            placeholder="Keep forever"
+          />
+        </div>
+        <div className="setting-item">
+          <label>Maximum storage (MB)</label>
+          <input
+            type="number"
+            value={
+              config.retention.max_total_bytes === null
+                ? ""
+                : config.retention.max_total_bytes / BYTES_PER_MB
+            }
+            onChange={(e) => {
+              const megabytes = parseFloat(e.target.value);
+              handleRetentionChange({
+                max_total_bytes:
+                  megabytes > 0 ? Math.round(megabytes * BYTES_PER_MB) : null,
+              });
+            }}
+            min="1"
+            placeholder="No limit"
+          />
+          <small>Least recently used items are deleted first</small>
+        </div>
+        {RETENTION_TYPES.map(([contentType, label]) => (
+          <div key={contentType} className="setting-item">
+            <label>Maximum {label}</label>
+            <input
+              type="number"
+              value={config.retention.max_items_per_type[contentType] ?? ""}
+              onChange={(e) =>
+                handleTypeLimitChange(contentType, e.target.value)
+              }
+              min="1"
+              placeholder="History limit"
+            />
+          </div>
+        ))}
+      </div>
+      <div className="privacy-section">
+        <h4>Privacy</h4>
+          <div className="setting-item">
+          <label>Don't save text matching (one regex per line)</label>
+          <textarea
+            value={config.exclusion_rules.patterns.join("\n")}
+            onChange={(e) =>
+              handleExclusionChange({
+                patterns: e.target.value.split("\n"),
+              })
+            }
+            onBlur={(e) =>
+              handleExclusionChange({
+                patterns: e.target.value
+                  .split("\n")
+                  .filter((pattern) => pattern.trim() !== ""),
+              })
+            }
+            rows={3}
+            placeholder="^ghp_[A-Za-z0-9]+$"
+          />
+        </div>
+        <div className="setting-item">
+          <label>Don't save copies from these apps (one per line)</label>
+          <textarea
+            value={config.exclusion_rules.apps.join("\n")}
+            onChange={(e) =>
+              handleExclusionChange({
+                apps: e.target.value.split("\n"),
+              })
+            }
+            onBlur={(e) =>
+              handleExclusionChange({
+                apps: e.target.value
+                  .split("\n")
+                  .filter((app) => app.trim() !== ""),
+              })
+            }
+            rows={3}
+            placeholder={"keepassxc\nclass:org.remmina.*"}
+          />
+          <small>
+            Process name or window class, as shown next to copied items. Prefix
+            with process: or class: to match only one, and use * as a wildcard.
+          </small>
+        </div>
+        {(Object.keys(SECRET_KIND_DISPLAY_NAMES) as SecretKind[]).map((kind) => (
+          <div key={kind} className="setting-item setting-item-inline">
+            <label>Skip {SECRET_KIND_DISPLAY_NAMES[kind].toLowerCase()}</label>
+            <input
+              type="checkbox"
+              checked={config.exclusion_rules.detectors.includes(kind)}
+              onChange={(e) => toggleDetector(kind, e.target.checked)}
+            />
+          </div>
+        ))}
+        <div className="setting-item setting-item-inline">
+          <label>Skip content hidden by password managers</label>
+          <input
+            type="checkbox"
+            checked={config.exclusion_rules.respect_concealed_hint}
+            onChange={(e) =>
+              handleExclusionChange({ respect_concealed_hint: e.target.checked })
+            }
+          />
+        </div>
+        <div className="setting-item">
+          <label>Maximum text length</label>
+          <input
+            type="number"
+            value={config.exclusion_rules.max_length ?? ""}
+            onChange={(e) =>
+              handleExclusionChange({
+                max_length: parseInt(e.target.value) || null,
+              })
+            }
+            min="1"
+            placeholder="No limit"
+          />
+        </div>
+      </div>
+      <div className="privacy-section">
+        <h4>Sensitive items</h4>
+        <div className="setting-item">
+          <label>Expire text matching (one regex per line)</label>
+          <textarea
+            value={config.sensitive.patterns.join("\n")}
+            onChange={(e) =>
+              handleSensitiveChange({
+                patterns: e.target.value.split("\n"),
+              })
+            }
+            onBlur={(e) =>
+              handleSensitiveChange({
+                patterns: e.target.value
+                  .split("\n")
+                  .filter((pattern) => pattern.trim() !== ""),
+              })
+            }
+            rows={3}
+            placeholder="^Your code is"
+          />
+        </div>
+        {(Object.keys(SECRET_KIND_DISPLAY_NAMES) as SecretKind[]).map((kind) => (
+          <div key={kind} className="setting-item setting-item-inline">
+            <label>Expire {SECRET_KIND_DISPLAY_NAMES[kind].toLowerCase()}</label>
+            <input
+              type="checkbox"
+              checked={config.sensitive.detectors.includes(kind)}
+              onChange={(e) => toggleSensitiveDetector(kind, e.target.checked)}
+            />
+          </div>
+        ))}
+        <div className="setting-item">
+          <label>Delete sensitive items after (seconds)</label>
+          <input
+            type="number"
+            value={config.sensitive.expire_after_secs}
+            onChange={(e) =>
+              handleSensitiveChange({
+                expire_after_secs: Math.max(1, parseInt(e.target.value) || 0),
+              })
+            }
+            min="1"
+          />
+          <small>They are also cleared from the clipboard if still there</small>
+        </div>
+      </div>
+      <div className="privacy-section">
+        <h4>Encryption</h4>
+        <div className="setting-item">
+          <label>Encrypt history</label>
+          <select
+            value={newEncryptionMode}
+            onChange={(e) => {
+              setNewEncryptionMode(e.target.value as EncryptionMode);
+              setEncryptionMessage(null);
+            }}
+          >
+            {(
+              Object.keys(ENCRYPTION_MODE_DISPLAY_NAMES) as EncryptionMode[]
+            ).map((mode) => (
+              <option key={mode} value={mode}>
+                {ENCRYPTION_MODE_DISPLAY_NAMES[mode]}
+              </option>
+            ))}
+          </select>
+          {newEncryptionMode === "key-file" && (
+            <small>
+              A random key is stored next to the history. Anyone who can read
+              your files can read the history.
+            </small>
+          )}
+        </div>
+        {newEncryptionMode === "passphrase" && (
+          <div className="setting-item">
+            <label>
+              {encryptionMode === "passphrase" ? "New passphrase" : "Passphrase"}
+            </label>
+            <input
+              type="password"
+              value={passphrase}
+              onChange={(e) => setPassphrase(e.target.value)}
+            />
+            <input
+              type="password"
+              value={confirmPassphrase}
+              onChange={(e) => setConfirmPassphrase(e.target.value)}
+              placeholder="Confirm passphrase"
+            />
+            <small>
+              The passphrase is asked for on every start and cannot be
+              recovered.
+            </small>
+          </div>
+        )}
+        <div className="setting-item">
+          <button
+            onClick={handleEncryptionApply}
+            className="save-button"
+            disabled={
+              applyingEncryption ||
+              (newEncryptionMode === "passphrase" && passphrase === "") ||
+              (newEncryptionMode === "none" && encryptionMode === "none")
+            }
+          >
+            {applyingEncryption ? "Applying..." : "Apply encryption"}
+          </button>
+          {encryptionMessage && <small>{encryptionMessage}</small>}
+        </div>
+      </div>
+      <div className="privacy-section">
+        <h4>Export and import</h4>
+        <div className="setting-item">
+          <button
+            onClick={handleExport}
+            className="save-button"
+            disabled={transferring}
+          >
+            Export history and settings
+          </button>
+          <small>
+            Saved to your downloads folder. Sensitive items waiting to expire
+            are left out.
+          </small>
+        </div>
+        <div className="setting-item">
+          <label>Import from file</label>
+          <input
+            type="text"
+            value={importPath}
+            onChange={(e) => setImportPath(e.target.value)}
+            placeholder="/path/to/clipray-export.jsonl"
+          />
+          <select
+            value={importMode}
+            onChange={(e) => setImportMode(e.target.value as ImportMode)}
+          >
+            <option value="merge">Merge with the current history</option>
+            <option value="replace">
+              Replace the history, snippets and settings
+            </option>
+          </select>
+          <button
+            onClick={handleImport}
+            className="save-button"
+            disabled={transferring || importPath.trim() === ""}
+          >
+            Import
+          </button>
+          {transferMessage && <small>{transferMessage}</small>}
+        </div>
+      </div>
+      {saveError && <small className="save-error">{saveError}</small>}
       <div className="setting-actions">
-        <button onClick={() => onSave(config)} className="save-button">
+        <button onClick={handleSave} className="save-button">
           Save
         </button>
         <button onClick={onCancel} className="cancel-button">
@@ -0,0 +1,395 @@
+import React, { useEffect, useState } from "react";
+import { BookMarked, FileText, Pencil, Plus, Trash2 } from "lucide-react";
+import {
+  CombinedSearchResults,
+  Snippet,
+  SnippetDraft,
+  SnippetFolder,
+} from "../types";
+
+interface SnippetLibraryProps {
+  snippets: Snippet[];
+  folders: SnippetFolder[];
+  searchQuery: string;
+  onCopy: (id: number, fields?: Record<string, string>) => void;
+  onGetFields: (id: number) => Promise<string[]>;
+  onCopyItem: (id: number) => void;
+  onSave: (snippet: SnippetDraft, id?: number) => Promise<string | null>;
+  onDelete: (id: number) => void;
+  onCreateFolder: (name: string) => Promise<string | null>;
+  onDeleteFolder: (id: number) => void;
+  onSearch: (query: string) => Promise<CombinedSearchResults>;
+}
+
+const EMPTY_DRAFT: SnippetDraft = {
+  name: "",
+  abbreviation: null,
+  content: "",
+  folder_id: null,
+};
+
+// Search excerpts mark matches with <mark></mark>; shown as plain text here
+const stripHighlights = (excerpt: string) =>
+  excerpt.replace(/<\/?mark>/g, "");
+
+const SnippetLibrary: React.FC<SnippetLibraryProps> = ({
+  snippets,
+  folders,
+  searchQuery,
+  onCopy,
+  onGetFields,
+  onCopyItem,
+  onSave,
+  onDelete,
+  onCreateFolder,
+  onDeleteFolder,
+  onSearch,
+}) => {
+  // "" shows every snippet, "none" the ones outside any folder
+  const [folderFilter, setFolderFilter] = useState("");
+  const [editing, setEditing] = useState<{
+    id?: number;
+    draft: SnippetDraft;
+  } | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [newFolderName, setNewFolderName] = useState("");
+  const [results, setResults] = useState<CombinedSearchResults | null>(null);
+  // Snippet waiting for its fill-in fields before being copied
+  const [filling, setFilling] = useState<{
+    id: number;
+    name: string;
+    values: Record<string, string>;
+  } | null>(null);
+
+  useEffect(() => {
+    if (searchQuery.trim() === "") {
+      setResults(null);
+      return;
+    }
+    onSearch(searchQuery).then(setResults);
+  }, [searchQuery, snippets]);
+
+  const visibleSnippets = snippets.filter((snippet) => {
+    if (folderFilter === "") return true;
+    if (folderFilter === "none") return snippet.folder_id === null;
+    return snippet.folder_id === Number(folderFilter);
+  });
+
+  const folderName = (id: number | null) =>
+    folders.find((folder) => folder.id === id)?.name;
+
+  const startEditing = (snippet?: Snippet) => {
+    setError(null);
+    setEditing(
+      snippet
+        ? {
+            id: snippet.id,
+            draft: {
+              name: snippet.name,
+              abbreviation: snippet.abbreviation,
+              content: snippet.content,
+              folder_id: snippet.folder_id,
+            },
+          }
+        : {
+            draft: {
+              ...EMPTY_DRAFT,
+              folder_id:
+                folderFilter === "" || folderFilter === "none"
+                  ? null
+                  : Number(folderFilter),
+            },
+          }
+    );
+  };
+
+  const updateDraft = (changes: Partial<SnippetDraft>) => {
+    if (editing) {
+      setEditing({ ...editing, draft: { ...editing.draft, ...changes } });
+    }
+  };
+
+  const handleSave = async () => {
+    if (!editing) return;
+    const saveError = await onSave(editing.draft, editing.id);
+    setError(saveError);
+    if (!saveError) {
+      setEditing(null);
+    }
+  };
+
+  const handleCopy = async (snippet: Snippet) => {
+    const fields = await onGetFields(snippet.id);
+    if (fields.length === 0) {
+      onCopy(snippet.id);
+      return;
+    }
+    setFilling({
+      id: snippet.id,
+      name: snippet.name,
+      values: Object.fromEntries(fields.map((field) => [field, ""])),
+    });
+  };
+
+  const handleFillIn = () => {
+    if (!filling) return;
+    onCopy(filling.id, filling.values);
+    setFilling(null);
+  };
+
+  const handleCreateFolder = async () => {
+    const folderError = await onCreateFolder(newFolderName);
+    setError(folderError);
+    if (!folderError) {
+      setNewFolderName("");
+    }
+  };
+
+  const renderSnippet = (snippet: Snippet, excerpt?: string) => (
+    <div
+      key={snippet.id}
+      className="clipboard-item"
+      onClick={() => handleCopy(snippet)}
+    >
+      <div className="item-icon">
+        <BookMarked size={18} />
+      </div>
+      <div className="item-content">
+        <div className="text-item">
+          <div className="item-text">
+            {snippet.name}
+            {snippet.abbreviation && (
+              <span className="snippet-abbreviation">
+                {snippet.abbreviation}
+              </span>
+            )}
+          </div>
+          <div className="item-meta">
+            {excerpt !== undefined
+              ? stripHighlights(excerpt)
+              : snippet.content.split("\n")[0]}
+            {folderName(snippet.folder_id) &&
+              ` · ${folderName(snippet.folder_id)}`}
+          </div>
+        </div>
+      </div>
+      <div className="item-actions">
+        <button
+          onClick={(e) => {
+            e.stopPropagation();
+            startEditing(snippet);
+          }}
+          className="icon-button"
+          title="Edit"
+        >
+          <Pencil size={14} />
+        </button>
+        <button
+          onClick={(e) => {
+            e.stopPropagation();
+            onDelete(snippet.id);
+          }}
+          className="icon-button delete"
+          title="Delete"
+        >
+          <Trash2 size={14} />
+        </button>
+      </div>
+    </div>
+  );
+
+  if (editing) {
+    return (
+      <div className="settings-panel snippet-editor">
+        <h3>{editing.id === undefined ? "New snippet" : "Edit snippet"}</h3>
+        <div className="setting-item">
+          <label>Name</label>
+          <input
+            type="text"
+            value={editing.draft.name}
+            onChange={(e) => updateDraft({ name: e.target.value })}
+          />
+        </div>
+        <div className="setting-item">
+          <label>Abbreviation</label>
+          <input
+            type="text"
+            value={editing.draft.abbreviation ?? ""}
+            onChange={(e) =>
+              updateDraft({ abbreviation: e.target.value || null })
+            }
+            placeholder="Optional, e.g. sig"
+          />
+        </div>
+        <div className="setting-item">
+          <label>Folder</label>
+          <select
+            value={editing.draft.folder_id ?? ""}
+            onChange={(e) =>
+              updateDraft({
+                folder_id: e.target.value === "" ? null : Number(e.target.value),
+              })
+            }
+          >
+            <option value="">No folder</option>
+            {folders.map((folder) => (
+              <option key={folder.id} value={folder.id}>
+                {folder.name}
+              </option>
+            ))}
+          </select>
+        </div>
+        <div className="setting-item">
+          <label>Content</label>
+          <textarea
+            value={editing.draft.content}
+            onChange={(e) => updateDraft({ content: e.target.value })}
+            rows={6}
+          />
+          <small>
+            Placeholders: {"{date}"}, {"{date:%d/%m/%Y}"}, {"{clipboard}"},{" "}
+            {"{uuid}"} and {"{field:Name}"}, asked for when copying
+          </small>
+        </div>
+        {error && (
+          <div className="setting-item">
+            <small className="error-text">{error}</small>
+          </div>
+        )}
+        <div className="setting-actions">
+          <button onClick={handleSave} className="save-button">
+            Save
+          </button>
+          <button onClick={() => setEditing(null)} className="cancel-button">
+            Cancel
+          </button>
+        </div>
+      </div>
+    );
+  }
+
+  if (filling) {
+    return (
+      <div className="settings-panel snippet-editor">
+        <h3>{filling.name}</h3>
+        {Object.keys(filling.values).map((field, index) => (
+          <div className="setting-item" key={field}>
+            <label>{field}</label>
+            <input
+              type="text"
+              value={filling.values[field]}
+              autoFocus={index === 0}
+              onChange={(e) =>
+                setFilling({
+                  ...filling,
+                  values: { ...filling.values, [field]: e.target.value },
+                })
+              }
+              onKeyDown={(e) => {
+                if (e.key === "Enter") handleFillIn();
+              }}
+            />
+          </div>
+        ))}
+        <div className="setting-actions">
+          <button onClick={handleFillIn} className="save-button">
+            Copy
+          </button>
+          <button onClick={() => setFilling(null)} className="cancel-button">
+            Cancel
+          </button>
+        </div>
+      </div>
+    );
+  }
+
+  if (results) {
+    return (
+      <div className="clipboard-list">
+        {results.snippets.map((result) =>
+          renderSnippet(result.snippet, result.excerpt)
+        )}
+        {results.history.length > 0 && (
+          <div className="snippet-section-title">From history</div>
+        )}
+        {results.history.map((result) => (
+          <div
+            key={`item-${result.item.id}`}
+            className="clipboard-item"
+            onClick={() => onCopyItem(result.item.id)}
+          >
+            <div className="item-icon">
+              <FileText size={18} />
+            </div>
+            <div className="item-content">
+              <div className="item-text">
+                {stripHighlights(result.snippet)}
+              </div>
+            </div>
+          </div>
+        ))}
+        {results.snippets.length === 0 && results.history.length === 0 && (
+          <div className="empty-state">
+            <p>No matches</p>
+          </div>
+        )}
+      </div>
+    );
+  }
+
+  return (
+    <div className="clipboard-list">
+      <div className="snippet-toolbar">
+        <select
+          value={folderFilter}
+          onChange={(e) => setFolderFilter(e.target.value)}
+        >
+          <option value="">All folders</option>
+          <option value="none">No folder</option>
+          {folders.map((folder) => (
+            <option key={folder.id} value={folder.id}>
+              {folder.name}
+            </option>
+          ))}
+        </select>
+        {folderFilter !== "" && folderFilter !== "none" && (
+          <button
+            onClick={() => {
+              onDeleteFolder(Number(folderFilter));
+              setFolderFilter("");
+            }}
+            className="icon-button delete"
+            title="Delete folder (its snippets are kept)"
+          >
+            <Trash2 size={14} />
+          </button>
+        )}
+        <input
+          type="text"
+          value={newFolderName}
+          onChange={(e) => setNewFolderName(e.target.value)}
+          onKeyDown={(e) => {
+            if (e.key === "Enter") handleCreateFolder();
+          }}
+          placeholder="New folder"
+        />
+        <button
+          onClick={() => startEditing()}
+          className="icon-button"
+          title="New snippet"
+        >
+          <Plus size={16} />
+        </button>
+      </div>
+      {error && <small className="error-text">{error}</small>}
+      {visibleSnippets.map((snippet) => renderSnippet(snippet))}
+      {visibleSnippets.length === 0 && (
+        <div className="empty-state">
+          <p>No snippets</p>
+        </div>
+      )}
+    </div>
+  );
+};
+
+export default SnippetLibrary;
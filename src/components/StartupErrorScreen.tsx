@@ -0,0 +1,88 @@
+import React, { useState } from "react";
+import { StartupAction, StartupFailure } from "../types";
+
+interface StartupErrorScreenProps {
+  failure: StartupFailure;
+  onRecover: (action: StartupAction) => Promise<void>;
+  onOpenDataFolder: () => Promise<string | null>;
+  onExit: () => void;
+}
+
+const ACTION_LABELS: Record<StartupAction, string> = {
+  retry: "Try again",
+  "reset-config": "Reset settings",
+  "move-database-aside": "Start with a new history",
+};
+
+const StartupErrorScreen: React.FC<StartupErrorScreenProps> = ({
+  failure,
+  onRecover,
+  onOpenDataFolder,
+  onExit,
+}) => {
+  const [running, setRunning] = useState(false);
+  const [folderError, setFolderError] = useState<string | null>(null);
+
+  const handleAction = async (action: StartupAction) => {
+    if (
+      action === "move-database-aside" &&
+      !confirm(
+        "The current database file is renamed and kept in the data folder, and a new, empty history is started. Continue?"
+      )
+    ) {
+      return;
+    }
+    setRunning(true);
+    await onRecover(action);
+    setRunning(false);
+  };
+
+  const handleOpenFolder = async () => {
+    setFolderError(await onOpenDataFolder());
+  };
+
+  return (
+    <div className="modal-overlay">
+      <div className="modal-content">
+        <div className="modal-header">
+          <h3>Clipray could not start</h3>
+        </div>
+        <div className="modal-body">
+          <p className="warning-text">{failure.message}</p>
+          {failure.data_dir && (
+            <div className="setting-item">
+              <small>
+                Data folder: {failure.data_dir}. The error was also written to
+                startup-error.log there.
+              </small>
+              <button type="button" onClick={handleOpenFolder}>
+                Open data folder
+              </button>
+              {folderError && (
+                <small className="error-text">{folderError}</small>
+              )}
+            </div>
+          )}
+        </div>
+        <div className="modal-actions">
+          <button type="button" onClick={onExit} className="cancel-button">
+            Exit
+          </button>
+          {failure.actions.map((action) => (
+            <button
+              key={action}
+              type="button"
+              className="save-button"
+              disabled={running}
+              onClick={() => handleAction(action)}
+            >
+              {ACTION_LABELS[action]}
+            </button>
+          ))}
+        </div>
+      </div>
+    </div>
+  );
+};
+
+export default StartupErrorScreen;
@@ -0,0 +1,63 @@
+import React, { useState } from "react";
+
+interface UnlockScreenProps {
+  onUnlock: (passphrase: string) => Promise<string | null>;
+  onExit: () => void;
+}
+
+const UnlockScreen: React.FC<UnlockScreenProps> = ({ onUnlock, onExit }) => {
+  const [passphrase, setPassphrase] = useState("");
+  const [error, setError] = useState<string | null>(null);
+  const [unlocking, setUnlocking] = useState(false);
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    setUnlocking(true);
+    const message = await onUnlock(passphrase);
+    setUnlocking(false);
+    if (message) {
+      setError(message);
+      setPassphrase("");
+    }
+  };
+
+  return (
+    <div className="modal-overlay">
+      <form className="modal-content" onSubmit={handleSubmit}>
+        <div className="modal-header">
+          <h3>Unlock Clipray</h3>
+        </div>
+        <div className="modal-body">
+          <p className="warning-text">
+            Your clipboard history is encrypted. Enter the passphrase to open
+            it. Nothing is recorded until it is unlocked.
+          </p>
+          <div className="setting-item">
+            <input
+              type="password"
+              value={passphrase}
+              onChange={(e) => setPassphrase(e.target.value)}
+              placeholder="Passphrase"
+              autoFocus
+            />
+            {error && <small className="error-text">{error}</small>}
+          </div>
+        </div>
+        <div className="modal-actions">
+          <button type="button" onClick={onExit} className="cancel-button">
+            Exit
+          </button>
+          <button
+            type="submit"
+            className="save-button"
+            disabled={unlocking || passphrase === ""}
+          >
+            Unlock
+          </button>
+        </div>
+      </form>
+    </div>
+  );
+};
+
+export default UnlockScreen;
@@ -1,10 +1,37 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import { ClipboardItem, AppConfig } from "../types";
+import {
+  ClipboardItem,
+  AppConfig,
+  CombinedSearchResults,
+  DEFAULT_CAPTURE_SETTINGS,
+  DEFAULT_EXCLUSION_RULES,
+  DEFAULT_RETENTION_POLICY,
+  DEFAULT_SENSITIVE_RULES,
+  EncryptionMode,
+  EncryptionStatus,
+  ExportSummary,
+  HistoryPage,
+  HistoryQuery,
+  ImportMode,
+  ImportSummary,
+  PauseStatus,
+  RecoveryNotice,
+  Snippet,
+  SnippetDraft,
+  SnippetFolder,
+  StartupAction,
+  StartupFailure,
+  Tag,
+} from "../types";
+import { errorMessage } from "../utils/error";
 
 export const useClipboard = () => {
   const [clipboardItems, setClipboardItems] = useState<ClipboardItem[]>([]);
+  const [tags, setTags] = useState<Tag[]>([]);
+  const [snippets, setSnippets] = useState<Snippet[]>([]);
+  const [snippetFolders, setSnippetFolders] = useState<SnippetFolder[]>([]);
   const [config, setConfig] = useState<AppConfig>({
     max_history_count: 50,
     hotkey: "CommandOrControl+Shift+V",
@@ -12,7 +39,27 @@ export const useClipboard = () => {
       preset: "default",
     },
     autostart: false,
+    exclusion_rules: DEFAULT_EXCLUSION_RULES,
+    pause_hotkey: null,
+    persist_pause: false,
+    retention: DEFAULT_RETENTION_POLICY,
+    sensitive: DEFAULT_SENSITIVE_RULES,
+    capture: DEFAULT_CAPTURE_SETTINGS,
   });
+  const [pauseStatus, setPauseStatus] = useState<PauseStatus>({
+    paused: false,
+    until: null,
+  });
+  const [encryptionStatus, setEncryptionStatus] = useState<EncryptionStatus>({
+    locked: false,
+    mode: "none",
+  });
+  const [recoveryNotice, setRecoveryNotice] = useState<RecoveryNotice | null>(
+    null
+  );
+  const [startupFailure, setStartupFailure] = useState<StartupFailure | null>(
+    null
+  );
 
   // Get clipboard history
   const loadClipboardHistory = async () => {
@@ -26,6 +73,172 @@ export const useClipboard = () => {
     }
   };
 
+  // Query history with filters, e.g. every item with a tag
+  const queryHistory = async (query: HistoryQuery) => {
+    try {
+      const page = await invoke<HistoryPage>("query_clipboard_history", {
+        query,
+      });
+      return page.items;
+    } catch (error) {
+      console.error("Failed to query clipboard history:", error);
+      return [];
+    }
+  };
+
+  // Get tags
+  const loadTags = async () => {
+    try {
+      setTags(await invoke<Tag[]>("list_tags"));
+    } catch (error) {
+      console.error("Failed to load tags:", error);
+    }
+  };
+
+  // Create a tag; returns an error message on failure
+  const createTag = async (name: string) => {
+    try {
+      await invoke("create_tag", { name });
+      await loadTags();
+      return null;
+    } catch (error) {
+      return errorMessage(error);
+    }
+  };
+
+  // Rename a tag; returns an error message on failure
+  const renameTag = async (id: number, name: string) => {
+    try {
+      await invoke("rename_tag", { id, name });
+      await loadTags();
+      return null;
+    } catch (error) {
+      return errorMessage(error);
+    }
+  };
+
+  // Delete a tag; its items are kept
+  const deleteTag = async (id: number) => {
+    try {
+      await invoke("delete_tag", { id });
+      await loadTags();
+      await loadClipboardHistory();
+    } catch (error) {
+      console.error("Failed to delete tag:", error);
+    }
+  };
+
+  // Assign a tag to an item, or remove it
+  const setItemTag = async (itemId: number, tagId: number, tagged: boolean) => {
+    try {
+      await invoke("set_item_tag", { itemId, tagId, tagged });
+      await loadClipboardHistory();
+    } catch (error) {
+      console.error("Failed to update item tags:", error);
+    }
+  };
+
+  // Get snippets and their folders
+  const loadSnippets = async () => {
+    try {
+      setSnippets(await invoke<Snippet[]>("list_snippets"));
+      setSnippetFolders(await invoke<SnippetFolder[]>("list_snippet_folders"));
+    } catch (error) {
+      console.error("Failed to load snippets:", error);
+    }
+  };
+
+  // Create or edit a snippet; returns an error message on failure
+  const saveSnippet = async (snippet: SnippetDraft, id?: number) => {
+    try {
+      if (id === undefined) {
+        await invoke("create_snippet", { snippet });
+      } else {
+        await invoke("update_snippet", { id, snippet });
+      }
+      await loadSnippets();
+      return null;
+    } catch (error) {
+      return errorMessage(error);
+    }
+  };
+
+  // Save the text of a history item as a snippet
+  const createSnippetFromItem = async (itemId: number) => {
+    try {
+      await invoke("create_snippet_from_item", { itemId });
+      await loadSnippets();
+    } catch (error) {
+      console.error("Failed to create snippet:", error);
+    }
+  };
+
+  // Delete a snippet
+  const deleteSnippet = async (id: number) => {
+    try {
+      await invoke("delete_snippet", { id });
+      await loadSnippets();
+    } catch (error) {
+      console.error("Failed to delete snippet:", error);
+    }
+  };
+
+  // Create a snippet folder; returns an error message on failure
+  const createSnippetFolder = async (name: string) => {
+    try {
+      await invoke("create_snippet_folder", { name });
+      await loadSnippets();
+      return null;
+    } catch (error) {
+      return errorMessage(error);
+    }
+  };
+
+  // Delete a snippet folder; its snippets are kept
+  const deleteSnippetFolder = async (id: number) => {
+    try {
+      await invoke("delete_snippet_folder", { id });
+      await loadSnippets();
+    } catch (error) {
+      console.error("Failed to delete snippet folder:", error);
+    }
+  };
+
+  // Search snippets and history together
+  const searchEverything = async (query: string) => {
+    try {
+      return await invoke<CombinedSearchResults>("search_everything", {
+        query,
+      });
+    } catch (error) {
+      console.error("Failed to search:", error);
+      return { snippets: [], history: [] };
+    }
+  };
+
+  // Copy a snippet and hide window
+  // Names of the fill-in fields to ask for before copying a snippet
+  const getSnippetFields = async (id: number): Promise<string[]> => {
+    try {
+      return await invoke<string[]>("get_snippet_fields", { id });
+    } catch (error) {
+      console.error("Failed to read snippet fields:", error);
+      return [];
+    }
+  };
+
+  const copySnippetAndHide = async (
+    id: number,
+    fields?: Record<string, string>
+  ) => {
+    try {
+      await invoke("copy_snippet_and_hide", { id, fields: fields ?? null });
+    } catch (error) {
+      console.error("Failed to copy snippet:", error);
+      alert("Failed to copy to clipboard. Please try again.");
+    }
+  };
+
   // Get configuration
   const loadConfig = async () => {
     try {
@@ -36,6 +249,12 @@ export const useClipboard = () => {
         theme: {
           preset: "default",
         },
+        exclusion_rules: DEFAULT_EXCLUSION_RULES,
+        pause_hotkey: null,
+        persist_pause: false,
+        retention: DEFAULT_RETENTION_POLICY,
+        sensitive: DEFAULT_SENSITIVE_RULES,
+        capture: DEFAULT_CAPTURE_SETTINGS,
       });
     } catch (error) {
       console.error("Failed to load config:", error);
@@ -47,17 +266,190 @@ export const useClipboard = () => {
         theme: {
           preset: "default",
         },
+        exclusion_rules: DEFAULT_EXCLUSION_RULES,
+        pause_hotkey: null,
+        persist_pause: false,
+        retention: DEFAULT_RETENTION_POLICY,
+        sensitive: DEFAULT_SENSITIVE_RULES,
+        capture: DEFAULT_CAPTURE_SETTINGS,
       });
     }
   };
 
-  // Copy item to clipboard
-  const copyToClipboard = async (
-    content: string,
-    contentType: string = "text"
+  // Get capture pause status
+  const loadPauseStatus = async () => {
+    try {
+      setPauseStatus(await invoke<PauseStatus>("get_pause_status"));
+    } catch (error) {
+      console.error("Failed to load pause status:", error);
+    }
+  };
+
+  // Pause capture, for a number of minutes or until resumed
+  const pauseCapture = async (minutes?: number) => {
+    try {
+      setPauseStatus(await invoke<PauseStatus>("pause_capture", { minutes }));
+    } catch (error) {
+      console.error("Failed to pause capture:", error);
+    }
+  };
+
+  // Resume capture
+  const resumeCapture = async () => {
+    try {
+      setPauseStatus(await invoke<PauseStatus>("resume_capture"));
+    } catch (error) {
+      console.error("Failed to resume capture:", error);
+    }
+  };
+
+  // Load everything that is stored in the database
+  const loadAll = () => {
+    loadClipboardHistory();
+    loadConfig();
+    loadPauseStatus();
+    loadTags();
+    loadSnippets();
+    loadRecoveryNotice();
+  };
+
+  // The backend keeps the notice until it has been fetched once
+  const loadRecoveryNotice = async () => {
+    try {
+      const notice = await invoke<RecoveryNotice | null>(
+        "take_recovery_notice"
+      );
+      if (notice) {
+        setRecoveryNotice(notice);
+      }
+    } catch (error) {
+      console.error("Failed to load recovery notice:", error);
+    }
+  };
+
+  const dismissRecoveryNotice = () => {
+    setRecoveryNotice(null);
+  };
+
+  // Open an encrypted database; returns an error message on failure.
+  // A startup failure after the unlock is shown with its recovery actions.
+  const unlockDatabase = async (passphrase: string) => {
+    try {
+      await invoke("unlock_database", { passphrase });
+      setEncryptionStatus(
+        await invoke<EncryptionStatus>("get_encryption_status")
+      );
+      loadAll();
+      return null;
+    } catch (error) {
+      const failure = await invoke<StartupFailure | null>(
+        "get_startup_failure"
+      ).catch(() => null);
+      if (failure) {
+        setStartupFailure(failure);
+      }
+      return errorMessage(error);
+    }
+  };
+
+  // Check the encryption and load everything once the database is open
+  const loadWhenUnlocked = async () => {
+    try {
+      const status = await invoke<EncryptionStatus>("get_encryption_status");
+      setEncryptionStatus(status);
+      if (!status.locked) {
+        loadAll();
+      }
+    } catch (error) {
+      console.error("Failed to load encryption status:", error);
+      loadAll();
+    }
+  };
+
+  // Run a recovery action after a failed startup; a new failure replaces the old one
+  const recoverStartup = async (action: StartupAction) => {
+    try {
+      await invoke("recover_startup", { action });
+      setStartupFailure(null);
+      loadWhenUnlocked();
+    } catch (error) {
+      console.error("Startup recovery failed:", error);
+      setStartupFailure(
+        await invoke<StartupFailure | null>("get_startup_failure")
+      );
+    }
+  };
+
+  // Show the data directory; returns an error message on failure
+  const openDataFolder = async () => {
+    try {
+      await invoke("open_data_folder");
+      return null;
+    } catch (error) {
+      return errorMessage(error);
+    }
+  };
+
+  // Encrypt, re-key or decrypt the database; returns an error message on failure
+  const setDatabaseEncryption = async (
+    mode: EncryptionMode,
+    passphrase?: string
   ) => {
     try {
-      await invoke("copy_to_clipboard", { content, contentType });
+      setEncryptionStatus(
+        await invoke<EncryptionStatus>("set_database_encryption", {
+          mode,
+          passphrase,
+        })
+      );
+      return null;
+    } catch (error) {
+      console.error("Failed to change database encryption:", error);
+      return errorMessage(error);
+    }
+  };
+
+  // Export the history and settings; returns a message to show
+  const exportData = async (): Promise<string> => {
+    try {
+      const summary = await invoke<ExportSummary>("export_data");
+      return `Exported ${summary.items} items to ${summary.path}`;
+    } catch (error) {
+      console.error("Failed to export data:", error);
+      return errorMessage(error);
+    }
+  };
+
+  // Import an export file; returns a message to show
+  const importData = async (path: string, mode: ImportMode): Promise<string> => {
+    try {
+      const summary = await invoke<ImportSummary>("import_data", {
+        path,
+        mode,
+      });
+      await Promise.all([
+        loadClipboardHistory(),
+        loadTags(),
+        loadSnippets(),
+        loadConfig(),
+      ]);
+      return (
+        `Imported ${summary.imported} items, ${summary.merged} already in the history` +
+        (summary.snippets > 0 ? `, ${summary.snippets} snippets` : "") +
+        (summary.trimmed > 0
+          ? `, ${summary.trimmed} removed by the history limits`
+          : "")
+      );
+    } catch (error) {
+      console.error("Failed to import data:", error);
+      return errorMessage(error);
+    }
+  };
+
+  // Copy item to clipboard
+  const copyToClipboard = async (id: number) => {
+    try {
+      await invoke("copy_to_clipboard", { id });
       // Reload history to show the copied item
       await loadClipboardHistory();
     } catch (error) {
@@ -68,9 +460,9 @@ export const useClipboard = () => {
   };
 
   // Copy item and hide window
-  const copyAndHide = async (content: string, contentType: string = "text") => {
+  const copyAndHide = async (id: number) => {
     try {
-      await invoke("copy_and_hide", { content, contentType });
+      await invoke("copy_and_hide", { id });
     } catch (error) {
       console.error("Failed to copy and hide:", error);
       // Show user-friendly error message
@@ -98,13 +490,25 @@ export const useClipboard = () => {
     }
   };
 
-  // Save configuration
+  // Mark an item as sensitive so it expires, or keep it again
+  const setItemSensitive = async (id: number, sensitive: boolean) => {
+    try {
+      await invoke("set_item_sensitive", { id, sensitive });
+      await loadClipboardHistory();
+    } catch (error) {
+      console.error("Failed to mark item as sensitive:", error);
+    }
+  };
+
+  // Save configuration; returns an error message on failure
   const saveConfig = async (newConfig: AppConfig) => {
     try {
       await invoke("update_config", { config: newConfig });
       setConfig(newConfig);
+      return null;
     } catch (error) {
       console.error("Failed to save config:", error);
+      return errorMessage(error);
     }
   };
 
@@ -128,16 +532,37 @@ export const useClipboard = () => {
 
   // Initialize
   useEffect(() => {
-    loadClipboardHistory();
-    loadConfig();
+    // A failed start is shown with its recovery actions; otherwise an encrypted
+    // database stays locked until the passphrase is entered
+    invoke<StartupFailure | null>("get_startup_failure")
+      .then((failure) => {
+        if (failure) {
+          setStartupFailure(failure);
+        } else {
+          loadWhenUnlocked();
+        }
+      })
+      .catch((error) => {
+        console.error("Failed to load startup status:", error);
+        loadWhenUnlocked();
+      });
 
     // Clipboard update listener
     const unlistenClipboard = listen("clipboard-updated", () => {
       loadClipboardHistory();
     });
 
+    // Pause changes from the hotkey or when a timed pause ends
+    const unlistenPause = listen<PauseStatus>(
+      "capture-pause-changed",
+      (event) => {
+        setPauseStatus(event.payload);
+      }
+    );
+
     return () => {
       unlistenClipboard.then((fn) => fn());
+      unlistenPause.then((fn) => fn());
     };
   }, []);
 
@@ -146,10 +571,40 @@ export const useClipboard = () => {
     config,
     setConfig,
     loadClipboardHistory,
+    queryHistory,
+    tags,
+    createTag,
+    renameTag,
+    deleteTag,
+    setItemTag,
+    snippets,
+    snippetFolders,
+    saveSnippet,
+    createSnippetFromItem,
+    deleteSnippet,
+    createSnippetFolder,
+    deleteSnippetFolder,
+    searchEverything,
+    getSnippetFields,
+    copySnippetAndHide,
+    pauseStatus,
+    pauseCapture,
+    resumeCapture,
+    startupFailure,
+    recoverStartup,
+    openDataFolder,
+    encryptionStatus,
+    unlockDatabase,
+    setDatabaseEncryption,
+    recoveryNotice,
+    dismissRecoveryNotice,
+    exportData,
+    importData,
     copyToClipboard,
     copyAndHide,
     deleteItem,
     togglePin,
+    setItemSensitive,
     saveConfig,
     hideWindow,
     exitApp,
@@ -1,20 +1,20 @@
 import { useEffect } from "react";
-import { ClipboardItem } from "../types";
+import { ClipboardItem, ListTab } from "../types";
 
 interface UseKeyboardNavigationProps {
   showSettings: boolean;
   selectedIndex: number;
   setSelectedIndex: (index: number) => void;
   filteredItems: ClipboardItem[];
-  copyAndHide: (content: string, contentType: string) => void;
+  copyAndHide: (id: number) => void;
   deleteItem: (id: number) => void;
   togglePin: (id: number) => void;
   hideWindow: () => void;
   setShowSettings: (show: boolean) => void;
   resetSearch: () => void;
   setShowExitModal: (show: boolean) => void;
-  activeTab: "pinned" | "history";
-  handleTabChange: (tab: "pinned" | "history") => void;
+  activeTab: ListTab;
+  handleTabChange: (tab: ListTab) => void;
 }
 
 export const useKeyboardNavigation = ({
@@ -50,8 +50,8 @@ export const useKeyboardNavigation = ({
       // Tab switching with Ctrl+Tab
       if (event.ctrlKey && event.key === "Tab") {
         event.preventDefault();
-        const nextTab = activeTab === "history" ? "pinned" : "history";
-        handleTabChange(nextTab);
+        const tabs: ListTab[] = ["history", "pinned", "snippets"];
+        handleTabChange(tabs[(tabs.indexOf(activeTab) + 1) % tabs.length]);
         return;
       }
 
@@ -70,7 +70,7 @@ export const useKeyboardNavigation = ({
         event.preventDefault();
         if (filteredItems[selectedIndex]) {
           const item = filteredItems[selectedIndex];
-          copyAndHide(item.content, item.content_type);
+          copyAndHide(item.id);
           resetSearch(); // Reset search
         }
         return;
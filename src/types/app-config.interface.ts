@@ -1,8 +1,157 @@
 import { ThemeConfig } from "./theme";
+import { ContentType } from "./clipboard-item.interface";
 
 export interface AppConfig {
   max_history_count: number;
   hotkey: string;
   theme: ThemeConfig;
   autostart: boolean;
+  exclusion_rules: ExclusionRules;
+  pause_hotkey: string | null; // toggles capture pause
+  persist_pause: boolean; // keep capture paused across restarts
+  retention: RetentionPolicy;
+  sensitive: SensitiveRules;
+  capture: CaptureSettings;
+}
+
+// Limits on top of max_history_count; pinned items are never deleted
+export interface RetentionPolicy {
+  max_age_days: number | null; // since last copied
+  max_total_bytes: number | null;
+  max_items_per_type: Partial<Record<ContentType, number>>;
+}
+
+export const DEFAULT_RETENTION_POLICY: RetentionPolicy = {
+  max_age_days: null,
+  max_total_bytes: null,
+  max_items_per_type: {},
+};
+
+// How clipboard changes are picked up
+export interface CaptureSettings {
+  poll_interval_ms: number; // only used when change notifications are unavailable
+  primary_selection: boolean; // also capture the selection pasted with a middle click
+  selection_debounce_ms: number; // wait this long after the selection stops changing
+  write_back: WriteBack; // where copying an item puts it
+  own_writes: OwnWritePolicy; // what copying an item does to the history
+}
+
+export type WriteBack = "clipboard" | "primary" | "both";
+
+// Copying an item never creates a new one; it only keeps its id and metadata
+export type OwnWritePolicy = "ignore" | "move-to-top";
+
+export const DEFAULT_CAPTURE_SETTINGS: CaptureSettings = {
+  poll_interval_ms: 1000,
+  primary_selection: false,
+  selection_debounce_ms: 300,
+  write_back: "clipboard",
+  own_writes: "move-to-top",
+};
+
+export const WRITE_BACK_DISPLAY_NAMES: Record<WriteBack, string> = {
+  clipboard: "Clipboard",
+  primary: "Primary selection",
+  both: "Clipboard and primary selection",
+};
+
+export interface PauseStatus {
+  paused: boolean;
+  until: string | null; // capture resumes on its own at this time
+}
+
+export type EncryptionMode = "none" | "passphrase" | "key-file";
+
+export interface EncryptionStatus {
+  locked: boolean; // waiting for the passphrase entered at startup
+  mode: EncryptionMode;
+}
+
+export const ENCRYPTION_MODE_DISPLAY_NAMES: Record<EncryptionMode, string> = {
+  none: "Not encrypted",
+  passphrase: "Passphrase",
+  "key-file": "Key file",
+};
+
+export type SecretKind =
+  | "credit-card"
+  | "jwt"
+  | "aws-key"
+  | "private-key"
+  | "one-time-code";
+
+// Clipboard content matching these rules is never saved
+export interface ExclusionRules {
+  patterns: string[]; // regular expressions
+  detectors: SecretKind[];
+  max_length: number | null; // characters
+  respect_concealed_hint: boolean;
+  apps: string[]; // process names or window classes
+}
+
+export const SECRET_KIND_DISPLAY_NAMES: Record<SecretKind, string> = {
+  "credit-card": "Credit card numbers",
+  jwt: "JSON Web Tokens",
+  "aws-key": "AWS access keys",
+  "private-key": "Private keys",
+  "one-time-code": "One-time codes",
+};
+
+export const DEFAULT_EXCLUSION_RULES: ExclusionRules = {
+  patterns: [],
+  detectors: ["credit-card", "jwt", "aws-key", "private-key"],
+  max_length: null,
+  respect_concealed_hint: true,
+  apps: [],
+};
+
+// Matching content is kept, but deleted (and cleared from the clipboard) after a while
+export interface SensitiveRules {
+  patterns: string[]; // regular expressions
+  detectors: SecretKind[];
+  expire_after_secs: number;
+}
+
+export const DEFAULT_SENSITIVE_RULES: SensitiveRules = {
+  patterns: [],
+  detectors: ["one-time-code"],
+  expire_after_secs: 120,
+};
+
+// How an imported archive is combined with the current history
+export type ImportMode = "merge" | "replace";
+
+export interface ImportSummary {
+  imported: number;
+  merged: number; // already in the history
+  trimmed: number; // removed by the history limit or retention policy
+  snippets: number; // snippets added
+}
+
+export interface ExportSummary {
+  path: string;
+  items: number;
+}
+
+// Why the application could not start, shown instead of the history
+export type StartupErrorCode =
+  | "data-dir"
+  | "database"
+  | "database-too-new"
+  | "config";
+
+export type StartupAction = "retry" | "reset-config" | "move-database-aside";
+
+export interface StartupFailure {
+  code: StartupErrorCode;
+  message: string;
+  data_dir: string | null; // can be opened with open_data_folder
+  actions: StartupAction[];
+}
+
+// Shown once after a damaged database was replaced at startup
+export interface RecoveryNotice {
+  reason: string;
+  backup_time: string | null; // null when no backup could be used
+  damaged_copy: string;
 }
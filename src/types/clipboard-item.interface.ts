@@ -1,7 +1,75 @@
-export interface ClipboardItem {
+export type ContentType = "text" | "html" | "rtf" | "image" | "files";
+
+// "primary" is the X11 selection pasted with a middle click
+export type Selection = "clipboard" | "primary";
+
+// Tagged by `content_type`; the remaining fields depend on the type
+export type ClipboardContent =
+  | { content_type: "text"; content: string }
+  | { content_type: "html"; content: string }
+  | { content_type: "rtf"; content: string }
+  | ({ content_type: "image" } & ImageInfo)
+  | { content_type: "files"; files: string[] }; // URIs
+
+export type ClipboardItem = ClipboardContent & {
   id: number;
-  content: string;
-  content_type: string;
-  timestamp: string;
+  timestamp: string; // first captured
   pinned: boolean;
+  last_used: string; // history is ordered by this
+  copy_count: number;
+  expires_at: string | null; // sensitive items are deleted at this time
+  tag_ids: number[];
+  selection: Selection; // where it was last captured from
+  source_app: SourceApp | null; // application it was last copied in
+};
+
+// The application with the focused window at the time of the copy
+export interface SourceApp {
+  process: string | null; // executable name
+  class: string | null; // window class
+  title: string | null;
+}
+
+// Tagged items are kept when the history is trimmed
+export interface Tag {
+  id: number;
+  name: string;
+}
+
+// Thumbnail only; fetch the full image with `get_full_image`
+export interface ImageInfo {
+  width: number;
+  height: number;
+  mime: string;
+  thumbnail: string; // Base64 PNG
+}
+
+export interface SearchResult {
+  item: ClipboardItem;
+  // Matched terms are wrapped in <mark></mark>; content is not HTML-escaped
+  snippet: string;
+  rank: number;
+}
+
+export type SortOrder = "newest-first" | "oldest-first";
+
+// Lists shown in the main window
+export type ListTab = "history" | "pinned" | "snippets";
+
+export interface HistoryQuery {
+  content_type?: ContentType;
+  pinned?: boolean;
+  tag_id?: number;
+  selection?: Selection;
+  source_app?: string; // process name or window class
+  since?: string;
+  until?: string;
+  sort?: SortOrder;
+  cursor?: string;
+  limit?: number;
+}
+
+export interface HistoryPage {
+  items: ClipboardItem[];
+  next_cursor: string | null;
 }
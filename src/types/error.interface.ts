@@ -0,0 +1,26 @@
+// Stable codes of the errors returned by the backend commands
+export type ErrorCode =
+  | "not-found"
+  | "invalid-input"
+  | "already-exists"
+  | "database-locked"
+  | "database-busy"
+  | "wrong-passphrase"
+  | "database-too-new"
+  | "database-damaged"
+  | "database"
+  | "invalid-hotkey"
+  | "hotkey-in-use"
+  | "clipboard"
+  | "window"
+  | "autostart"
+  | "file"
+  | "invalid-archive"
+  | "data-dir";
+
+// Error returned by every backend command
+export interface CommandError {
+  code: ErrorCode;
+  message: string; // shown to the user
+  detail: string | null; // underlying cause, for logs and tooltips
+}
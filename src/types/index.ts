@@ -1,3 +1,5 @@
 export * from "./clipboard-item.interface";
 export * from "./app-config.interface";
+export * from "./snippet.interface";
+export * from "./error.interface";
 export * from "./theme";
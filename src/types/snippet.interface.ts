@@ -0,0 +1,37 @@
+import { SearchResult } from "./clipboard-item.interface";
+
+// Kept apart from the history and never trimmed
+export interface Snippet {
+  id: number;
+  name: string;
+  abbreviation: string | null; // unique keyword, ignoring case
+  content: string;
+  folder_id: number | null;
+  created_at: string;
+  updated_at: string;
+}
+
+// Fields sent when creating or editing a snippet
+export interface SnippetDraft {
+  name: string;
+  abbreviation: string | null;
+  content: string;
+  folder_id: number | null;
+}
+
+export interface SnippetFolder {
+  id: number;
+  name: string;
+}
+
+export interface SnippetSearchResult {
+  snippet: Snippet;
+  // Matched terms are wrapped in <mark></mark>; content is not HTML-escaped
+  excerpt: string;
+  rank: number;
+}
+
+export interface CombinedSearchResults {
+  snippets: SnippetSearchResult[];
+  history: SearchResult[];
+}
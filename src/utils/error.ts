@@ -0,0 +1,18 @@
+import type { CommandError } from "../types/error.interface";
+
+/**
+ * Check whether a rejected invoke carries a backend command error
+ * @param error - The value the invoke was rejected with
+ */
+export const isCommandError = (error: unknown): error is CommandError =>
+  typeof error === "object" &&
+  error !== null &&
+  "code" in error &&
+  "message" in error;
+
+/**
+ * Message to show the user for a failed invoke
+ * @param error - The value the invoke was rejected with
+ */
+export const errorMessage = (error: unknown): string =>
+  isCommandError(error) ? error.message : String(error);
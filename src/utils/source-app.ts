@@ -0,0 +1,8 @@
+import type { ClipboardItem } from "../types/clipboard-item.interface";
+
+/**
+ * Name of the application an item was copied in, as the backend filters by it
+ * @param item - History item
+ */
+export const sourceAppName = (item: ClipboardItem): string | null =>
+  item.source_app?.process ?? item.source_app?.class ?? null;